@@ -59,6 +59,15 @@ impl LayerMap {
   }
 }
 
+/// the sub-package of module in layer, like "com.feakin.domain" and "Order" => "com.feakin.domain.order"
+pub fn module_package(layer_package: &str, module: &str) -> String {
+  if layer_package.is_empty() {
+    return module.to_lowercase();
+  }
+
+  format!("{}.{}", layer_package, module.to_lowercase())
+}
+
 /// convert java package to path
 /// Unix: "com.feakin.fklang" => "src/main/java/com/feakin.fklang"
 /// Windows: "com.feakin.fklang" => "src\\main\\java\\com\\feakin.fklang"
@@ -71,13 +80,19 @@ pub fn java_package_to_path(package: &str) -> String {
 #[cfg(test)]
 mod tests {
   use fkl_mir::{Layer, LayeredArchitecture};
-  use crate::builtin::funcs::{java_package_to_path, LayerMap};
+  use crate::builtin::funcs::{java_package_to_path, LayerMap, module_package};
 
   #[test]
   fn package_convert() {
     assert_eq!(java_package_to_path("com.feakin.fklang"), "src/main/java/com/feakin/fklang")
   }
 
+  #[test]
+  fn module_package_in_layer() {
+    assert_eq!(module_package("com.feakin.domain", "Order"), "com.feakin.domain.order");
+    assert_eq!(module_package("", "order"), "order");
+  }
+
   #[test]
   fn package_convert_with_trailing_slash() {
     let layer_map = LayerMap::from(LayeredArchitecture {
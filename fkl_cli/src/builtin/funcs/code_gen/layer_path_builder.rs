@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use crate::builtin::funcs::LayerMap;
+use crate::builtin::funcs::{java_package_to_path, LayerMap};
 
 pub struct LayerPathBuilder {}
 
@@ -18,4 +18,11 @@ impl LayerPathBuilder {
 
     format!("{}", target_path.display())
   }
+
+  pub fn package(base: &PathBuf, package: &str, class_name: &str) -> PathBuf {
+    let mut target_path = base.clone();
+    target_path.push(java_package_to_path(package));
+    target_path.push(format!("{}.java", class_name));
+    target_path
+  }
 }
@@ -1,16 +1,18 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use indexmap::IndexMap;
 use log::info;
 
-use fkl_codegen_java::gen_http_api;
-use fkl_mir::{ContextMap, Implementation};
+use fkl_codegen_java::{gen_enum, gen_http_api, gen_state_enum, gen_value_object, state_enum_name};
+use fkl_mir::{ContextMap, Implementation, ValueObject};
 
 use crate::deconstruct::code_construct::CodeConstruct;
 use crate::deconstruct::java_construct::JavaConstruct;
 use crate::builtin::funcs;
 use crate::builtin::funcs::LayerMap;
 use crate::builtin::funcs::LayerPathBuilder;
+use crate::builtin::funcs::module_package;
 use crate::inserter::inserter::Inserter;
 use crate::inserter::java_inserter::JavaInserter;
 
@@ -31,13 +33,20 @@ pub enum DddLayer {
   Infrastructure,
 }
 
-pub fn code_gen_by_path(input_path: &PathBuf, filter_impl: Option<String>, base_path: &PathBuf) {
+pub fn code_gen_by_path(input_path: &Path, filter_impl: Option<String>, base_path: &PathBuf) {
   let mir = funcs::mir_from_file(input_path);
   code_gen_by_mir(&mir, filter_impl, base_path);
 }
 
 // todo: extract to a separate module
 pub fn code_gen_by_mir(mir: &ContextMap, filter_impl: Option<String>, base_path: &PathBuf) {
+  if filter_impl.is_none() && mir.layered.is_some() {
+    let layer_map = LayerMap::from(mir.layered.clone().unwrap());
+    gen_value_objects(mir, &layer_map, base_path);
+    gen_enums(mir, &layer_map, base_path);
+    gen_state_enums(mir, &layer_map, base_path);
+  }
+
   let code_blocks = collect_codes(filter_impl, &mir);
   let has_layered_define = mir.layered.is_some();
   if !code_blocks.is_empty() {
@@ -68,6 +77,95 @@ pub fn code_gen_by_mir(mir: &ContextMap, filter_impl: Option<String>, base_path:
   }
 }
 
+/// generate the value objects as embeddable classes in domain layer, skip the existing ones.
+fn gen_value_objects(mir: &ContextMap, layer_map: &LayerMap, base_path: &PathBuf) {
+  for vo in collect_value_objects(mir).values() {
+    let package = domain_package(mir, layer_map, &vo.owner);
+    let path = LayerPathBuilder::package(base_path, &package, &vo.name);
+    if path.exists() {
+      info!("value object {} already exists: {}", vo.name, path.display());
+      continue;
+    }
+
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent).unwrap();
+    }
+
+    fs::write(&path, gen_value_object(vo, &package)).unwrap();
+    info!("generated value object {} to {}", vo.name, path.display());
+  }
+}
+
+/// generate the enums as Java enums in domain layer, skip the existing ones.
+fn gen_enums(mir: &ContextMap, layer_map: &LayerMap, base_path: &PathBuf) {
+  for enum_ in &mir.enums {
+    let package = domain_package(mir, layer_map, &enum_.owner);
+    let path = LayerPathBuilder::package(base_path, &package, &enum_.name);
+    if path.exists() {
+      info!("enum {} already exists: {}", enum_.name, path.display());
+      continue;
+    }
+
+    if let Some(parent) = path.parent() {
+      fs::create_dir_all(parent).unwrap();
+    }
+
+    fs::write(&path, gen_enum(enum_, &package)).unwrap();
+    info!("generated enum {} to {}", enum_.name, path.display());
+  }
+}
+
+/// generate the states of entities as Java enums in domain layer, skip the existing ones.
+fn gen_state_enums(mir: &ContextMap, layer_map: &LayerMap, base_path: &PathBuf) {
+  for aggregate in mir.contexts.iter().flat_map(|bc| bc.aggregates.iter()) {
+    for entity in &aggregate.entities {
+      let package = domain_package(mir, layer_map, &aggregate.name);
+      let code = match gen_state_enum(entity, &package) {
+        Some(code) => code,
+        None => continue,
+      };
+
+      let path = LayerPathBuilder::package(base_path, &package, &state_enum_name(entity));
+      if path.exists() {
+        info!("state enum of {} already exists: {}", entity.name, path.display());
+        continue;
+      }
+
+      if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).unwrap();
+      }
+
+      fs::write(&path, code).unwrap();
+      info!("generated state enum of {} to {}", entity.name, path.display());
+    }
+  }
+}
+
+/// the classes of aggregates in a module are generated into the sub-package of module.
+fn domain_package(mir: &ContextMap, layer_map: &LayerMap, aggregate: &str) -> String {
+  match mir.contexts.iter().find_map(|context| context.module_of(aggregate)) {
+    Some(module) => module_package(&layer_map.domain, &module.name),
+    None => layer_map.domain.clone(),
+  }
+}
+
+/// collect all value objects by name, the nested ones in entities and aggregates first.
+fn collect_value_objects(mir: &ContextMap) -> IndexMap<String, ValueObject> {
+  let mut value_objects: IndexMap<String, ValueObject> = IndexMap::new();
+  for aggregate in mir.contexts.iter().flat_map(|bc| bc.aggregates.iter()) {
+    let nested = aggregate.entities.iter().flat_map(|entity| entity.value_objects.iter());
+    for vo in nested.chain(aggregate.value_objects.iter()) {
+      value_objects.entry(vo.name.clone()).or_insert_with(|| vo.clone());
+    }
+  }
+
+  for vo in &mir.value_objects {
+    value_objects.entry(vo.name.clone()).or_insert_with(|| vo.clone());
+  }
+
+  value_objects
+}
+
 /// collect codes for generate.
 fn collect_codes(filter_impl: Option<String>, mir: &ContextMap) -> Vec<CodeBlock> {
   let mut codes: Vec<CodeBlock> = vec![];
@@ -102,3 +200,55 @@ fn collect_codes(filter_impl: Option<String>, mir: &ContextMap) -> Vec<CodeBlock
 
   codes
 }
+
+#[cfg(test)]
+mod tests {
+  use crate::builtin::funcs::code_gen::{collect_value_objects, domain_package};
+  use crate::builtin::funcs::LayerMap;
+  use crate::builtin::funcs::mir_from_str;
+
+  #[test]
+  fn collect_nested_and_top_level_value_objects() {
+    let context_map = mir_from_str("
+Context Cart {
+  Aggregate Cart {
+    Entity Cart {
+      ValueObject CartId
+    }
+    ValueObject Money;
+  }
+}
+
+ValueObject Money {
+  constructor(amount: BigDecimal, currency: String)
+}
+    ");
+
+    let value_objects = collect_value_objects(&context_map);
+    assert_eq!(value_objects.keys().collect::<Vec<_>>(), vec!["CartId", "Money"]);
+    assert_eq!(value_objects["Money"].fields.len(), 2);
+  }
+
+  #[test]
+  fn module_sub_package() {
+    let context_map = mir_from_str("
+Context Ordering {
+  Module order {
+    Aggregate Order {
+      ValueObject Money;
+    }
+  }
+  Aggregate Customer;
+}
+    ");
+
+    let layer_map = LayerMap {
+      domain: "com.feakin.domain".to_string(),
+      ..Default::default()
+    };
+
+    assert_eq!(domain_package(&context_map, &layer_map, "Order"), "com.feakin.domain.order");
+    assert_eq!(domain_package(&context_map, &layer_map, "Customer"), "com.feakin.domain");
+    assert_eq!(domain_package(&context_map, &layer_map, ""), "com.feakin.domain");
+  }
+}
@@ -1,19 +1,22 @@
-use log::info;
+use log::{info, warn};
 use reqwest::blocking::{Client, Response};
 use reqwest::header;
 use reqwest::header::HeaderMap;
 
-use fkl_mir::{ContextMap, HttpApiImpl, HttpEndpoint, HttpMethod, Implementation, Struct};
+use fkl_mir::{ContextMap, Field, HttpApiImpl, HttpEndpoint, HttpMethod, Implementation, Struct};
 use fkl_mir::authorization::HttpAuthorization;
 
 use crate::highlighter::Highlighter;
 use crate::mock::fake_user_agent::UserAgent;
 use crate::mock::fake_value::FakeValue;
+use crate::mock::request_validator::validate_body;
 use crate::RunFuncName;
 
 pub struct EndpointRunner {
   endpoint: HttpEndpoint,
   request_struct: Option<Struct>,
+  /// the declared structs of response variants, by their names
+  response_structs: Vec<(String, Struct)>,
 }
 
 pub(crate) fn endpoint_runner(context_map: &ContextMap, func_name: &RunFuncName, impl_name: &str) {
@@ -44,6 +47,12 @@ pub(crate) fn endpoint_runner(context_map: &ContextMap, func_name: &RunFuncName,
         }
       }
 
+      for variant in endpoint.response_variants() {
+        if let Some(struct_) = context_map.get_struct(&variant.name) {
+          runner.response_structs.push((variant.name, struct_));
+        }
+      }
+
       runner.send_request().expect("TODO: panic message");
     }
     _ => {}
@@ -55,6 +64,7 @@ impl EndpointRunner {
     EndpointRunner {
       endpoint,
       request_struct: None,
+      response_structs: vec![],
     }
   }
 
@@ -73,26 +83,55 @@ impl EndpointRunner {
   }
 
   fn handle_response(&self, resp: Response) {
-    let content_type = resp.headers().get("content-type").unwrap().to_str().unwrap();
-    info!("Content-Type: {}", content_type);
-    match content_type {
-      "application/json" => {
-        let json: serde_json::Value = resp.json().expect("Failed to parse response");
-        Highlighter::json(&json.to_string());
-      }
-      "text/plain; charset=utf-8" => {
-        let text = resp.text().unwrap();
-        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&text) {
-          Highlighter::json(&serde_json::to_string_pretty(&json).unwrap());
-        } else {
-          println!("{}", text);
+    let status = resp.status().as_u16();
+    let content_type = resp.headers().get(header::CONTENT_TYPE)
+      .and_then(|value| value.to_str().ok())
+      .map(|value| value.to_string());
+    info!("Status: {}, Content-Type: {:?}", status, content_type);
+
+    let text = resp.text().expect("Failed to parse response");
+    let json = serde_json::from_str::<serde_json::Value>(&text).ok();
+    for problem in self.validate_response(status, content_type.as_deref(), json.as_ref()) {
+      warn!("response mismatch: {}", problem);
+    }
+
+    match json {
+      Some(json) => Highlighter::json(&serde_json::to_string_pretty(&json).unwrap()),
+      None => println!("{}", text),
+    }
+  }
+
+  /// check the response is one of declared variants, in its content type and with the required fields
+  pub fn validate_response(&self, status: u16, content_type: Option<&str>, body: Option<&serde_json::Value>) -> Vec<String> {
+    let variant = match self.endpoint.response_of(status) {
+      Some(variant) => variant,
+      None if self.endpoint.responses.is_empty() && self.endpoint.response.is_none() => return vec![],
+      None => return vec![format!("status {} is not declared", status)],
+    };
+
+    let mut problems = vec![];
+    let media_type = content_type.map(|it| it.split(';').next().unwrap_or("").trim());
+    if media_type.is_some_and(|it| !it.eq_ignore_ascii_case(variant.media_type())) {
+      problems.push(format!("content type of status {} should be {}, but {}", status, variant.media_type(), content_type.unwrap_or("")));
+    }
+
+    // a `204 No Content` response has no body to check
+    let struct_ = self.response_structs.iter().find(|(name, _)| name == &variant.name).filter(|_| status != 204);
+    if let Some((_, struct_)) = struct_ {
+      match body {
+        Some(body) => {
+          for field in struct_.fields.iter().filter(|field| HttpEndpoint::is_required(field)) {
+            if body.get(&field.name).is_none_or(|value| value.is_null()) {
+              problems.push(format!("field {} of {} is missing", field.name, struct_.name));
+            }
+          }
+          problems.extend(validate_body(&struct_.fields, body));
         }
-      }
-      _ => {
-        let text = resp.text().expect("Failed to parse response");
-        println!("{}", text);
+        None => problems.push(format!("body of status {} should be {}", status, struct_.name)),
       }
     }
+
+    problems
   }
 
   fn do_request(&self, headers: HeaderMap, request: Option<String>) -> Response {
@@ -101,10 +140,13 @@ impl EndpointRunner {
       .build()
       .expect("TODO: panic message");
 
+    let url = self.url();
+    info!("url: {}", url);
+
     let resp: Response;
     match self.endpoint.method {
       HttpMethod::GET => {
-        resp = client.get(&self.endpoint.path)
+        resp = client.get(&url)
           .send()
           .expect("Failed to send request");
       }
@@ -113,23 +155,23 @@ impl EndpointRunner {
           info!("body: {:?}", req.clone());
         }
 
-        resp = client.post(&self.endpoint.path)
+        resp = client.post(&url)
           .body(request.unwrap_or("".to_string()))
           .send()
           .expect("Failed to send request");
       }
       HttpMethod::PUT => {
-        resp = client.put(&self.endpoint.path)
+        resp = client.put(&url)
           .send()
           .expect("Failed to send request");
       }
       HttpMethod::DELETE => {
-        resp = client.delete(&self.endpoint.path)
+        resp = client.delete(&url)
           .send()
           .expect("Failed to send request");
       }
       HttpMethod::PATCH => {
-        resp = client.patch(&self.endpoint.path)
+        resp = client.patch(&url)
           .send()
           .expect("Failed to send request");
       }
@@ -160,9 +202,51 @@ impl EndpointRunner {
       }
     }
 
+    for header in self.endpoint.headers.iter().filter(|it| Self::should_send(it)) {
+      let name = header::HeaderName::from_bytes(HttpEndpoint::header_name(header).as_bytes()).expect("invalid header name");
+      headers.insert(name, Self::param_value(header).parse().unwrap());
+    }
+
     headers
   }
 
+  /// the path with the values of path parameters, and the query parameters
+  fn url(&self) -> String {
+    let mut url = self.endpoint.path.clone();
+    for param in &self.endpoint.path_params {
+      url = url.replace(&format!("{{{}}}", param.name), &Self::param_value(param));
+    }
+
+    let query: Vec<String> = self.endpoint.query_params.iter()
+      .filter(|it| Self::should_send(it))
+      .map(|param| format!("{}={}", param.name, Self::param_value(param)))
+      .collect();
+    if !query.is_empty() {
+      url = format!("{}?{}", url, query.join("&"));
+    }
+
+    url
+  }
+
+  /// the optional parameters without default value are not sent
+  fn should_send(param: &Field) -> bool {
+    param.initializer.is_some() || HttpEndpoint::is_required(param)
+  }
+
+  /// the default value of parameter, or a fake value of its type
+  fn param_value(param: &Field) -> String {
+    if let Some(value) = &param.initializer {
+      return value.trim_matches('"').to_string();
+    }
+
+    let fake = FakeValue::fake(&vec![param.clone()]);
+    match fake.get(&param.name).map(|value| serde_json::to_value(value).unwrap()) {
+      Some(serde_json::Value::String(str)) => str,
+      Some(value) => value.to_string(),
+      None => "".to_string(),
+    }
+  }
+
   fn create_request_body(&self) -> Option<String> {
     return if let Some(req) = &self.request_struct {
       let map = FakeValue::fake(&req.fields);
@@ -175,7 +259,9 @@ impl EndpointRunner {
 
 #[cfg(test)]
 mod tests {
-  use fkl_mir::{Field, Request};
+  use fkl_mir::{Field, Request, ResponseVariant};
+  use fkl_parser::parse_type_expr;
+
   use super::*;
 
   #[test]
@@ -189,6 +275,7 @@ mod tests {
       response: None,
       description: "".to_string(),
       auth: None,
+      ..Default::default()
     };
     let runner = EndpointRunner::new(endpoint);
     let _resp = runner.send_request();
@@ -208,6 +295,7 @@ mod tests {
       response: None,
       description: "".to_string(),
       auth: None,
+      ..Default::default()
     };
 
     let mut runner = EndpointRunner::new(endpoint);
@@ -217,9 +305,79 @@ mod tests {
         name: "name".to_string(),
         initializer: None,
         type_type: "String".to_string(),
+        ..Default::default()
       }],
+      ..Default::default()
     });
 
     let _resp = runner.send_request();
   }
+
+  #[test]
+  fn url_with_params() {
+    let mut page = Field::new("page", parse_type_expr("Int").unwrap());
+    page.initializer = Some("2".to_string());
+    let mut id = Field::new("id", parse_type_expr("String").unwrap());
+    id.initializer = Some("\"42\"".to_string());
+
+    let endpoint = HttpEndpoint {
+      path: "http://localhost:8080/orders/{id}".to_string(),
+      path_params: vec![id],
+      query_params: vec![page, Field::new("keyword", parse_type_expr("String?").unwrap())],
+      ..Default::default()
+    };
+
+    let runner = EndpointRunner::new(endpoint);
+    assert_eq!(runner.url(), "http://localhost:8080/orders/42?page=2");
+  }
+
+  #[test]
+  fn validate_response_variants() {
+    let endpoint = HttpEndpoint {
+      path: "/orders".to_string(),
+      responses: vec![ResponseVariant::new(201, "Order"), ResponseVariant::new(404, "")],
+      ..Default::default()
+    };
+
+    let mut runner = EndpointRunner::new(endpoint);
+    runner.response_structs.push(("Order".to_string(), Struct {
+      name: "Order".to_string(),
+      fields: vec![Field::new("id", parse_type_expr("Long").unwrap()), Field::new("note", parse_type_expr("String?").unwrap())],
+      ..Default::default()
+    }));
+
+    let order = serde_json::json!({ "id": 1 });
+    assert!(runner.validate_response(201, Some("application/json; charset=utf-8"), Some(&order)).is_empty());
+    assert!(runner.validate_response(404, None, None).is_empty());
+
+    let problems = runner.validate_response(201, Some("text/plain"), Some(&serde_json::json!({})));
+    assert_eq!(problems, vec![
+      "content type of status 201 should be application/json, but text/plain",
+      "field id of Order is missing",
+    ]);
+    assert_eq!(runner.validate_response(500, None, None), vec!["status 500 is not declared"]);
+  }
+
+  #[test]
+  fn validate_legacy_response() {
+    let endpoint = HttpEndpoint {
+      path: "/users".to_string(),
+      response: Some(fkl_mir::Response { name: "User".to_string(), ..Default::default() }),
+      ..Default::default()
+    };
+
+    let mut runner = EndpointRunner::new(endpoint);
+    runner.response_structs.push(("User".to_string(), Struct {
+      name: "User".to_string(),
+      fields: vec![Field::new("id", parse_type_expr("Long").unwrap())],
+      ..Default::default()
+    }));
+
+    let user = serde_json::json!({ "id": 1 });
+    assert!(runner.validate_response(200, None, Some(&user)).is_empty());
+    assert!(runner.validate_response(201, None, Some(&user)).is_empty());
+    assert!(runner.validate_response(204, None, None).is_empty());
+    assert_eq!(runner.validate_response(201, None, Some(&serde_json::json!({}))), vec!["field id of User is missing"]);
+    assert_eq!(runner.validate_response(404, None, None), vec!["status 404 is not declared"]);
+  }
 }
@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use fkl_mir::LayeredArchitecture;
+use fkl_mir::{BoundedContext, LayeredArchitecture};
 
 use crate::deconstruct::file_resolver::{FileResolver, ResolvedFile};
 use crate::builtin::funcs::layered_guarding::package_guarding::PackageGuarding;
@@ -12,11 +12,12 @@ pub struct LayeredGuardingExec<'p> {
 }
 
 impl LayeredGuardingExec<'_> {
-  pub fn guarding(path: PathBuf, arch: &LayeredArchitecture) -> Vec<String> {
+  pub fn guarding(path: PathBuf, arch: &LayeredArchitecture, contexts: &[BoundedContext]) -> Vec<String> {
     let mut resolver = FileResolver::default();
     resolver.load_dir(&path);
 
-    let guarding = PackageGuarding::from_arch(arch);
+    let mut guarding = PackageGuarding::from_arch(arch);
+    guarding.add_modules(arch, contexts);
 
     let exec = LayeredGuardingExec {
       package_guarding: &guarding,
@@ -58,7 +59,7 @@ mod tests {
 
     let file = mir_from_file(&source);
     let arch = file.layered.unwrap();
-    let errors = LayeredGuardingExec::guarding(base, &arch);
+    let errors = LayeredGuardingExec::guarding(base, &arch, &file.contexts);
 
     assert_eq!(errors.len(), 1);
     assert_eq!(errors[0], "package com.feakin.demo.domain imported com.feakin.demo.rest")
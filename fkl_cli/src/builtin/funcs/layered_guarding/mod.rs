@@ -3,14 +3,14 @@ use std::process;
 
 use log::error;
 
-use fkl_mir::LayeredArchitecture;
+use fkl_mir::{BoundedContext, LayeredArchitecture};
 pub use layered_guarding_exec::LayeredGuardingExec;
 
 pub mod package_guarding;
 pub mod layered_guarding_exec;
 
-pub fn guarding_runner(root: PathBuf, layered: &LayeredArchitecture) {
-  let errors = LayeredGuardingExec::guarding(root, layered);
+pub fn guarding_runner(root: PathBuf, layered: &LayeredArchitecture, contexts: &[BoundedContext]) {
+  let errors = LayeredGuardingExec::guarding(root, layered, contexts);
 
   if errors.len() > 0 {
     for error in errors {
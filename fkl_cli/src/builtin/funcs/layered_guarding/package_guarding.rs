@@ -1,12 +1,14 @@
 use std::collections::HashMap;
-use fkl_mir::LayeredArchitecture;
+use fkl_mir::{BoundedContext, LayeredArchitecture};
 
+use crate::builtin::funcs::{LayerMap, module_package};
 use crate::code_meta::CodeFile;
 
 #[derive(Debug, Clone)]
 pub struct PackageGuarding {
   pub all_layer: Vec<String>,
   pub rules: Vec<PackageRule>,
+  pub modules: Vec<ModuleRule>,
 }
 
 #[derive(Debug, Clone)]
@@ -15,11 +17,21 @@ pub struct PackageRule {
   pub targets: Vec<String>,
 }
 
+/// the module is an isolation unit in the domain layer, it can only import the other modules of the
+/// same context which are in its dependencies.
+#[derive(Debug, Clone)]
+pub struct ModuleRule {
+  pub package: String,
+  pub siblings: Vec<String>,
+  pub dependencies: Vec<String>,
+}
+
 impl PackageGuarding {
   pub fn new() -> Self {
     PackageGuarding {
       all_layer: vec![],
       rules: vec![],
+      modules: vec![],
     }
   }
 
@@ -66,6 +78,25 @@ impl PackageGuarding {
     guarding
   }
 
+  /// add the module rules of contexts, the modules are packaged under the domain layer.
+  pub fn add_modules(&mut self, arch: &LayeredArchitecture, contexts: &[BoundedContext]) {
+    let domain = LayerMap::from(arch.clone()).domain;
+    for context in contexts {
+      let packages: Vec<String> = context.modules.iter()
+        .map(|module| module_package(&domain, &module.name))
+        .collect();
+
+      for module in &context.modules {
+        let package = module_package(&domain, &module.name);
+        self.modules.push(ModuleRule {
+          siblings: packages.iter().filter(|it| **it != package).cloned().collect(),
+          dependencies: module.dependencies.iter().map(|name| module_package(&domain, name)).collect(),
+          package,
+        });
+      }
+    }
+  }
+
   pub fn verify(&self, file: &CodeFile) -> Vec<String> {
     let mut errors: Vec<String> = vec![];
 
@@ -75,6 +106,12 @@ impl PackageGuarding {
       }
     }
 
+    for rule in &self.modules {
+      if is_in_package(&file.package, &rule.package) {
+        self.filter_module_imports(file, &mut errors, rule);
+      }
+    }
+
     return errors;
   }
 
@@ -86,6 +123,22 @@ impl PackageGuarding {
       }
     }
   }
+
+  fn filter_module_imports(&self, file: &CodeFile, errors: &mut Vec<String>, rule: &ModuleRule) {
+    for import in &file.imports {
+      let package_name = package_name(import);
+      let sibling = rule.siblings.iter().find(|sibling| is_in_package(package_name, sibling));
+      if let Some(sibling) = sibling {
+        if !rule.dependencies.contains(sibling) {
+          errors.push(format!("module {} imported module {}", rule.package, sibling));
+        }
+      }
+    }
+  }
+}
+
+fn is_in_package(package: &str, parent: &str) -> bool {
+  package == parent || package.starts_with(&format!("{}.", parent))
 }
 
 pub fn package_name(package_name: &str) -> &str {
@@ -182,4 +235,35 @@ class Demo {}
 
     assert_eq!(errors.len(), 0);
   }
+
+  #[test]
+  fn guarding_module_isolation() {
+    let java_code = r#"
+package com.phodal.domain.shipping;
+
+import com.phodal.domain.order.Order;
+import com.phodal.domain.payment.Payment;
+
+class Demo {}
+"#;
+
+    let file = JavaConstruct::parse(java_code);
+    let context = mir_from_str(&format!("{}{}", sample_layer(), r#"
+Context Ordering {
+  Module order { Aggregate Order; }
+  Module payment { Aggregate Payment; }
+  Module shipping {
+    dependencies: [order];
+    Aggregate Shipment;
+  }
+}
+"#));
+
+    let arch = context.layered.unwrap();
+    let mut guarding = PackageGuarding::from_arch(&arch);
+    guarding.add_modules(&arch, &context.contexts);
+    let errors = guarding.verify(&file);
+
+    assert_eq!(errors, vec!["module com.phodal.domain.shipping imported module com.phodal.domain.payment"]);
+  }
 }
@@ -1,5 +1,4 @@
-use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use log::error;
 
@@ -10,7 +9,7 @@ pub use code_gen::layer_path_builder::*;
 #[allow(unused_imports)]
 pub use datasource_orm::*;
 use fkl_mir::ContextMap;
-use fkl_parser::parse;
+use fkl_parser::{parse, parse_file, parse_files};
 pub use http_request::*;
 pub use layered_guarding::*;
 pub use layered_guarding::layered_guarding_exec::LayeredGuardingExec;
@@ -27,9 +26,23 @@ pub mod datasource_orm;
 pub mod mock_server;
 pub mod custom_function;
 
-pub fn mir_from_file(input_path: &PathBuf) -> ContextMap {
-  let code = fs::read_to_string(input_path).unwrap();
-  mir_from_str(&code)
+pub fn mir_from_file(input_path: &Path) -> ContextMap {
+  let mir: ContextMap = parse_file(input_path).map_err(|e| {
+    error!("{}", e);
+    e
+  }).unwrap();
+
+  mir
+}
+
+/// compile the entry files of workspace as a whole
+pub fn mir_from_files(paths: &[PathBuf]) -> ContextMap {
+  let mir: ContextMap = parse_files(paths).map_err(|e| {
+    error!("{}", e);
+    e
+  }).unwrap();
+
+  mir
 }
 
 pub fn mir_from_str(code: &str) -> ContextMap {
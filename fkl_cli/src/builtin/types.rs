@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 use rocket::serde::{Deserialize, Serialize};
 
+use fkl_mir::TypeExpr;
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum BuiltinType {
   Any,
@@ -53,6 +55,26 @@ impl BuiltinType {
       _ => BuiltinType::Special(origin.to_owned()),
     }
   }
+
+  /// map a lowered type expression, optional types are mocked as their inner type.
+  pub fn from_type_expr(expr: &TypeExpr) -> Self {
+    match expr {
+      TypeExpr::Named(name) => BuiltinType::from(name),
+      TypeExpr::Array(inner) => BuiltinType::Array(vec![BuiltinType::from_type_expr(inner)]),
+      TypeExpr::Optional(inner) => BuiltinType::from_type_expr(inner),
+      TypeExpr::Generic(name, args) => {
+        match (name.to_lowercase().as_str(), args.len()) {
+          ("list" | "set" | "vec", 1) => BuiltinType::Array(vec![BuiltinType::from_type_expr(&args[0])]),
+          ("map", 2) => {
+            let mut map = HashMap::new();
+            map.insert(BuiltinType::from_type_expr(&args[0]).to_string(), BuiltinType::from_type_expr(&args[1]));
+            BuiltinType::Map(map)
+          }
+          _ => BuiltinType::Special(expr.to_string()),
+        }
+      }
+    }
+  }
 }
 
 impl ToString for BuiltinType {
@@ -103,6 +125,8 @@ impl Default for BuiltinType {
 
 #[cfg(test)]
 mod tests {
+  use fkl_parser::parse_type_expr;
+
   use super::*;
 
   #[test]
@@ -128,6 +152,17 @@ mod tests {
     assert_eq!(t.to_string(), s);
   }
 
+  #[test]
+  fn test_builtin_type_from_type_expr() {
+    let t = BuiltinType::from_type_expr(&parse_type_expr("List<Map<String, Order>>").unwrap());
+    let mut map = HashMap::new();
+    map.insert("string".to_owned(), BuiltinType::Special("Order".to_owned()));
+    assert_eq!(t, BuiltinType::Array(vec![BuiltinType::Map(map)]));
+
+    assert_eq!(BuiltinType::from_type_expr(&parse_type_expr("int?").unwrap()), BuiltinType::Integer);
+    assert_eq!(BuiltinType::from_type_expr(&parse_type_expr("String[]").unwrap()), BuiltinType::Array(vec![BuiltinType::String]));
+  }
+
   #[test]
   fn test_uuid() {
     let s = "uuid";
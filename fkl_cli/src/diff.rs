@@ -0,0 +1,161 @@
+use fkl_codegen_dot::graph::Graph;
+use fkl_codegen_dot::helper::naming::cluster_name;
+use fkl_codegen_dot::node::Node;
+use fkl_codegen_dot::subgraph::Subgraph;
+use fkl_mir::{relation_text, Change, Changeset, ConnectionDirection, ContextMap, ContextRelation};
+
+const ADDED: &str = "green";
+const REMOVED: &str = "red";
+const CHANGED: &str = "orange";
+
+/// the Dot overlay of changeset, the `ToBe` context map with the removed contexts, aggregates and
+/// relations of `AsIs`, the added ones are green, the removed ones are red and dashed, and the
+/// moved or changed ones are orange.
+pub fn to_dot(as_is: &ContextMap, to_be: &ContextMap, changeset: &Changeset) -> String {
+  let mut graph = Graph::new(&to_be.name);
+  graph.use_default_style();
+
+  let mut clusters: Vec<(String, Subgraph)> = vec![];
+  for context in &to_be.contexts {
+    let mut subgraph = Subgraph::new(&context.name, &format!("{}(Context)", context.name));
+    if let Some(color) = context_color(changeset, &context.name, as_is) {
+      subgraph.add_style(&format!("color={}", color));
+    }
+
+    for aggregate in &context.aggregates {
+      let color = changeset.changes.iter().find_map(|change| match change {
+        Change::AggregateAdded { aggregate: name, .. } if name == &aggregate.name => Some(ADDED),
+        Change::AggregateMoved { aggregate: name, .. } if name == &aggregate.name => Some(CHANGED),
+        _ => None,
+      });
+      subgraph.add_node(aggregate_node(&aggregate.name, color, false));
+    }
+
+    clusters.push((context.name.clone(), subgraph));
+  }
+
+  for change in &changeset.changes {
+    if let Change::AggregateRemoved { context, aggregate } = change {
+      if !clusters.iter().any(|(name, _)| name == context) {
+        let mut subgraph = Subgraph::new(context, &format!("{}(Context)", context));
+        subgraph.add_style(&format!("color={}", REMOVED));
+        subgraph.add_style("style=dashed");
+        clusters.push((context.clone(), subgraph));
+      }
+
+      let index = clusters.iter().position(|(name, _)| name == context).unwrap();
+      clusters[index].1.add_node(aggregate_node(aggregate, Some(REMOVED), true));
+    }
+  }
+
+  for (_, subgraph) in clusters {
+    graph.add_subgraph(subgraph);
+  }
+
+  for relation in &to_be.relations {
+    let text = relation_text(relation);
+    let color = changeset.changes.iter().find_map(|change| match change {
+      Change::RelationAdded { relation } if relation == &text => Some(ADDED),
+      Change::RelationChanged { to, .. } if to == &text => Some(CHANGED),
+      _ => None,
+    });
+    add_relation_edge(&mut graph, relation, color, false);
+  }
+
+  for relation in &as_is.relations {
+    let text = relation_text(relation);
+    let removed = changeset.changes.iter()
+      .any(|change| matches!(change, Change::RelationRemoved { relation } if relation == &text));
+    if removed {
+      add_relation_edge(&mut graph, relation, Some(REMOVED), true);
+    }
+  }
+
+  format!("{}", graph)
+}
+
+/// the new contexts are green, the split or merged ones are orange
+fn context_color(changeset: &Changeset, context: &str, as_is: &ContextMap) -> Option<&'static str> {
+  let is_new = !as_is.contexts.iter().any(|it| it.name == context);
+  let is_reshaped = changeset.changes.iter().any(|change| match change {
+    Change::ContextSplit { context: name, .. } => name == context,
+    Change::ContextsMerged { into, .. } => into == context,
+    _ => false,
+  });
+
+  match (is_new, is_reshaped) {
+    (true, _) => Some(ADDED),
+    (false, true) => Some(CHANGED),
+    _ => None,
+  }
+}
+
+fn aggregate_node(name: &str, color: Option<&str>, dashed: bool) -> Node {
+  let mut style = vec![];
+  if let Some(color) = color {
+    style.push(format!("color={}", color));
+  }
+  if dashed {
+    style.push("style=dashed".to_string());
+  }
+
+  Node::styled(&format!("aggregate_{}", name), name, style)
+}
+
+fn add_relation_edge(graph: &mut Graph, relation: &ContextRelation, color: Option<&str>, dashed: bool) {
+  let mut style = vec![];
+  if let Some(color) = color {
+    style.push(format!("color={}", color));
+  }
+  if dashed {
+    style.push("style=dashed".to_string());
+  }
+
+  let source = &cluster_name(&relation.source);
+  let target = &cluster_name(&relation.target);
+  match &relation.connection_type {
+    ConnectionDirection::NegativeDirected => graph.add_edge_with_style(target, source, style),
+    ConnectionDirection::BiDirected => {
+      graph.add_edge_with_style(source, target, style.clone());
+      graph.add_edge_with_style(target, source, style);
+    }
+    _ => graph.add_edge_with_style(source, target, style),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use fkl_mir::Changeset;
+  use fkl_parser::parse;
+
+  use crate::diff::to_dot;
+
+  #[test]
+  fn overlay_changes() {
+    let as_is = parse(r#"
+ContextMap Shop {
+  state: AsIs;
+  Sales -> Legacy;
+}
+
+Context Sales { Aggregate Order, Invoice; }
+Context Legacy { Aggregate Report; }
+"#).unwrap();
+    let to_be = parse(r#"
+ContextMap Shop {
+  Sales -> Billing;
+}
+
+Context Sales { Aggregate Order; }
+Context Billing { Aggregate Invoice; }
+"#).unwrap();
+
+    let changeset = Changeset::diff(&as_is, &to_be);
+    let dot = to_dot(&as_is, &to_be, &changeset);
+    assert!(dot.contains("subgraph cluster_billing {\n    label=\"Billing(Context)\";\n    color=green;"));
+    assert!(dot.contains("aggregate_Invoice [label=\"Invoice\" color=orange];"));
+    assert!(dot.contains("aggregate_Report [label=\"Report\" color=red style=dashed];"));
+    assert!(dot.contains("cluster_sales -> cluster_billing [color=green];"));
+    assert!(dot.contains("cluster_sales -> cluster_legacy [color=red,style=dashed];"));
+  }
+}
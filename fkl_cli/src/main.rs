@@ -1,12 +1,13 @@
 use std::fs;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use log::info;
 
-use fkl_mir::{ContextMap, Environment};
-use fkl_parser::parse;
+use fkl_mir::{Changeset, ContextMap, ContextState, Environment};
+
+use crate::manifest::{MANIFEST_FILE, Workspace};
 
 /// parse source code and generate MIR
 pub mod deconstruct;
@@ -25,6 +26,10 @@ mod datasource;
 pub mod mock;
 /// generate feakin code
 pub mod generator;
+/// the workspace manifest, `fkl.toml`
+pub mod manifest;
+/// the changeset between `AsIs` and `ToBe` context maps
+pub mod diff;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -38,13 +43,39 @@ struct Cli {
 enum Commands {
   #[command(about = "generate Graphviz/Dot from fkl file")]
   Dot {
+    /// the entry file, or the entries of `fkl.toml` in current directory if not given
     #[arg(short, long)]
-    main: PathBuf,
+    main: Option<PathBuf>,
   },
   #[command(about = "generate ast from fkl file")]
   Ast {
     #[arg(short, long)]
-    main: PathBuf,
+    main: Option<PathBuf>,
+    /// don't output the source spans
+    #[arg(long = "no-span")]
+    no_span: bool,
+  },
+  #[command(about = "check fkl file and report all syntax errors")]
+  Check {
+    #[arg(short, long)]
+    main: Option<PathBuf>,
+  },
+  #[command(about = "format fkl files in place")]
+  Fmt {
+    #[arg(required = true)]
+    paths: Vec<PathBuf>,
+    /// don't write the files, exit with error if any file is not formatted
+    #[arg(long)]
+    check: bool,
+  },
+  #[command(about = "compare the AsIs and ToBe context maps")]
+  Diff {
+    #[arg(long = "as-is")]
+    as_is: PathBuf,
+    #[arg(long = "to-be")]
+    to_be: PathBuf,
+    #[arg(short, long, value_enum, default_value = "text")]
+    format: DiffFormat,
   },
   #[command(about = "generate code from fkl file")]
   Gen(GenOpt),
@@ -54,12 +85,13 @@ enum Commands {
 
 #[derive(Debug, Args)]
 struct GenOpt {
-  #[arg(short, long, required = true)]
-  main: PathBuf,
+  #[arg(short, long)]
+  main: Option<PathBuf>,
   #[arg(short, long = "impl")]
   impl_name: Option<String>,
-  #[arg(short, long = "framework", default_value = "spring")]
-  framework: SupportedFramework,
+  /// the framework of generated code, default is `spring`
+  #[arg(short, long = "framework")]
+  framework: Option<SupportedFramework>,
 }
 
 #[derive(clap::ValueEnum, PartialEq, Debug, Clone)]
@@ -67,11 +99,18 @@ pub enum SupportedFramework {
   Spring,
 }
 
+#[derive(clap::ValueEnum, PartialEq, Debug, Clone)]
+pub enum DiffFormat {
+  Text,
+  Json,
+  Dot,
+}
+
 #[derive(Debug, Args)]
 struct RunOpt {
-  /// main file of feakin
-  #[arg(short, long, required = true)]
-  main: PathBuf,
+  /// main file of feakin, or the entries of `fkl.toml` in current directory if not given
+  #[arg(short, long)]
+  main: Option<PathBuf>,
   /// the path of the function to run
   #[arg(short, long, required = false)]
   path: Option<PathBuf>,
@@ -106,40 +145,79 @@ async fn main() {
 
   let cli: Cli = Cli::parse();
   match &cli.command {
-    Commands::Dot { main: path } => {
-      gen_to_dot(path);
+    Commands::Dot { main } => {
+      gen_to_dot(&load_workspace(main));
+    }
+    Commands::Ast { main, no_span } => {
+      parse_to_ast(&load_workspace(main), *no_span);
+    }
+    Commands::Check { main } => {
+      let workspace = load_workspace(main);
+      let entries = workspace.entries();
+      // report the syntax errors of all entries, not only the first broken one
+      let broken = entries.iter().filter(|path| !check_syntax(path)).count();
+      if broken > 0 {
+        std::process::exit(1);
+      }
+
+      if !check_references(&entries) {
+        std::process::exit(1);
+      }
+    }
+    Commands::Fmt { paths, check } => {
+      if !format_files(paths, *check) {
+        std::process::exit(1);
+      }
     }
-    Commands::Ast { main: path } => {
-      parse_to_ast(path);
+    Commands::Diff { as_is, to_be, format } => {
+      diff_context_maps(as_is, to_be, format);
     }
     Commands::Gen(opt) => {
-      let parent = &opt.main.parent().unwrap().to_path_buf();
-      builtin::funcs::code_gen::code_gen_by_path(&opt.main, opt.impl_name.clone(), &parent);
+      let workspace = load_workspace(&opt.main);
+      let config = &workspace.package.manifest.gen;
+      let impl_name = opt.impl_name.clone().or_else(|| config.impl_name.clone());
+      let framework = match (&opt.framework, &config.framework) {
+        (Some(framework), _) => framework.clone(),
+        (None, Some(name)) => SupportedFramework::from_str(name, true)
+          .unwrap_or_else(|_| panic!("unsupported framework in {}: {}", MANIFEST_FILE, name)),
+        (None, None) => SupportedFramework::Spring,
+      };
+
+      let mir = builtin::funcs::mir_from_files(&workspace.entries());
+      match framework {
+        SupportedFramework::Spring => {
+          builtin::funcs::code_gen::code_gen_by_mir(&mir, impl_name, &workspace.dir().to_path_buf());
+        }
+      }
     }
     Commands::Run(run) => {
+      let workspace = load_workspace(&run.main);
+      let config = &workspace.package.manifest.run;
       let root = match &run.path {
         Some(path) => path.clone(),
-        None => run.main.parent().unwrap().to_path_buf(),
+        None => workspace.dir().to_path_buf(),
       };
+      let impl_name = run.impl_name.clone().or_else(|| config.impl_name.clone());
+      let env_name = run.env.clone().or_else(|| config.env.clone());
 
-      let mir = builtin::funcs::mir_from_file(&run.main);
+      let mir = builtin::funcs::mir_from_files(&workspace.entries());
 
       info!("runOpt: {:?}", run);
       match &run.func_name {
         RunFuncName::HttpRequest => {
-          let impl_name = run.impl_name.as_ref().unwrap();
+          let impl_name = impl_name.expect("impl name is required");
           builtin::funcs::endpoint_runner(&mir, &run.func_name, &impl_name);
         }
         RunFuncName::Guarding => {
           let layered = mir.layered.expect("layered architecture is required");
-          builtin::funcs::guarding_runner(root, &layered);
+          builtin::funcs::guarding_runner(root, &layered, &mir.contexts);
         }
         RunFuncName::TestConnection => {
           if mir.envs.len() == 0 {
             panic!("environment is required");
           }
 
-          let env = env_from_opt(&run, &mir);
+          let env = env_from_opt(&env_name, &mir);
           builtin::funcs::test_connection_runner(&env).await;
         }
         RunFuncName::MockServer => {
@@ -151,7 +229,7 @@ async fn main() {
             None => panic!("custom function name is required"),
           };
 
-          let env = env_from_opt(&run, &mir);
+          let env = env_from_opt(&env_name, &mir);
           builtin::funcs::custom_function_runner(&mir, &env, &func_name).await;
         }
       }
@@ -159,8 +237,8 @@ async fn main() {
   }
 }
 
-fn env_from_opt(run: &RunOpt, mir: &ContextMap) -> Environment {
-  let env: &Environment = match &run.env {
+fn env_from_opt(env_name: &Option<String>, mir: &ContextMap) -> Environment {
+  let env: &Environment = match env_name {
     Some(env_name) => {
       mir.envs.iter()
         .filter(|env| &env.name == env_name)
@@ -174,9 +252,32 @@ fn env_from_opt(run: &RunOpt, mir: &ContextMap) -> Environment {
   env.clone()
 }
 
-fn gen_to_dot(path: &PathBuf) {
-  let contents = fs::read_to_string(path).expect("Something went wrong reading the file");
-  let context_map = parse(&*contents).expect("TODO: panic message");
+/// the workspace of `--main` file, or the one of `fkl.toml` in current directory or its ancestors
+fn load_workspace(main: &Option<PathBuf>) -> Workspace {
+  if let Some(main) = main {
+    return Workspace::single(main);
+  }
+
+  let current = std::env::current_dir().expect("cannot get current directory");
+  let manifest = match Workspace::discover(&current) {
+    Some(manifest) => manifest,
+    None => {
+      eprintln!("no --main file is given, and no {} is found", MANIFEST_FILE);
+      std::process::exit(1);
+    }
+  };
+
+  match Workspace::load(&manifest) {
+    Ok(workspace) => workspace,
+    Err(e) => {
+      eprintln!("{}", e);
+      std::process::exit(1);
+    }
+  }
+}
+
+fn gen_to_dot(workspace: &Workspace) {
+  let context_map = builtin::funcs::mir_from_files(&workspace.entries());
 
   let json = serde_json::to_string(&context_map).expect("TODO: panic message");
 
@@ -184,10 +285,96 @@ fn gen_to_dot(path: &PathBuf) {
   file.write_all(json.as_bytes()).expect("TODO: panic message");
 }
 
+/// print the changeset from the `AsIs` file to the `ToBe` file, warn if their declared states are
+/// not as expected
+fn diff_context_maps(as_is_path: &Path, to_be_path: &Path, format: &DiffFormat) {
+  let as_is = builtin::funcs::mir_from_files(&[as_is_path.to_path_buf()]);
+  let to_be = builtin::funcs::mir_from_files(&[to_be_path.to_path_buf()]);
+  if as_is.state != ContextState::AsIs {
+    eprintln!("warning: {}: the state of context map is not AsIs", as_is_path.display());
+  }
+  if to_be.state != ContextState::ToBe {
+    eprintln!("warning: {}: the state of context map is not ToBe", to_be_path.display());
+  }
 
-fn parse_to_ast(path: &PathBuf) {
+  let changeset = Changeset::diff(&as_is, &to_be);
+  match format {
+    DiffFormat::Text => print!("{}", changeset),
+    DiffFormat::Json => println!("{}", serde_json::to_string_pretty(&changeset).unwrap()),
+    DiffFormat::Dot => println!("{}", diff::to_dot(&as_is, &to_be, &changeset)),
+  }
+}
+
+/// report all syntax errors in one pass, returns false if there is any error
+fn check_syntax(path: &Path) -> bool {
   let contents = fs::read_to_string(path).expect("Something went wrong reading the file");
-  let context_map = parse(&*contents).expect("TODO: panic message");
+  let (_, errors) = fkl_parser::parse_with_recovery(&contents);
+  if errors.is_empty() {
+    info!("{}: no syntax errors", path.display());
+    return true;
+  }
+
+  for mut error in errors {
+    error.file = Some(path.display().to_string());
+    eprintln!("{}\n", error);
+  }
+
+  false
+}
+
+/// report the semantic diagnostics, returns false if there is any error
+fn check_references(paths: &[PathBuf]) -> bool {
+  let (_, diagnostics) = match fkl_parser::check_files(paths) {
+    Ok(result) => result,
+    Err(e) => {
+      eprintln!("{}\n", e);
+      return false;
+    }
+  };
+
+  for diagnostic in &diagnostics {
+    eprintln!("{}\n", diagnostic);
+  }
+
+  !diagnostics.iter().any(|d| d.is_error())
+}
+
+/// format the files in place, or only check them, returns false if any file has syntax errors or
+/// is not formatted in check mode
+fn format_files(paths: &[PathBuf], check: bool) -> bool {
+  let mut success = true;
+  for path in paths {
+    let contents = fs::read_to_string(path).expect("Something went wrong reading the file");
+    let formatted = match fkl_parser::format(&contents) {
+      Ok(formatted) => formatted,
+      Err(e) => {
+        eprintln!("{}: {}\n", path.display(), e);
+        success = false;
+        continue;
+      }
+    };
+
+    if formatted == contents {
+      continue;
+    }
+
+    if check {
+      eprintln!("{}: not formatted", path.display());
+      success = false;
+    } else {
+      fs::write(path, formatted).expect("Something went wrong writing the file");
+      info!("{}: formatted", path.display());
+    }
+  }
+
+  success
+}
+
+fn parse_to_ast(workspace: &Workspace, no_span: bool) {
+  let mut context_map = builtin::funcs::mir_from_files(&workspace.entries());
+  if no_span {
+    context_map.strip_spans();
+  }
 
   let json = serde_json::to_string(&context_map).expect("TODO: panic message");
 
@@ -0,0 +1,352 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use semver::{Version, VersionReq};
+use serde::Deserialize;
+
+pub const MANIFEST_FILE: &str = "fkl.toml";
+
+/// # Manifest
+/// The `fkl.toml` of a model package, like:
+/// ```toml
+/// [package]
+/// name = "shop"
+/// version = "0.1.0"
+/// entries = ["main.fkl"]
+///
+/// [dependencies]
+/// shared-kernel = { path = "../shared-kernel", version = "^1.0" }
+/// payment = "1.2"
+///
+/// [gen]
+/// impl = "PlaceOrder"
+/// framework = "spring"
+///
+/// [run]
+/// env = "Local"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Default)]
+pub struct Manifest {
+  pub package: Package,
+  #[serde(default)]
+  pub dependencies: BTreeMap<String, Dependency>,
+  #[serde(default)]
+  pub gen: GenConfig,
+  #[serde(default)]
+  pub run: RunConfig,
+}
+
+/// - [`root`]: the workspace root which the entries are relative to, default is the directory of manifest
+/// - [`vendor`]: the directory of vendored packages, relative to the directory of manifest
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Package {
+  pub name: String,
+  #[serde(default = "default_version")]
+  pub version: String,
+  #[serde(default = "default_entries")]
+  pub entries: Vec<PathBuf>,
+  #[serde(default)]
+  pub root: Option<PathBuf>,
+  #[serde(default = "default_vendor")]
+  pub vendor: PathBuf,
+}
+
+impl Default for Package {
+  fn default() -> Self {
+    Package {
+      name: "".to_string(),
+      version: default_version(),
+      entries: default_entries(),
+      root: None,
+      vendor: default_vendor(),
+    }
+  }
+}
+
+fn default_version() -> String {
+  "0.1.0".to_string()
+}
+
+fn default_entries() -> Vec<PathBuf> {
+  vec![PathBuf::from("main.fkl")]
+}
+
+fn default_vendor() -> PathBuf {
+  PathBuf::from("vendor")
+}
+
+/// the dependency in local path, or in the vendor directory if path is not given, like
+/// `{ path = "../shared-kernel", version = "^1.0" }` or `"1.2"`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum Dependency {
+  Version(String),
+  Detailed {
+    #[serde(default)]
+    path: Option<PathBuf>,
+    #[serde(default)]
+    version: Option<String>,
+  },
+}
+
+impl Dependency {
+  pub fn path(&self) -> Option<&PathBuf> {
+    match self {
+      Dependency::Version(_) => None,
+      Dependency::Detailed { path, .. } => path.as_ref(),
+    }
+  }
+
+  pub fn version(&self) -> Option<&str> {
+    match self {
+      Dependency::Version(version) => Some(version),
+      Dependency::Detailed { version, .. } => version.as_deref(),
+    }
+  }
+}
+
+/// the default options of `gen` command
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Default)]
+pub struct GenConfig {
+  #[serde(default, rename = "impl")]
+  pub impl_name: Option<String>,
+  #[serde(default)]
+  pub framework: Option<String>,
+}
+
+/// the default options of `run` command
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Default)]
+pub struct RunConfig {
+  #[serde(default, rename = "impl")]
+  pub impl_name: Option<String>,
+  #[serde(default)]
+  pub env: Option<String>,
+}
+
+impl Manifest {
+  pub fn parse(content: &str) -> Result<Manifest, String> {
+    toml::from_str(content).map_err(|e| e.to_string())
+  }
+
+  pub fn from_path(path: &Path) -> Result<Manifest, String> {
+    let content = fs::read_to_string(path)
+      .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    Manifest::parse(&content).map_err(|e| format!("{}: {}", path.display(), e))
+  }
+}
+
+/// A model package with its manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedPackage {
+  pub dir: PathBuf,
+  pub manifest: Manifest,
+}
+
+impl ResolvedPackage {
+  pub fn load(dir: &Path) -> Result<ResolvedPackage, String> {
+    let dir = dir.canonicalize().map_err(|e| format!("cannot read {}: {}", dir.display(), e))?;
+    let manifest = Manifest::from_path(&dir.join(MANIFEST_FILE))?;
+    Ok(ResolvedPackage { dir, manifest })
+  }
+
+  pub fn root(&self) -> PathBuf {
+    match &self.manifest.package.root {
+      Some(root) => self.dir.join(root),
+      None => self.dir.clone(),
+    }
+  }
+
+  pub fn entries(&self) -> Vec<PathBuf> {
+    let root = self.root();
+    self.manifest.package.entries.iter().map(|entry| root.join(entry)).collect()
+  }
+
+  fn dependency_dir(&self, name: &str, dependency: &Dependency) -> PathBuf {
+    match dependency.path() {
+      Some(path) => self.dir.join(path),
+      None => self.dir.join(&self.manifest.package.vendor).join(name),
+    }
+  }
+}
+
+/// # Workspace
+/// The package of `fkl.toml` and all packages it depends on, for compiling them as a whole.
+/// - [`dependencies`]: the resolved dependencies, every package is after the ones it depends on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workspace {
+  pub package: ResolvedPackage,
+  pub dependencies: Vec<ResolvedPackage>,
+}
+
+impl Workspace {
+  /// find the `fkl.toml` in the directory or its ancestors
+  pub fn discover(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+      .map(|dir| dir.join(MANIFEST_FILE))
+      .find(|path| path.is_file())
+  }
+
+  /// the workspace of the single file, without dependencies
+  pub fn single(main: &Path) -> Workspace {
+    let dir = main.parent().unwrap_or(Path::new("")).to_path_buf();
+    let mut manifest = Manifest::default();
+    manifest.package.entries = vec![PathBuf::from(main.file_name().unwrap_or_default())];
+
+    Workspace {
+      package: ResolvedPackage { dir, manifest },
+      dependencies: vec![],
+    }
+  }
+
+  pub fn load(manifest_path: &Path) -> Result<Workspace, String> {
+    let dir = manifest_path.parent().unwrap_or(Path::new("")).to_path_buf();
+    let package = ResolvedPackage { dir, manifest: Manifest::from_path(manifest_path)? };
+
+    let mut dependencies: Vec<ResolvedPackage> = vec![];
+    let mut stack: Vec<String> = vec![package.manifest.package.name.clone()];
+    resolve_dependencies(&package, &mut stack, &mut dependencies)?;
+
+    Ok(Workspace { package, dependencies })
+  }
+
+  /// the directory of workspace, for the generated code and the `run` functions
+  pub fn dir(&self) -> &Path {
+    &self.package.dir
+  }
+
+  /// the entry files of dependencies and then the workspace, in compiling order
+  pub fn entries(&self) -> Vec<PathBuf> {
+    self.dependencies.iter()
+      .chain(std::iter::once(&self.package))
+      .flat_map(|package| package.entries())
+      .collect()
+  }
+}
+
+/// resolve the dependencies in depth-first order, the package required by many packages is resolved
+/// once, but it should be in the same directory.
+fn resolve_dependencies(package: &ResolvedPackage, stack: &mut Vec<String>, resolved: &mut Vec<ResolvedPackage>) -> Result<(), String> {
+  for (name, dependency) in &package.manifest.dependencies {
+    if stack.contains(name) {
+      let mut chain = stack.clone();
+      chain.push(name.clone());
+      return Err(format!("dependency cycle detected: {}", chain.join(" -> ")));
+    }
+
+    let dir = package.dependency_dir(name, dependency);
+    if let Some(exist) = resolved.iter().find(|it| &it.manifest.package.name == name) {
+      if !same_dir(&exist.dir, &dir) {
+        return Err(format!("dependency `{}` is resolved to both {} and {}", name, exist.dir.display(), dir.display()));
+      }
+      check_version(name, dependency, &exist.manifest.package.version)?;
+      continue;
+    }
+
+    let dependency_package = ResolvedPackage::load(&dir)
+      .map_err(|e| format!("cannot load dependency `{}` of `{}`: {}", name, package.manifest.package.name, e))?;
+    if &dependency_package.manifest.package.name != name {
+      return Err(format!("dependency `{}` is named `{}` in {}", name, dependency_package.manifest.package.name, dir.display()));
+    }
+    check_version(name, dependency, &dependency_package.manifest.package.version)?;
+
+    stack.push(name.clone());
+    resolve_dependencies(&dependency_package, stack, resolved)?;
+    stack.pop();
+
+    resolved.push(dependency_package);
+  }
+
+  Ok(())
+}
+
+fn check_version(name: &str, dependency: &Dependency, version: &str) -> Result<(), String> {
+  let requirement = match dependency.version() {
+    Some(requirement) => requirement,
+    None => return Ok(()),
+  };
+
+  let req = VersionReq::parse(requirement)
+    .map_err(|e| format!("invalid version requirement `{}` of `{}`: {}", requirement, name, e))?;
+  let found = Version::parse(version)
+    .map_err(|e| format!("invalid version `{}` of `{}`: {}", version, name, e))?;
+  if !req.matches(&found) {
+    return Err(format!("dependency `{}` requires version `{}`, but found `{}`", name, requirement, version));
+  }
+
+  Ok(())
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+  match (a.canonicalize(), b.canonicalize()) {
+    (Ok(a), Ok(b)) => a == b,
+    _ => a == b,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::fs;
+  use std::path::PathBuf;
+
+  use crate::manifest::{Dependency, Manifest, Workspace};
+
+  #[test]
+  fn parse_manifest() {
+    let manifest = Manifest::parse(r#"[package]
+name = "shop"
+entries = ["sales.fkl", "payment.fkl"]
+
+[dependencies]
+shared-kernel = { path = "../shared-kernel", version = "^1.0" }
+payment = "1.2"
+
+[gen]
+impl = "PlaceOrder"
+framework = "spring"
+"#).unwrap();
+
+    assert_eq!(manifest.package.name, "shop");
+    assert_eq!(manifest.package.version, "0.1.0");
+    assert_eq!(manifest.package.entries, vec![PathBuf::from("sales.fkl"), PathBuf::from("payment.fkl")]);
+    assert_eq!(manifest.dependencies["payment"], Dependency::Version("1.2".to_string()));
+    assert_eq!(manifest.dependencies["shared-kernel"].path(), Some(&PathBuf::from("../shared-kernel")));
+    assert_eq!(manifest.gen.impl_name, Some("PlaceOrder".to_string()));
+    assert_eq!(manifest.run.env, None);
+  }
+
+  #[test]
+  fn resolve_workspace() {
+    let temp = tempfile::tempdir().unwrap();
+    let dir = temp.path();
+    fs::create_dir_all(dir.join("shop/vendor/payment")).unwrap();
+    fs::create_dir_all(dir.join("kernel")).unwrap();
+    fs::write(dir.join("shop/fkl.toml"), r#"[package]
+name = "shop"
+
+[dependencies]
+kernel = { path = "../kernel", version = "^1.0" }
+payment = "0.2"
+"#).unwrap();
+    fs::write(dir.join("shop/vendor/payment/fkl.toml"), r#"[package]
+name = "payment"
+version = "0.2.1"
+entries = ["payment.fkl"]
+
+[dependencies]
+kernel = { path = "../../../kernel" }
+"#).unwrap();
+    fs::write(dir.join("kernel/fkl.toml"), "[package]\nname = \"kernel\"\nversion = \"1.1.0\"\nroot = \"model\"\n").unwrap();
+
+    let workspace = Workspace::load(&dir.join("shop/fkl.toml")).unwrap();
+    assert_eq!(workspace.entries(), vec![
+      dir.join("kernel/model/main.fkl"),
+      dir.join("shop/vendor/payment/payment.fkl"),
+      dir.join("shop/main.fkl"),
+    ]);
+
+    fs::write(dir.join("kernel/fkl.toml"), "[package]\nname = \"kernel\"\nversion = \"2.0.0\"\n").unwrap();
+    let error = Workspace::load(&dir.join("shop/fkl.toml")).unwrap_err();
+    assert_eq!(error, "dependency `kernel` requires version `^1.0`, but found `2.0.0`");
+  }
+}
@@ -5,7 +5,7 @@ use rand::distributions::Alphanumeric;
 use rand::Rng;
 use sqlx::types::uuid;
 
-use fkl_mir::{Field, Struct};
+use fkl_mir::{Enum, Field, Struct};
 
 use crate::builtin::types::BuiltinType;
 use crate::mock::mock_type::MockType;
@@ -25,7 +25,12 @@ impl FakeValue {
   pub fn builtin_type(fields: &Vec<Field>) -> IndexMap<String, BuiltinType> {
     let mut map = IndexMap::new();
     for field in fields {
-      map.insert(field.name.clone(), BuiltinType::from(&field.type_type));
+      let builtin = if field.type_expr.is_empty() {
+        BuiltinType::from(&field.type_type)
+      } else {
+        BuiltinType::from_type_expr(&field.type_expr)
+      };
+      map.insert(field.name.clone(), builtin);
     }
 
     map
@@ -35,7 +40,9 @@ impl FakeValue {
     FakeValue::fake_values(&FakeValue::builtin_type(fields))
   }
 
-  pub fn fake_with_custom(fields: &Vec<Field>, struct_map: &HashMap<String, Struct>) -> IndexMap<String, MockType> {
+  /// fake the fields with custom types, the struct type will be faked recursively, and the enum type
+  /// will be one of its variants.
+  pub fn fake_with_custom(fields: &Vec<Field>, struct_map: &HashMap<String, Struct>, enums: &[Enum]) -> IndexMap<String, MockType> {
     let to_types = FakeValue::builtin_type(fields);
     let mut result = IndexMap::new();
     for (key, value) in to_types {
@@ -43,8 +50,10 @@ impl FakeValue {
         BuiltinType::Special(special) => {
           if let Some(got_struct) = struct_map.get(special) {
             let struct_fields = got_struct.fields.clone();
-            let fake_value = FakeValue::fake_with_custom(&struct_fields, struct_map);
+            let fake_value = FakeValue::fake_with_custom(&struct_fields, struct_map, enums);
             result.insert(key.clone(), MockType::Map(fake_value));
+          } else if let Some(got_enum) = enums.iter().find(|it| &it.name == special) {
+            result.insert(key, RandomValue::variant(&got_enum.variants));
           } else {
             result.insert(key, FakeValue::convert_type(&value));
           }
@@ -133,6 +142,16 @@ impl RandomValue {
     MockType::Float(n)
   }
 
+  /// one of the enum variants, null for the enum without variant
+  pub fn variant(variants: &[String]) -> MockType {
+    if variants.is_empty() {
+      return MockType::Null;
+    }
+
+    let index = rand::thread_rng().gen_range(0..variants.len());
+    MockType::String(variants[index].clone())
+  }
+
   pub fn string() -> MockType {
     let n: String = rand::thread_rng()
       .sample_iter(&Alphanumeric)
@@ -276,21 +295,25 @@ mod tests {
         name: "id".to_string(),
         initializer: None,
         type_type: "int".to_string(),
+        ..Default::default()
       },
       Field {
         name: "name".to_string(),
         initializer: None,
         type_type: "string".to_string(),
+        ..Default::default()
       },
       Field {
         name: "age".to_string(),
         initializer: None,
         type_type: "int".to_string(),
+        ..Default::default()
       },
       Field {
         name: "created_at".to_string(),
         initializer: None,
         type_type: "datetime".to_string(),
+        ..Default::default()
       },
     ];
 
@@ -1,7 +1,9 @@
 pub mod stub_aggregate_api;
+pub mod stub_impl_api;
 pub mod stub_server;
 pub mod mock_type;
 pub mod mock_composite_type;
 pub mod mock_strategy;
 pub mod fake_value;
 pub mod fake_user_agent;
+pub mod request_validator;
@@ -0,0 +1,129 @@
+use std::cmp::Ordering;
+
+use regex::Regex;
+use serde_json::Value;
+
+use fkl_mir::{CompareOperator, Field, Validation};
+
+/// validate the request body by the validations of fields, returns the violation messages,
+/// the missing fields are only checked by `Required`.
+pub fn validate_body(fields: &[Field], body: &Value) -> Vec<String> {
+  let mut errors = vec![];
+  for field in fields {
+    let value = body.get(&field.name).filter(|value| !value.is_null());
+    for validation in &field.validations {
+      if let Some(error) = validate_value(field, value, validation, body) {
+        errors.push(error);
+      }
+    }
+  }
+
+  errors
+}
+
+fn validate_value(field: &Field, value: Option<&Value>, validation: &Validation, body: &Value) -> Option<String> {
+  let name = &field.name;
+  if let Validation::Required(true) = validation {
+    return match value {
+      Some(_) => None,
+      None => Some(format!("{} is required", name)),
+    };
+  }
+
+  let value = value?;
+  match validation {
+    Validation::Range(range) => {
+      let number = value.as_f64()?;
+      let too_small = matches!(range.min, Some(min) if number < min);
+      let too_large = matches!(range.max, Some(max) if number > max);
+      if too_small || too_large {
+        return Some(format!("{} should be in range {}..{}", name, bound(range.min), bound(range.max)));
+      }
+    }
+    Validation::Length(length) => {
+      let len = match value {
+        Value::String(str) => str.chars().count(),
+        Value::Array(array) => array.len(),
+        _ => return None,
+      };
+      let too_short = matches!(length.min, Some(min) if len < min);
+      let too_long = matches!(length.max, Some(max) if len > max);
+      if too_short || too_long {
+        return Some(format!("{} length should be in range {}..{}", name, bound(length.min), bound(length.max)));
+      }
+    }
+    Validation::Regex(regex) => {
+      let str = value.as_str()?;
+      let pattern = Regex::new(&regex.pattern).ok()?;
+      if !pattern.is_match(str) {
+        return Some(format!("{} should match pattern {}", name, regex.pattern));
+      }
+    }
+    Validation::Compare(compare) => {
+      let other = body.get(&compare.right)?;
+      let ordering = match (value, other) {
+        (Value::Number(left), Value::Number(right)) => left.as_f64()?.partial_cmp(&right.as_f64()?)?,
+        (Value::String(left), Value::String(right)) => left.cmp(right),
+        _ => return None,
+      };
+
+      let is_valid = match compare.operator {
+        CompareOperator::Equal => ordering == Ordering::Equal,
+        CompareOperator::NotEqual => ordering != Ordering::Equal,
+        CompareOperator::GreaterThan => ordering == Ordering::Greater,
+        CompareOperator::GreaterThanOrEqual => ordering != Ordering::Less,
+        CompareOperator::LessThan => ordering == Ordering::Less,
+        CompareOperator::LessThanOrEqual => ordering != Ordering::Greater,
+      };
+      if !is_valid {
+        return Some(format!("{} should be {:?} {}", name, compare.operator, compare.right));
+      }
+    }
+    Validation::None | Validation::Required(_) => {}
+  }
+
+  None
+}
+
+fn bound<T: ToString>(value: Option<T>) -> String {
+  value.map(|it| it.to_string()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+  use serde_json::json;
+
+  use crate::builtin::funcs::mir_from_str;
+  use crate::mock::request_validator::validate_body;
+
+  #[test]
+  fn validate_by_constraints() {
+    let context_map = mir_from_str(r#"
+Context Account {
+  Aggregate Account {
+    Entity User {
+      struct {
+        name: String @required @length(1, 8)
+        age: Int @range(0, 150)
+        email: String @pattern("^[a-z]+@[a-z]+\.com$")
+        endAt: Int @compare(">", startAt)
+        startAt: Int
+      }
+    }
+  }
+}
+    "#);
+    let fields = &context_map.get_entity("User").unwrap().fields;
+
+    let valid = json!({ "name": "phodal", "age": 30, "email": "me@feakin.com", "startAt": 1, "endAt": 2 });
+    assert!(validate_body(fields, &valid).is_empty());
+
+    let invalid = json!({ "age": 200, "email": "me", "startAt": 2, "endAt": 1 });
+    assert_eq!(validate_body(fields, &invalid), vec![
+      "name is required",
+      "age should be in range 0..150",
+      "email should match pattern ^[a-z]+@[a-z]+\\.com$",
+      "endAt should be GreaterThan startAt",
+    ]);
+  }
+}
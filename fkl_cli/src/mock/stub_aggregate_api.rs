@@ -1,12 +1,17 @@
 use indexmap::IndexMap;
 use rocket::{delete, get, post, put, State};
-use rocket::response::status::NotFound;
+use rocket::http::Status;
+use rocket::response::status::{Custom, NotFound};
 use rocket::serde::json::Json;
+use serde_json::Value;
 
-use fkl_mir::{ContextMap, Entity};
+use std::collections::HashMap;
+
+use fkl_mir::{ContextMap, Entity, Struct};
 
 use crate::mock::fake_value::FakeValue;
 use crate::mock::mock_type::MockType;
+use crate::mock::request_validator::validate_body;
 use crate::mock::stub_server::{ApiError, MockServerConfig};
 
 fn filter_entity(aggregate_name: &str, entity_name: &str, context_map: &ContextMap) -> Option<Entity> {
@@ -67,45 +72,62 @@ pub async fn get_entities(
   return Ok(Json(vec));
 }
 
-#[put("/<aggregate_name>/<entity_name>")]
+#[put("/<aggregate_name>/<entity_name>", data = "<body>")]
 pub async fn create_entity(
   aggregate_name: &str,
   entity_name: &str,
+  body: Option<Json<Value>>,
   config: &State<MockServerConfig>,
-) -> Result<Json<Vec<IndexMap<String, MockType>>>, NotFound<Json<ApiError>>> {
-  let opt_entity = filter_entity(aggregate_name, entity_name, &config.context_map);
-  if let None = opt_entity {
-    return Err(NotFound(Json(ApiError {
-      msg: format!("Entity {} not found", entity_name),
-    })));
-  }
-
-  let entity = opt_entity.unwrap();
+) -> Result<Json<Vec<IndexMap<String, MockType>>>, Custom<Json<ApiError>>> {
+  let entity = validated_entity(aggregate_name, entity_name, body, config)?;
   let map = mock_value_from_entity(&entity, &config.context_map);
   return Ok(Json(vec![map]));
 }
 
 // update entity
 #[allow(unused_variables)]
-#[post("/<aggregate_name>/<entity_name>/<id>")]
+#[post("/<aggregate_name>/<entity_name>/<id>", data = "<body>")]
 pub async fn update_entity(
   aggregate_name: &str,
   entity_name: &str,
   id: usize,
+  body: Option<Json<Value>>,
   config: &State<MockServerConfig>,
-) -> Result<Json<Vec<IndexMap<String, MockType>>>, NotFound<Json<ApiError>>> {
-  let opt_entity = filter_entity(aggregate_name, entity_name, &config.context_map);
-  if let None = opt_entity {
-    return Err(NotFound(Json(ApiError {
-      msg: format!("Entity {} not found", entity_name),
-    })));
-  }
-
-  let entity = opt_entity.unwrap();
+) -> Result<Json<Vec<IndexMap<String, MockType>>>, Custom<Json<ApiError>>> {
+  let entity = validated_entity(aggregate_name, entity_name, body, config)?;
   let map = mock_value_from_entity(&entity, &config.context_map);
   return Ok(Json(vec![map]));
 }
 
+/// find the entity and validate the request body by the field validations, the request without
+/// json body is not validated.
+fn validated_entity(
+  aggregate_name: &str,
+  entity_name: &str,
+  body: Option<Json<Value>>,
+  config: &State<MockServerConfig>,
+) -> Result<Entity, Custom<Json<ApiError>>> {
+  let entity = match filter_entity(aggregate_name, entity_name, &config.context_map) {
+    Some(entity) => entity,
+    None => {
+      return Err(Custom(Status::NotFound, Json(ApiError {
+        msg: format!("Entity {} not found", entity_name),
+      })));
+    }
+  };
+
+  if let Some(body) = body {
+    let errors = validate_body(&entity.fields, &body);
+    if !errors.is_empty() {
+      return Err(Custom(Status::UnprocessableEntity, Json(ApiError {
+        msg: errors.join("; "),
+      })));
+    }
+  }
+
+  Ok(entity)
+}
+
 // delete entity
 #[allow(unused_variables)]
 #[delete("/<aggregate_name>/<entity_name>/<id>")]
@@ -129,7 +151,27 @@ pub async fn delete_entity(
 
 fn mock_value_from_entity(entity: &Entity, bcs: &ContextMap) -> IndexMap<String, MockType> {
   let fields = &entity.fields;
-  FakeValue::fake_with_custom(fields, &bcs.structs)
+  FakeValue::fake_with_custom(fields, &embedded_types(entity, bcs), &bcs.enums)
+}
+
+/// the structs and value objects which can be embedded in the entity, the value objects of
+/// entity take precedence over the ones in aggregates and top-level.
+fn embedded_types(entity: &Entity, bcs: &ContextMap) -> HashMap<String, Struct> {
+  let mut types = bcs.structs.clone();
+  let aggregate_vos = bcs.contexts.iter()
+    .flat_map(|bc| bc.aggregates.iter())
+    .flat_map(|aggregate| aggregate.value_objects.iter());
+
+  for vo in bcs.value_objects.iter().chain(aggregate_vos).chain(entity.value_objects.iter()) {
+    types.insert(vo.name.clone(), Struct {
+      name: vo.name.clone(),
+      fields: vo.fields.clone(),
+      annotations: vo.annotations.clone(),
+      span: vo.span.clone(),
+    });
+  }
+
+  types
 }
 
 #[cfg(test)]
@@ -174,4 +216,53 @@ struct Address {
     let address: &MockType = from_entity.get("address").unwrap();
     assert_eq!(address.as_map().len(), 4);
   }
+
+  #[test]
+  fn value_object_as_embedded_type() {
+    let context_map = mir_from_str("
+Context Cart {
+  Aggregate Cart {
+    Entity Cart {
+      struct {
+        id: String
+        total: CartTotal
+      }
+      ValueObject CartTotal {
+        constructor(amount: Float, currency: String)
+      }
+    }
+  }
+}
+    ");
+
+    let entity = context_map.get_entity("Cart").unwrap();
+    let from_entity = mock_value_from_entity(&entity, &context_map);
+
+    let total: &MockType = from_entity.get("total").unwrap();
+    assert_eq!(total.as_map().len(), 2);
+  }
+
+  #[test]
+  fn enum_variant_value() {
+    let context_map = mir_from_str("
+Context Ticket {
+  Aggregate Ticket {
+    Entity Ticket {
+      struct {
+        id: String
+        status: TicketStatus
+      }
+    }
+  }
+}
+
+enum TicketStatus { Open, Closed }
+    ");
+
+    let entity = context_map.get_entity("Ticket").unwrap();
+    let from_entity = mock_value_from_entity(&entity, &context_map);
+
+    let status = from_entity.get("status").unwrap().as_string();
+    assert!(status == "Open" || status == "Closed");
+  }
 }
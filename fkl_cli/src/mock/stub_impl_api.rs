@@ -0,0 +1,204 @@
+use std::path::{Path, PathBuf};
+
+use indexmap::IndexMap;
+use rocket::{delete, get, patch, post, put, State};
+use rocket::http::{ContentType, Status};
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::response::status::Custom;
+use rocket::serde::json::Json;
+use serde_json::{Map, Value};
+
+use fkl_mir::{ContextMap, Field, HttpApiImpl, HttpEndpoint, HttpMethod, Implementation, ResponseVariant};
+
+use crate::mock::fake_value::FakeValue;
+use crate::mock::mock_type::MockType;
+use crate::mock::request_validator::validate_body;
+use crate::mock::stub_server::{ApiError, MockServerConfig};
+
+/// the header to choose the response variant by status code, like `X-Mock-Status: 404`
+pub const MOCK_STATUS_HEADER: &str = "X-Mock-Status";
+
+type ImplResult = Result<Custom<(ContentType, Json<IndexMap<String, MockType>>)>, Custom<Json<ApiError>>>;
+
+/// the query parameters and headers of request, for checking the typed parameters of endpoint
+pub struct ImplRequest {
+  query: Vec<(String, String)>,
+  headers: Vec<(String, String)>,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ImplRequest {
+  type Error = ();
+
+  async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+    let query = match request.uri().query() {
+      Some(query) => query.segments().map(|(key, value)| (key.to_string(), value.to_string())).collect(),
+      None => vec![],
+    };
+    let headers = request.headers().iter()
+      .map(|header| (header.name().to_string(), header.value().to_string()))
+      .collect();
+
+    Outcome::Success(ImplRequest { query, headers })
+  }
+}
+
+#[get("/<path..>", rank = 20)]
+pub async fn get_endpoint(path: PathBuf, request: ImplRequest, config: &State<MockServerConfig>) -> ImplResult {
+  mock_endpoint(HttpMethod::GET, &path, &request, None, &config.context_map)
+}
+
+#[post("/<path..>", data = "<body>", rank = 20)]
+pub async fn post_endpoint(path: PathBuf, request: ImplRequest, body: Option<Json<Value>>, config: &State<MockServerConfig>) -> ImplResult {
+  mock_endpoint(HttpMethod::POST, &path, &request, body, &config.context_map)
+}
+
+#[put("/<path..>", data = "<body>", rank = 20)]
+pub async fn put_endpoint(path: PathBuf, request: ImplRequest, body: Option<Json<Value>>, config: &State<MockServerConfig>) -> ImplResult {
+  mock_endpoint(HttpMethod::PUT, &path, &request, body, &config.context_map)
+}
+
+#[patch("/<path..>", data = "<body>", rank = 20)]
+pub async fn patch_endpoint(path: PathBuf, request: ImplRequest, body: Option<Json<Value>>, config: &State<MockServerConfig>) -> ImplResult {
+  mock_endpoint(HttpMethod::PATCH, &path, &request, body, &config.context_map)
+}
+
+#[delete("/<path..>", rank = 20)]
+pub async fn delete_endpoint(path: PathBuf, request: ImplRequest, config: &State<MockServerConfig>) -> ImplResult {
+  mock_endpoint(HttpMethod::DELETE, &path, &request, None, &config.context_map)
+}
+
+/// the endpoints of implementations, which are served by the mock server
+pub fn impl_endpoints(context_map: &ContextMap) -> Vec<&HttpEndpoint> {
+  context_map.implementations.iter().filter_map(|implementation| match implementation {
+    Implementation::PublishHttpApi(HttpApiImpl { endpoint, .. }) => Some(endpoint),
+    _ => None,
+  }).collect()
+}
+
+/// the path of endpoint without the scheme and host, like `/orders/{id}` for `http://localhost/orders/{id}`
+pub fn route_path(endpoint: &HttpEndpoint) -> &str {
+  match endpoint.path.split_once("://") {
+    Some((_, rest)) => rest.find('/').map(|index| &rest[index..]).unwrap_or("/"),
+    None => &endpoint.path,
+  }
+}
+
+fn mock_endpoint(method: HttpMethod, path: &Path, request: &ImplRequest, body: Option<Json<Value>>, context_map: &ContextMap) -> ImplResult {
+  let path = format!("/{}", path.display());
+  let (endpoint, placeholders) = impl_endpoints(context_map).into_iter()
+    .filter(|endpoint| endpoint.method == method)
+    .find_map(|endpoint| match_path(route_path(endpoint), &path).map(|values| (endpoint, values)))
+    .ok_or_else(|| error(Status::NotFound, format!("no endpoint for {:?} {}", method, path)))?;
+
+  let mut errors = check_params(&endpoint.path_params, &placeholders, "path parameter", false);
+  errors.extend(check_params(&endpoint.query_params, &request.query, "query parameter", true));
+
+  let headers: Vec<(String, String)> = endpoint.headers.iter().filter_map(|header| {
+    let name = HttpEndpoint::header_name(header);
+    request.headers.iter()
+      .find(|(key, _)| key.eq_ignore_ascii_case(name))
+      .map(|(_, value)| (header.name.clone(), value.clone()))
+  }).collect();
+  errors.extend(check_params(&endpoint.headers, &headers, "header", true));
+
+  if let (Some(body), Some(request)) = (body, &endpoint.request) {
+    if let Some(struct_) = context_map.get_struct(&request.name) {
+      errors.extend(validate_body(&struct_.fields, &body));
+    }
+  }
+
+  if !errors.is_empty() {
+    return Err(error(Status::BadRequest, errors.join("; ")));
+  }
+
+  let variant = response_variant(endpoint, request)?;
+  let status = Status::from_code(variant.status).unwrap_or(Status::Ok);
+  let content_type = ContentType::parse_flexible(variant.media_type()).unwrap_or(ContentType::JSON);
+  Ok(Custom(status, (content_type, Json(mock_response(&variant, context_map)))))
+}
+
+/// the variant of status in `X-Mock-Status` header, or the first successful one
+fn response_variant(endpoint: &HttpEndpoint, request: &ImplRequest) -> Result<ResponseVariant, Custom<Json<ApiError>>> {
+  let status = request.headers.iter().find(|(key, _)| key.eq_ignore_ascii_case(MOCK_STATUS_HEADER));
+  match status {
+    Some((_, status)) => status.parse::<u16>().ok()
+      .and_then(|status| endpoint.response_of(status))
+      .ok_or_else(|| error(Status::BadRequest, format!("response of status {} is not declared", status))),
+    None => {
+      let variants = endpoint.response_variants();
+      Ok(variants.iter().find(|variant| variant.is_success()).cloned().unwrap_or_else(|| ResponseVariant::new(200, "")))
+    }
+  }
+}
+
+/// match the path with the route, like `/orders/{id}`, returns the values of placeholders
+fn match_path(route: &str, path: &str) -> Option<Vec<(String, String)>> {
+  let route_segments: Vec<&str> = route.trim_matches('/').split('/').collect();
+  let path_segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+  if route_segments.len() != path_segments.len() {
+    return None;
+  }
+
+  let mut values = vec![];
+  for (route_segment, path_segment) in route_segments.iter().zip(path_segments.iter()) {
+    match route_segment.strip_prefix('{').and_then(|it| it.strip_suffix('}')) {
+      Some(name) => values.push((name.to_string(), path_segment.to_string())),
+      None if route_segment == path_segment => {}
+      None => return None,
+    }
+  }
+
+  Some(values)
+}
+
+/// check the parameters are given and in their types, then check them by the field validations,
+/// a missing parameter is reported only when `required_when_missing` and it's not optional
+fn check_params(params: &[Field], values: &[(String, String)], kind: &str, required_when_missing: bool) -> Vec<String> {
+  let mut errors = vec![];
+  let mut typed = Map::new();
+  for param in params {
+    match values.iter().find(|(name, _)| name == &param.name) {
+      Some((_, value)) => match typed_value(param, value) {
+        Some(value) => {
+          typed.insert(param.name.clone(), value);
+        }
+        None => errors.push(format!("{} {} should be {}", kind, param.name, param.type_type)),
+      },
+      None if required_when_missing && HttpEndpoint::is_required(param) => {
+        errors.push(format!("{} {} is required", kind, param.name));
+      }
+      None => {}
+    }
+  }
+
+  errors.extend(validate_body(params, &Value::Object(typed)));
+  errors
+}
+
+/// the value in the type of parameter, none if it can't be converted
+fn typed_value(param: &Field, value: &str) -> Option<Value> {
+  match param.type_expr.name().to_lowercase().as_str() {
+    "int" | "integer" | "long" | "short" => value.parse::<i64>().ok().map(Value::from),
+    "float" | "double" | "decimal" | "bigdecimal" => value.parse::<f64>().ok().map(Value::from),
+    "bool" | "boolean" => value.parse::<bool>().ok().map(Value::from),
+    "uuid" => uuid::Uuid::parse_str(value).ok().map(|_| Value::from(value)),
+    _ => Some(Value::from(value)),
+  }
+}
+
+/// the fake value of response struct or entity, or an empty object if its type is not declared
+fn mock_response(variant: &ResponseVariant, context_map: &ContextMap) -> IndexMap<String, MockType> {
+  let name = &variant.name;
+  let fields = match (context_map.get_struct(name), context_map.get_entity(name)) {
+    (Some(struct_), _) => struct_.fields,
+    (None, Some(entity)) => entity.fields,
+    _ => return IndexMap::new(),
+  };
+
+  FakeValue::fake_with_custom(&fields, &context_map.structs, &context_map.enums)
+}
+
+fn error(status: Status, msg: String) -> Custom<Json<ApiError>> {
+  Custom(status, Json(ApiError { msg }))
+}
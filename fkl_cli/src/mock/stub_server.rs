@@ -10,6 +10,7 @@ use fkl_mir::{Aggregate, ContextMap, Entity};
 use fkl_mir::default_config;
 
 pub use super::stub_aggregate_api;
+pub use super::stub_impl_api;
 
 #[get("/")]
 pub(crate) async fn index(conf: &State<MockServerConfig>) -> Json<ContextMap> {
@@ -46,7 +47,12 @@ pub fn feakin_rocket(context_map: &ContextMap) -> Rocket<Build> {
 
   rocket::custom(figment)
     .mount("/", routes![
-      index
+      index,
+      stub_impl_api::get_endpoint,
+      stub_impl_api::post_endpoint,
+      stub_impl_api::put_endpoint,
+      stub_impl_api::patch_endpoint,
+      stub_impl_api::delete_endpoint,
     ])
     .mount("/api", routes![
       stub_aggregate_api::get_aggregate_by_id,
@@ -72,14 +78,20 @@ fn merge_config(context_map: &ContextMap) -> MockServerConfig {
   server_config
 }
 
+/// the apis of aggregates, then the endpoints of implementations
 pub fn gen_api_list(context_map: &ContextMap) -> Vec<String> {
-  context_map.contexts.iter().flat_map(|env| {
+  let aggregate_apis = context_map.contexts.iter().flat_map(|env| {
     env.aggregates.iter().flat_map(|aggregate| {
       aggregate.entities.iter().flat_map(|entity| {
         collect_apis(aggregate, entity)
       })
     })
-  }).collect()
+  });
+
+  let impl_apis = stub_impl_api::impl_endpoints(context_map).into_iter()
+    .map(|endpoint| stub_impl_api::route_path(endpoint).to_string());
+
+  aggregate_apis.chain(impl_apis).collect()
 }
 
 fn collect_apis(aggregate: &Aggregate, entity: &Entity) -> Vec<String> {
@@ -94,7 +106,7 @@ fn collect_apis(aggregate: &Aggregate, entity: &Entity) -> Vec<String> {
 
 #[cfg(test)]
 mod test {
-  use rocket::http::Status;
+  use rocket::http::{ContentType, Header, Status};
   use rocket::local::blocking::Client;
 
   use fkl_mir::ContextMap;
@@ -190,4 +202,104 @@ Entity Seat {
     let response = client.delete("/api/ticket/seat/1").dispatch();
     assert_eq!(response.status(), Status::Ok);
   }
+
+  #[test]
+  fn reject_invalid_request() {
+    let context_map: ContextMap = parse(r#"
+Context TicketContext {
+  Aggregate Ticket {
+    Entity Seat {
+      Struct {
+        row: Int @required @range(1, 30);
+        number: Int;
+      }
+    }
+  }
+}
+"#).unwrap();
+
+    let client = Client::tracked(feakin_rocket(&context_map)).expect("valid rocket instance");
+    let response = client.put("/api/ticket/seat")
+      .header(ContentType::JSON)
+      .body(r#"{ "row": 42, "number": 1 }"#)
+      .dispatch();
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+
+    let response = client.put("/api/ticket/seat")
+      .header(ContentType::JSON)
+      .body(r#"{ "row": 12, "number": 1 }"#)
+      .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+  }
+
+  #[test]
+  fn typed_endpoint_params() {
+    let context_map: ContextMap = parse(r#"
+impl GetOrder {
+  endpoint {
+    GET "/orders/{id}";
+    path { id: Long; }
+    query { page: Int = 1; size: Int @range(1, 100); }
+    header { @name("X-Tenant") tenant: String; }
+    response: Order;
+  }
+}
+
+struct Order {
+  id: Long;
+  title: String;
+}
+"#).unwrap();
+
+    let client = Client::tracked(feakin_rocket(&context_map)).expect("valid rocket instance");
+    let response = client.get("/orders/42?size=10").header(Header::new("X-Tenant", "shop")).dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    assert!(response.into_string().unwrap().contains("title"));
+
+    let response = client.get("/orders/abc?size=10").header(Header::new("X-Tenant", "shop")).dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+
+    let response = client.get("/orders/42?size=200").dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+    assert_eq!(response.into_string().unwrap(), r#"{"msg":"size should be in range 1..100; header tenant is required"}"#);
+
+    let response = client.get("/customers/42").dispatch();
+    assert_eq!(response.status(), Status::NotFound);
+  }
+
+  #[test]
+  fn endpoint_response_variants() {
+    let context_map: ContextMap = parse(r#"
+impl CreateOrder {
+  endpoint {
+    POST "/orders";
+    responses {
+      201: Order;
+      422: ErrorBody as "application/problem+json";
+    }
+  }
+}
+
+struct Order {
+  id: Long;
+}
+
+struct ErrorBody {
+  message: String;
+}
+"#).unwrap();
+
+    let client = Client::tracked(feakin_rocket(&context_map)).expect("valid rocket instance");
+    let response = client.post("/orders").dispatch();
+    assert_eq!(response.status(), Status::Created);
+    assert_eq!(response.content_type(), Some(ContentType::JSON));
+
+    let response = client.post("/orders").header(Header::new("X-Mock-Status", "422")).dispatch();
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+    assert_eq!(response.content_type().unwrap().to_string(), "application/problem+json");
+    assert!(response.into_string().unwrap().contains("message"));
+
+    let response = client.post("/orders").header(Header::new("X-Mock-Status", "500")).dispatch();
+    assert_eq!(response.status(), Status::BadRequest);
+  }
 }
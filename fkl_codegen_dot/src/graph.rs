@@ -46,6 +46,10 @@ impl Graph {
     self.name = name.to_string();
   }
 
+  pub fn add_graph_style(&mut self, style: &str) {
+    self.graph_style.push(style.to_string());
+  }
+
   pub fn add_node_style(&mut self, style: &str) {
     self.node_styles.push(style.to_string());
   }
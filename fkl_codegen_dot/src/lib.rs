@@ -98,6 +98,18 @@ mod tests {
 }"#);
   }
 
+  #[test]
+  fn graph_with_styled_node() {
+    let mut graph = Graph::new("styled_node");
+    graph.add_graph_style("rankdir=LR");
+    graph.add_node(Node::styled("a", "", vec!["shape=point".to_string()]));
+
+    assert_eq!(format!("{}", graph), r#"digraph styled_node {
+  rankdir=LR;
+  a [label="" shape=point];
+}"#);
+  }
+
   #[test]
   fn graph_width_rect_shape_style() {
     let mut graph = Graph::new("graph_width_rect_shape_style");
@@ -5,20 +5,29 @@ use std::fmt::Formatter;
 pub struct Node {
   name: String,
   label: String,
+  style: Vec<String>,
 }
 
 impl Node {
   pub fn new(name: &str) -> Self {
-    Node { name: name.to_string(), label: name.to_string() }
+    Node { name: name.to_string(), label: name.to_string(), style: vec![] }
   }
 
   pub fn label(name: &str, label: &str) -> Self {
-    Node { name: name.to_string(), label: label.to_string() }
+    Node { name: name.to_string(), label: label.to_string(), style: vec![] }
+  }
+
+  pub fn styled(name: &str, label: &str, style: Vec<String>) -> Self {
+    Node { name: name.to_string(), label: label.to_string(), style }
   }
 }
 
 impl fmt::Display for Node {
   fn fmt(&self, out: &mut Formatter<'_>) -> fmt::Result {
-    out.write_str(&format!("{} [label=\"{}\"];", self.name, self.label))
+    if !self.style.is_empty() {
+      out.write_str(&format!("{} [label=\"{}\" {}];", self.name, self.label, self.style.join(" ")))
+    } else {
+      out.write_str(&format!("{} [label=\"{}\"];", self.name, self.label))
+    }
   }
 }
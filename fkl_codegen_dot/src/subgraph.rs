@@ -10,6 +10,7 @@ pub struct Subgraph {
   label: String,
   // for indent
   depth: usize,
+  styles: Vec<String>,
   nodes: Vec<Node>,
   edges: Vec<Edge>,
   subgraph: Vec<Subgraph>,
@@ -21,6 +22,7 @@ impl Subgraph {
       name: cluster_name(name),
       label: label.to_string(),
       depth: 1,
+      styles: Vec::new(),
       nodes: Vec::new(),
       edges: Vec::new(),
       subgraph: Vec::new(),
@@ -38,6 +40,11 @@ impl Subgraph {
   pub fn set_depth(&mut self, depth: usize) {
     self.depth = depth;
   }
+
+  /// the attribute of cluster, like `color=red`
+  pub fn add_style(&mut self, style: &str) {
+    self.styles.push(style.to_string());
+  }
 }
 
 impl fmt::Display for Subgraph {
@@ -48,6 +55,10 @@ impl fmt::Display for Subgraph {
 
     out.write_str(&format!("{}label=\"{}\";\n", space, self.label))?;
 
+    for style in &self.styles {
+      out.write_str(&format!("{}{};\n", space, style))?
+    }
+
     for node in &self.nodes {
       out.write_str(&format!("{}{}\n", space, node))?
     }
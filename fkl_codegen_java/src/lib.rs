@@ -6,6 +6,10 @@ pub mod comments;
 pub use spring_gen::entity_gen::*;
 pub use spring_gen::jpa_gen::*;
 pub use spring_gen::spring_code_gen::*;
+pub use spring_gen::value_object_gen::*;
+pub use spring_gen::enum_gen::*;
+pub use spring_gen::validation_gen::*;
+pub use spring_gen::type_gen::*;
 
 use fkl_mir::implementation::HttpApiImpl;
 
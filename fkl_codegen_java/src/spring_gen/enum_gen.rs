@@ -0,0 +1,163 @@
+use fkl_mir::{Entity, Enum, Transition};
+
+/// generate enum as a Java enum, the variants keep the declared names, so the default values
+/// like `Status.Open` are valid in Java too.
+pub fn gen_enum(enum_: &Enum, package: &str) -> String {
+  let mut code = String::new();
+  if !package.is_empty() {
+    code.push_str(&format!("package {};\n\n", package));
+  }
+
+  code.push_str(&format!("public enum {} {{\n", enum_.name));
+  let variants = enum_.variants.iter()
+    .map(|variant| format!("    {}", variant))
+    .collect::<Vec<String>>()
+    .join(",\n");
+  if !variants.is_empty() {
+    code.push_str(&variants);
+    code.push('\n');
+  }
+
+  code.push_str("}\n");
+  code
+}
+
+/// generate the states of entity as a Java enum named `{Entity}State`, each trigger is a method which
+/// returns the next state, and throws `IllegalStateException` in the states it's not allowed. the
+/// transitions without trigger are named by the target state, like `toApproved`.
+pub fn gen_state_enum(entity: &Entity, package: &str) -> Option<String> {
+  let states = entity.states.as_ref()?;
+  let enum_name = state_enum_name(entity);
+
+  let mut code = String::new();
+  if !package.is_empty() {
+    code.push_str(&format!("package {};\n\n", package));
+  }
+
+  code.push_str(&format!("public enum {} {{\n", enum_name));
+  let variants = states.states.iter()
+    .map(|state| format!("    {}", state))
+    .collect::<Vec<String>>()
+    .join(",\n");
+  code.push_str(&variants);
+  code.push_str(";\n");
+
+  let mut methods: Vec<(String, Vec<&Transition>)> = vec![];
+  for transition in &states.transitions {
+    let method = transition_method(transition);
+    match methods.iter_mut().find(|(name, _)| name == &method) {
+      // the nondeterministic transitions are reported by the state check, only the first one is kept
+      Some((_, transitions)) if transitions.iter().any(|it| it.from == transition.from) => {}
+      Some((_, transitions)) => transitions.push(transition),
+      None => methods.push((method, vec![transition])),
+    }
+  }
+
+  for (method, transitions) in methods {
+    code.push_str(&format!("\n    public {} {}() {{\n", enum_name, method));
+    code.push_str("        switch (this) {\n");
+    for transition in transitions {
+      code.push_str(&format!("            case {}:\n", transition.from));
+      code.push_str(&format!("                return {};\n", transition.to));
+    }
+    code.push_str("            default:\n");
+    code.push_str(&format!("                throw new IllegalStateException(\"cannot {} in state \" + this);\n", method));
+    code.push_str("        }\n");
+    code.push_str("    }\n");
+  }
+
+  code.push_str("}\n");
+  Some(code)
+}
+
+pub fn state_enum_name(entity: &Entity) -> String {
+  format!("{}State", entity.name)
+}
+
+fn transition_method(transition: &Transition) -> String {
+  if transition.trigger.is_empty() {
+    return format!("to{}", transition.to);
+  }
+
+  let mut chars = transition.trigger.chars();
+  match chars.next() {
+    Some(first) => first.to_lowercase().chain(chars).collect(),
+    None => String::new(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use fkl_mir::{Entity, Enum, StateMachine, Transition};
+
+  use crate::{gen_enum, gen_state_enum};
+
+  #[test]
+  fn java_enum() {
+    let mut status = Enum::new("TicketStatus");
+    status.variants = vec!["Open".to_string(), "Closed".to_string()];
+
+    assert_eq!(gen_enum(&status, "com.feakin.domain"), r#"package com.feakin.domain;
+
+public enum TicketStatus {
+    Open,
+    Closed
+}
+"#);
+  }
+
+  #[test]
+  fn state_enum() {
+    let transition = |from: &str, to: &str, trigger: &str| Transition {
+      from: from.to_string(),
+      to: to.to_string(),
+      trigger: trigger.to_string(),
+      span: Default::default(),
+    };
+    let mut order = Entity::new("Order");
+    order.states = Some(StateMachine::new(vec![
+      transition("Draft", "Submitted", "Submit"),
+      transition("Submitted", "Draft", "Reject"),
+      transition("Submitted", "Approved", ""),
+      transition("Approved", "Draft", "Reject"),
+      transition("Draft", "Approved", "Submit"),
+    ]));
+
+    assert_eq!(gen_state_enum(&order, "").unwrap(), r#"public enum OrderState {
+    Draft,
+    Submitted,
+    Approved;
+
+    public OrderState submit() {
+        switch (this) {
+            case Draft:
+                return Submitted;
+            default:
+                throw new IllegalStateException("cannot submit in state " + this);
+        }
+    }
+
+    public OrderState reject() {
+        switch (this) {
+            case Submitted:
+                return Draft;
+            case Approved:
+                return Draft;
+            default:
+                throw new IllegalStateException("cannot reject in state " + this);
+        }
+    }
+
+    public OrderState toApproved() {
+        switch (this) {
+            case Submitted:
+                return Approved;
+            default:
+                throw new IllegalStateException("cannot toApproved in state " + this);
+        }
+    }
+}
+"#);
+    assert_eq!(gen_state_enum(&Entity::new("Ticket"), ""), None);
+  }
+}
@@ -2,3 +2,7 @@ pub mod spring_code_gen;
 pub mod entity_gen;
 pub mod jpa_gen;
 pub mod controller_gen;
+pub mod value_object_gen;
+pub mod enum_gen;
+pub mod validation_gen;
+pub mod type_gen;
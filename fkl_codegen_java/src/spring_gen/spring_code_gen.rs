@@ -1,7 +1,7 @@
-use fkl_mir::{Flow, HttpMethod};
+use fkl_mir::{Field, Flow, HttpMethod};
 use fkl_mir::implementation::{HttpEndpoint, Request, Response};
 
-use crate::{comments, naming};
+use crate::{comments, java_type, naming};
 
 /// generate spring code for a single endpoint
 /// contains the following:
@@ -60,9 +60,47 @@ impl SpringCodeGen {
   }
 
   fn method_header(http: &HttpEndpoint, method_name: &String) -> String {
+    let mut params = Self::params_to_strings(http);
     let request = Self::request_to_string(&http.request);
+    if !request.is_empty() {
+      params.push(request);
+    }
+
     let return_type = Self::response_to_return_type(&http.response);
-    format!("public {} {}({})", return_type, method_name, request)
+    format!("public {} {}({})", return_type, method_name, params.join(", "))
+  }
+
+  /// the path variables, request params and request headers, in declaration order
+  fn params_to_strings(http: &HttpEndpoint) -> Vec<String> {
+    let path_params = http.path_params.iter()
+      .map(|param| format!("@PathVariable {} {}", java_type(param), param.name));
+
+    let query_params = http.query_params.iter()
+      .map(|param| Self::param_to_string("@RequestParam", param, None));
+
+    let headers = http.headers.iter()
+      .map(|header| Self::param_to_string("@RequestHeader", header, Some(HttpEndpoint::header_name(header))));
+
+    path_params.chain(query_params).chain(headers).collect()
+  }
+
+  /// like `@RequestParam(defaultValue = "1") Integer page` or `@RequestHeader(value = "X-Trace-Id", required = false) String traceId`
+  fn param_to_string(annotation: &str, param: &Field, name: Option<&str>) -> String {
+    let mut attributes = vec![];
+    if let Some(name) = name.filter(|name| *name != param.name) {
+      attributes.push(format!("value = \"{}\"", name));
+    }
+    match &param.initializer {
+      Some(value) => attributes.push(format!("defaultValue = \"{}\"", value.trim_matches('"'))),
+      None if !HttpEndpoint::is_required(param) => attributes.push("required = false".to_string()),
+      None => {}
+    }
+
+    let annotation = match attributes.len() {
+      0 => annotation.to_string(),
+      _ => format!("{}({})", annotation, attributes.join(", ")),
+    };
+    format!("{} {} {}", annotation, java_type(param), param.name)
   }
 
   fn method_name(http: &HttpEndpoint) -> String {
@@ -96,8 +134,9 @@ impl SpringCodeGen {
 
 #[cfg(test)]
 mod tests {
-  use fkl_mir::HttpMethod;
+  use fkl_mir::{Field, HttpMethod};
   use fkl_mir::implementation::{HttpEndpoint, Request, Response};
+  use fkl_parser::parse_type_expr;
 
   use crate::spring_gen::spring_code_gen::SpringCodeGen;
 
@@ -152,6 +191,7 @@ import org.springframework.web.bind.annotation.RestController;
         name: "List<Employee>".to_string(),
         post_validate: None,
       }),
+      ..Default::default()
     }, &None,
     );
 
@@ -171,9 +211,35 @@ import org.springframework.web.bind.annotation.RestController;
         pre_validate: None,
       }),
       response: None,
+      ..Default::default()
     }, &None,
     );
 
     assert_eq!(annotation.method_header, "public void createEmployee(@RequestBody CreateEmployeeRequest request)");
   }
+
+  #[test]
+  fn method_header_with_params() {
+    let mut trace_id = Field::new("traceId", parse_type_expr("String?").unwrap());
+    trace_id.annotations = vec![fkl_mir::Annotation::new("name", vec!["X-Trace-Id".to_string()])];
+    let mut page = Field::new("page", parse_type_expr("Integer").unwrap());
+    page.initializer = Some("1".to_string());
+
+    let annotation = SpringCodeGen::from(&HttpEndpoint {
+      name: "OrderUpdated".to_string(),
+      method: HttpMethod::PUT,
+      path: "/orders/{id}".to_string(),
+      path_params: vec![Field::new("id", parse_type_expr("Long").unwrap())],
+      query_params: vec![page],
+      headers: vec![trace_id],
+      request: Some(Request {
+        name: "UpdateOrderRequest".to_string(),
+        pre_validate: None,
+      }),
+      ..Default::default()
+    }, &None,
+    );
+
+    assert_eq!(annotation.method_header, "public void updateOrder(@PathVariable Long id, @RequestParam(defaultValue = \"1\") Integer page, @RequestHeader(value = \"X-Trace-Id\", required = false) String traceId, @RequestBody UpdateOrderRequest request)");
+  }
 }
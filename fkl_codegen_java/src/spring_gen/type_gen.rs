@@ -0,0 +1,49 @@
+use fkl_mir::{Field, TypeExpr};
+
+/// the Java type of field, fields without a lowered type expression keep their source text.
+pub fn java_type(field: &Field) -> String {
+  if field.type_expr.is_empty() {
+    return field.type_type.clone();
+  }
+
+  java_type_expr(&field.type_expr)
+}
+
+/// optional types are plain references in Java, so only the inner type is kept.
+pub fn java_type_expr(expr: &TypeExpr) -> String {
+  match expr {
+    TypeExpr::Named(name) => name.clone(),
+    TypeExpr::Generic(name, args) => {
+      let args = args.iter().map(java_type_expr).collect::<Vec<String>>().join(", ");
+      format!("{}<{}>", name, args)
+    }
+    TypeExpr::Array(inner) => format!("{}[]", java_type_expr(inner)),
+    TypeExpr::Optional(inner) => java_type_expr(inner),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use fkl_mir::Field;
+  use fkl_parser::parse_type_expr;
+
+  use crate::{java_type, java_type_expr};
+
+  #[test]
+  fn nested_generic() {
+    assert_eq!(java_type_expr(&parse_type_expr("List<Map<String, Order>>").unwrap()), "List<Map<String, Order>>");
+    assert_eq!(java_type_expr(&parse_type_expr("Order?").unwrap()), "Order");
+    assert_eq!(java_type_expr(&parse_type_expr("Order[]?").unwrap()), "Order[]");
+  }
+
+  #[test]
+  fn field_without_type_expr() {
+    let field = Field {
+      name: "amount".to_string(),
+      type_type: "BigDecimal".to_string(),
+      ..Default::default()
+    };
+
+    assert_eq!(java_type(&field), "BigDecimal");
+  }
+}
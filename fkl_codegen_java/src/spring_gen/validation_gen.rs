@@ -0,0 +1,63 @@
+use fkl_mir::{Field, Validation};
+
+/// the Bean Validation annotations of field, the `Compare` validation has no standard annotation,
+/// so it's skipped.
+pub fn validation_annotations(field: &Field) -> Vec<String> {
+  field.validations.iter().filter_map(|validation| {
+    match validation {
+      Validation::Required(true) => Some("@NotNull".to_string()),
+      Validation::Range(range) => {
+        let annotations = [range.min.map(|min| bound_annotation("Min", min)), range.max.map(|max| bound_annotation("Max", max))];
+        let joined = annotations.into_iter().flatten().collect::<Vec<String>>().join(" ");
+        if joined.is_empty() { None } else { Some(joined) }
+      }
+      Validation::Length(length) => {
+        let args = [length.min.map(|min| format!("min = {}", min)), length.max.map(|max| format!("max = {}", max))];
+        let args = args.into_iter().flatten().collect::<Vec<String>>().join(", ");
+        Some(format!("@Size({})", args))
+      }
+      Validation::Regex(regex) => Some(format!("@Pattern(regexp = \"{}\")", regex.pattern.replace('\\', "\\\\").replace('"', "\\\""))),
+      _ => None,
+    }
+  }).collect()
+}
+
+/// the `@Min`/`@Max` of integer bound, or the `@DecimalMin`/`@DecimalMax` of decimal bound
+fn bound_annotation(name: &str, bound: f64) -> String {
+  if bound.fract() == 0.0 {
+    format!("@{}({})", name, bound)
+  } else {
+    format!("@Decimal{}(\"{}\")", name, bound)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use fkl_mir::{Field, LengthValidation, RangeValidation, RegexValidation, Validation};
+
+  use crate::validation_annotations;
+
+  #[test]
+  fn bean_validation() {
+    let field = Field {
+      name: "code".to_string(),
+      type_type: "String".to_string(),
+      validations: vec![
+        Validation::Required(true),
+        Validation::Length(LengthValidation { min: Some(1), max: Some(64) }),
+        Validation::Range(RangeValidation { min: None, max: Some(10.0) }),
+        Validation::Range(RangeValidation { min: Some(-1.0), max: Some(1.5) }),
+        Validation::Regex(RegexValidation { pattern: "^\\d+$".to_string() }),
+      ],
+      ..Default::default()
+    };
+
+    assert_eq!(validation_annotations(&field), vec![
+      "@NotNull",
+      "@Size(min = 1, max = 64)",
+      "@Max(10)",
+      "@Min(-1) @DecimalMax(\"1.5\")",
+      "@Pattern(regexp = \"^\\\\d+$\")",
+    ]);
+  }
+}
@@ -0,0 +1,102 @@
+use fkl_mir::ValueObject;
+
+use crate::{java_type, validation_annotations};
+
+/// generate value object as a JPA embeddable class, the fields are assigned by an all-args
+/// constructor and only have getters, the protected no-args constructor is required by JPA.
+pub fn gen_value_object(vo: &ValueObject, package: &str) -> String {
+  let mut code = String::new();
+  if !package.is_empty() {
+    code.push_str(&format!("package {};\n\n", package));
+  }
+
+  code.push_str("import javax.persistence.Embeddable;\n");
+  if vo.fields.iter().any(|field| !field.validations.is_empty()) {
+    code.push_str("import javax.validation.constraints.*;\n");
+  }
+  code.push('\n');
+  code.push_str("@Embeddable\n");
+  code.push_str(&format!("public class {} {{\n", vo.name));
+
+  for field in &vo.fields {
+    for annotation in validation_annotations(field) {
+      code.push_str(&format!("    {}\n", annotation));
+    }
+    code.push_str(&format!("    private {} {};\n", java_type(field), field.name));
+  }
+
+  let params = vo.fields.iter()
+    .map(|field| format!("{} {}", java_type(field), field.name))
+    .collect::<Vec<String>>()
+    .join(", ");
+
+  code.push_str(&format!("\n    protected {}() {{\n    }}\n", vo.name));
+  code.push_str(&format!("\n    public {}({}) {{\n", vo.name, params));
+  for field in &vo.fields {
+    code.push_str(&format!("        this.{} = {};\n", field.name, field.name));
+  }
+  code.push_str("    }\n");
+
+  for field in &vo.fields {
+    code.push_str(&format!("\n    public {} get{}() {{\n", java_type(field), capitalize(&field.name)));
+    code.push_str(&format!("        return {};\n", field.name));
+    code.push_str("    }\n");
+  }
+
+  code.push_str("}\n");
+  code
+}
+
+fn capitalize(str: &str) -> String {
+  let mut chars = str.chars();
+  match chars.next() {
+    Some(first) => first.to_uppercase().chain(chars).collect(),
+    None => String::new(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use fkl_mir::{Field, ValueObject};
+
+  use crate::gen_value_object;
+
+  #[test]
+  fn embeddable_value_object() {
+    let vo = ValueObject {
+      name: "Money".to_string(),
+      fields: vec![
+        Field { name: "amount".to_string(), type_type: "BigDecimal".to_string(), ..Default::default() },
+        Field { name: "currency".to_string(), type_type: "String".to_string(), ..Default::default() },
+      ],
+      ..Default::default()
+    };
+
+    assert_eq!(gen_value_object(&vo, "com.feakin.domain"), r#"package com.feakin.domain;
+
+import javax.persistence.Embeddable;
+
+@Embeddable
+public class Money {
+    private BigDecimal amount;
+    private String currency;
+
+    protected Money() {
+    }
+
+    public Money(BigDecimal amount, String currency) {
+        this.amount = amount;
+        this.currency = currency;
+    }
+
+    public BigDecimal getAmount() {
+        return amount;
+    }
+
+    public String getCurrency() {
+        return currency;
+    }
+}
+"#);
+  }
+}
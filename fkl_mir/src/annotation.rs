@@ -0,0 +1,29 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+/// the metadata of declaration or field, like `@owner("sales")`, `@deprecated` or `@table(users)`,
+/// which isn't checked by the compiler, but read by code generators and plugins.
+/// - [`key`]: the name of annotation, like `owner`
+/// - [`value`]: the arguments, the strings are unquoted
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct Annotation {
+  pub key: String,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub value: Vec<String>,
+}
+
+impl Annotation {
+  pub fn new(key: &str, value: Vec<String>) -> Self {
+    Annotation { key: key.to_string(), value }
+  }
+
+  /// find the annotation by key, like `find(&entity.annotations, "table")`
+  pub fn find<'a>(annotations: &'a [Annotation], key: &str) -> Option<&'a Annotation> {
+    annotations.iter().find(|annotation| annotation.key == key)
+  }
+
+  /// the first argument, like `sales` for `@owner("sales")`
+  pub fn first_value(&self) -> Option<&str> {
+    self.value.first().map(|value| value.as_str())
+  }
+}
@@ -1,6 +1,6 @@
 use serde::Deserialize;
 use serde::Serialize;
-use crate::Flow;
+use crate::{Annotation, Flow, Span};
 use crate::implementation::HttpEndpoint;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
@@ -12,7 +12,14 @@ pub struct HttpApiImpl {
   // like "$moduleName:packageName
   pub qualified: String,
   pub endpoint: HttpEndpoint,
+  /// the name of command or query which is handled by the endpoint, see [`crate::ContextMap::commands`]
+  #[serde(default, skip_serializing_if = "String::is_empty")]
+  pub command: String,
   pub flow: Option<Flow>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub annotations: Vec<Annotation>,
+  #[serde(default, skip_serializing_if = "Span::is_empty")]
+  pub span: Span,
 }
 
 impl HttpApiImpl {
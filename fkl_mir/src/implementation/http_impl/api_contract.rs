@@ -1,8 +1,13 @@
 use serde::Deserialize;
 use serde::Serialize;
+use crate::{Annotation, Field, TypeExpr};
 use crate::authorization::HttpAuthorization;
 use crate::implementation::validation::Validation;
 
+/// - [`path_params`]: the types of `{placeholder}`s in path
+/// - [`query_params`]: the query parameters, the ones with default value or optional type are not required
+/// - [`headers`]: the request headers, named by the `@name("X-Request-Id")` annotation or the field name
+/// - [`responses`]: the responses by status code, the [`response`] is the first successful one of them
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 pub struct HttpEndpoint {
   pub name: String,
@@ -12,6 +17,14 @@ pub struct HttpEndpoint {
   pub method: HttpMethod,
   pub request: Option<Request>,
   pub response: Option<Response>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub path_params: Vec<Field>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub query_params: Vec<Field>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub headers: Vec<Field>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub responses: Vec<ResponseVariant>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -56,6 +69,53 @@ impl HttpEndpoint {
       ..Default::default()
     }
   }
+
+  /// the names of `{placeholder}`s in path, like `id` in `/orders/{id}`
+  pub fn placeholders(&self) -> Vec<&str> {
+    self.path.split('{').skip(1)
+      .filter_map(|segment| segment.split_once('}').map(|(name, _)| name))
+      .collect()
+  }
+
+  pub fn path_param(&self, name: &str) -> Option<&Field> {
+    self.path_params.iter().find(|param| param.name == name)
+  }
+
+  /// the name of header in request, like `X-Request-Id`
+  pub fn header_name(header: &Field) -> &str {
+    Annotation::find(&header.annotations, "name")
+      .and_then(|annotation| annotation.first_value())
+      .unwrap_or(&header.name)
+  }
+
+  /// the query parameter or header is required if it has no default value and is not optional
+  pub fn is_required(param: &Field) -> bool {
+    param.initializer.is_none() && !matches!(param.type_expr, TypeExpr::Optional(_))
+  }
+
+  /// the declared responses, or the single `response` as `200` if there are none
+  pub fn response_variants(&self) -> Vec<ResponseVariant> {
+    if !self.responses.is_empty() {
+      return self.responses.clone();
+    }
+
+    match &self.response {
+      Some(response) => vec![ResponseVariant::new(200, &response.name)],
+      None => vec![],
+    }
+  }
+
+  /// the variant of status, the single `response` matches any `2xx` status if there are no declared responses
+  pub fn response_of(&self, status: u16) -> Option<ResponseVariant> {
+    if !self.responses.is_empty() {
+      return self.responses.iter().find(|variant| variant.status == status).cloned();
+    }
+
+    match &self.response {
+      Some(response) if (200..300).contains(&status) => Some(ResponseVariant::new(status, &response.name)),
+      _ => None,
+    }
+  }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
@@ -70,3 +130,32 @@ pub struct Response {
   pub post_validate: Option<Validation>,
 }
 
+/// the response of status code, like `404: ErrorBody as "application/problem+json"`
+/// - [`content_type`]: empty if not declared, see [`ResponseVariant::media_type`]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ResponseVariant {
+  pub status: u16,
+  pub name: String,
+  #[serde(default, skip_serializing_if = "String::is_empty")]
+  pub content_type: String,
+}
+
+impl ResponseVariant {
+  pub fn new(status: u16, name: &str) -> Self {
+    ResponseVariant { status, name: name.to_string(), ..Default::default() }
+  }
+
+  pub fn is_success(&self) -> bool {
+    (200..300).contains(&self.status)
+  }
+
+  /// the declared content type, or `application/json`
+  pub fn media_type(&self) -> &str {
+    match self.content_type.is_empty() {
+      true => "application/json",
+      false => &self.content_type,
+    }
+  }
+}
+
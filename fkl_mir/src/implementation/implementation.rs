@@ -7,6 +7,7 @@ use crate::implementation::Implementation::PublishHttpApi;
 // Todo: Subscribe / Publish / Event / Flow
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[allow(clippy::large_enum_variant)]
 pub enum Implementation {
   PublishHttpApi(HttpApiImpl),
   // todos: add those supports
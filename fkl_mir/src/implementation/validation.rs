@@ -17,12 +17,16 @@ impl Default for Validation {
   }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+/// the bounds of number, which can be negative or decimal, like `@range(-1, 1.5)`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 pub struct RangeValidation {
-  pub min: Option<usize>,
-  pub max: Option<usize>,
+  pub min: Option<f64>,
+  pub max: Option<f64>,
 }
 
+// the bounds are parsed from number literals, so they are never `NaN`
+impl Eq for RangeValidation {}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 pub struct LengthValidation {
   pub min: Option<usize>,
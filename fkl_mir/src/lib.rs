@@ -5,16 +5,27 @@ pub mod binding;
 pub mod flow;
 pub mod default_config;
 pub mod symbol_table;
+pub mod span;
+pub mod annotation;
 
 pub use strategy::context_map::*;
 pub use strategy::domain::*;
 pub use strategy::bounded_context::*;
+pub use strategy::component::*;
+pub use strategy::saga::*;
+pub use strategy::changeset::*;
 pub use tactic::aggregate::*;
 pub use tactic::entity::*;
+pub use tactic::state_machine::*;
 pub use tactic::value_object::*;
+pub use tactic::domain_event::*;
+pub use tactic::service::*;
+pub use tactic::command::*;
 pub use tactic::domain_object::*;
 pub use tactic::block::*;
 pub use tactic::struct_::*;
+pub use tactic::enum_::*;
+pub use tactic::module::*;
 pub use implementation::*;
 
 pub use binding::*;
@@ -23,3 +34,6 @@ pub use flow::flow::*;
 pub use flow::step::*;
 
 pub use default_config::*;
+pub use span::*;
+pub use annotation::*;
+pub use symbol_table::*;
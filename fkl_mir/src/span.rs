@@ -0,0 +1,45 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+/// The source location of a MIR node in the `.fkl` file.
+/// - [`start`], [`end`]: the byte offsets of the node
+/// - [`line`], [`column`]: the 1-based line and column of the start offset
+/// - [`file`]: the source file, when the node is from an included file or a file on disk
+///
+/// an empty span means the node is not from source code, like a context which only used in relations,
+/// or the span has been stripped by [`crate::ContextMap::strip_spans`].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+  pub start: usize,
+  pub end: usize,
+  pub line: usize,
+  pub column: usize,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub file: Option<String>,
+}
+
+impl Span {
+  pub fn new(start: usize, end: usize, line: usize, column: usize) -> Self {
+    Span { start, end, line, column, file: None }
+  }
+
+  pub fn with_file(mut self, file: impl ToString) -> Self {
+    self.file = Some(file.to_string());
+    self
+  }
+
+  pub fn is_empty(&self) -> bool {
+    *self == Span::default()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::Span;
+
+  #[test]
+  fn empty_span() {
+    assert!(Span::default().is_empty());
+    assert!(!Span::new(0, 10, 1, 1).is_empty());
+  }
+}
@@ -3,6 +3,7 @@ use std::fmt::{Display, Formatter};
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::{Annotation, Module, Span};
 use crate::tactic::aggregate::Aggregate;
 
 /// # Bounded Context
@@ -12,11 +13,22 @@ use crate::tactic::aggregate::Aggregate;
 pub struct BoundedContext {
   pub name: String,
   pub aggregates: Vec<Aggregate>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub modules: Vec<Module>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub annotations: Vec<Annotation>,
+  #[serde(default, skip_serializing_if = "Span::is_empty")]
+  pub span: Span,
 }
 
 impl BoundedContext {
   pub fn new(name: &str) -> Self {
-    BoundedContext { name: name.to_string(), aggregates: vec![] }
+    BoundedContext { name: name.to_string(), aggregates: vec![], modules: vec![], annotations: vec![], span: Default::default() }
+  }
+
+  /// the module which groups the aggregate, none for the aggregates directly in context
+  pub fn module_of(&self, aggregate: &str) -> Option<&Module> {
+    self.modules.iter().find(|module| module.contains(aggregate))
   }
 }
 
@@ -28,6 +40,8 @@ pub struct ContextRelation {
   pub connection_type: ConnectionDirection,
   pub source_type: Vec<ContextRelationType>,
   pub target_type: Vec<ContextRelationType>,
+  #[serde(default, skip_serializing_if = "Span::is_empty")]
+  pub span: Span,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
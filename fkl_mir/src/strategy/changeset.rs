@@ -0,0 +1,372 @@
+use std::fmt::{Display, Formatter};
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::{Aggregate, ConnectionDirection, ContextMap, ContextRelation, Field};
+
+/// # Changeset
+/// The changes from the `AsIs` context map to the `ToBe` one, for reviewing the evolution of
+/// architecture. the contexts created by split or removed by merge are not reported as added or
+/// removed again.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Changeset {
+  pub as_is: String,
+  pub to_be: String,
+  pub changes: Vec<Change>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Change {
+  ContextAdded { context: String },
+  ContextRemoved { context: String },
+  /// the aggregates of context are moved into many contexts, some of them are new
+  ContextSplit { context: String, into: Vec<String> },
+  /// the aggregates of many contexts, some of them are removed, are moved into one context
+  ContextsMerged { contexts: Vec<String>, into: String },
+  AggregateAdded { context: String, aggregate: String },
+  AggregateRemoved { context: String, aggregate: String },
+  AggregateMoved { aggregate: String, from: String, to: String },
+  RelationAdded { relation: String },
+  RelationRemoved { relation: String },
+  RelationChanged { from: String, to: String },
+  /// the field of entity or value object, the owner is like `Order.OrderItem`
+  FieldAdded { owner: String, field: String, type_type: String },
+  FieldRemoved { owner: String, field: String, type_type: String },
+  FieldChanged { owner: String, field: String, from: String, to: String },
+}
+
+impl Changeset {
+  pub fn diff(as_is: &ContextMap, to_be: &ContextMap) -> Changeset {
+    let mut changes = vec![];
+    diff_contexts(as_is, to_be, &mut changes);
+    diff_aggregates(as_is, to_be, &mut changes);
+    diff_relations(as_is, to_be, &mut changes);
+    diff_fields(as_is, to_be, &mut changes);
+
+    Changeset { as_is: as_is.name.clone(), to_be: to_be.name.clone(), changes }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.changes.is_empty()
+  }
+}
+
+/// the context of aggregate, the first one if the aggregate name is not unique
+fn context_of<'a>(context_map: &'a ContextMap, aggregate: &str) -> Option<&'a str> {
+  context_map.contexts.iter()
+    .find(|context| context.aggregates.iter().any(|it| it.name == aggregate))
+    .map(|context| context.name.as_str())
+}
+
+fn has_context(context_map: &ContextMap, name: &str) -> bool {
+  context_map.contexts.iter().any(|context| context.name == name)
+}
+
+fn push_unique(names: &mut Vec<String>, name: &str) {
+  if !names.iter().any(|it| it == name) {
+    names.push(name.to_string());
+  }
+}
+
+fn diff_contexts(as_is: &ContextMap, to_be: &ContextMap, changes: &mut Vec<Change>) {
+  let mut created: Vec<String> = vec![];
+  let mut dropped: Vec<String> = vec![];
+
+  for context in &as_is.contexts {
+    let mut into: Vec<String> = vec![];
+    for aggregate in &context.aggregates {
+      if let Some(target) = context_of(to_be, &aggregate.name) {
+        push_unique(&mut into, target);
+      }
+    }
+
+    if into.len() > 1 && into.iter().any(|it| !has_context(as_is, it)) {
+      into.iter().filter(|it| !has_context(as_is, it)).for_each(|it| push_unique(&mut created, it));
+      changes.push(Change::ContextSplit { context: context.name.clone(), into });
+    }
+  }
+
+  for context in &to_be.contexts {
+    let mut contexts: Vec<String> = vec![];
+    for aggregate in &context.aggregates {
+      if let Some(source) = context_of(as_is, &aggregate.name) {
+        push_unique(&mut contexts, source);
+      }
+    }
+
+    if contexts.len() > 1 && contexts.iter().any(|it| !has_context(to_be, it)) {
+      contexts.iter().filter(|it| !has_context(to_be, it)).for_each(|it| push_unique(&mut dropped, it));
+      if !has_context(as_is, &context.name) {
+        push_unique(&mut created, &context.name);
+      }
+      changes.push(Change::ContextsMerged { contexts, into: context.name.clone() });
+    }
+  }
+
+  for context in &as_is.contexts {
+    if !has_context(to_be, &context.name) && !dropped.contains(&context.name) {
+      changes.push(Change::ContextRemoved { context: context.name.clone() });
+    }
+  }
+
+  for context in &to_be.contexts {
+    if !has_context(as_is, &context.name) && !created.contains(&context.name) {
+      changes.push(Change::ContextAdded { context: context.name.clone() });
+    }
+  }
+}
+
+fn diff_aggregates(as_is: &ContextMap, to_be: &ContextMap, changes: &mut Vec<Change>) {
+  for context in &as_is.contexts {
+    for aggregate in &context.aggregates {
+      match context_of(to_be, &aggregate.name) {
+        Some(target) if target != context.name => {
+          changes.push(Change::AggregateMoved { aggregate: aggregate.name.clone(), from: context.name.clone(), to: target.to_string() });
+        }
+        Some(_) => {}
+        None => {
+          changes.push(Change::AggregateRemoved { context: context.name.clone(), aggregate: aggregate.name.clone() });
+        }
+      }
+    }
+  }
+
+  for context in &to_be.contexts {
+    for aggregate in &context.aggregates {
+      if context_of(as_is, &aggregate.name).is_none() {
+        changes.push(Change::AggregateAdded { context: context.name.clone(), aggregate: aggregate.name.clone() });
+      }
+    }
+  }
+}
+
+/// the relations are compared by their contexts regardless of direction
+fn relation_key(relation: &ContextRelation) -> (String, String) {
+  match relation.source <= relation.target {
+    true => (relation.source.clone(), relation.target.clone()),
+    false => (relation.target.clone(), relation.source.clone()),
+  }
+}
+
+/// the relation in DSL style, like `Sales(OpenHostService) -> Billing(AntiCorruptionLayer)`
+pub fn relation_text(relation: &ContextRelation) -> String {
+  let arrow = match relation.connection_type {
+    ConnectionDirection::Undirected => "-",
+    ConnectionDirection::PositiveDirected => "->",
+    ConnectionDirection::NegativeDirected => "<-",
+    ConnectionDirection::BiDirected => "<->",
+  };
+
+  let with_types = |name: &str, types: &[crate::ContextRelationType]| -> String {
+    if types.is_empty() {
+      return name.to_string();
+    }
+    let types: Vec<String> = types.iter().map(|it| it.to_string()).collect();
+    format!("{}({})", name, types.join(", "))
+  };
+
+  format!("{} {} {}", with_types(&relation.source, &relation.source_type), arrow, with_types(&relation.target, &relation.target_type))
+}
+
+/// identical relations are matched first, the leftovers with the same contexts are changed
+fn diff_relations(as_is: &ContextMap, to_be: &ContextMap, changes: &mut Vec<Change>) {
+  let mut added: Vec<&ContextRelation> = to_be.relations.iter().collect();
+  let mut removed: Vec<&ContextRelation> = vec![];
+  for relation in &as_is.relations {
+    let text = relation_text(relation);
+    match added.iter().position(|it| relation_text(it) == text) {
+      Some(index) => {
+        added.remove(index);
+      }
+      None => removed.push(relation),
+    }
+  }
+
+  for relation in removed {
+    let key = relation_key(relation);
+    match added.iter().position(|it| relation_key(it) == key) {
+      Some(index) => {
+        let changed = added.remove(index);
+        changes.push(Change::RelationChanged { from: relation_text(relation), to: relation_text(changed) });
+      }
+      None => changes.push(Change::RelationRemoved { relation: relation_text(relation) }),
+    }
+  }
+
+  for relation in added {
+    changes.push(Change::RelationAdded { relation: relation_text(relation) });
+  }
+}
+
+/// the entities and value objects of aggregates with their owner name, like `Order.OrderItem`
+fn field_owners(context_map: &ContextMap) -> Vec<(String, &Vec<Field>)> {
+  let aggregates: Vec<&Aggregate> = context_map.contexts.iter().flat_map(|context| context.aggregates.iter()).collect();
+
+  let mut owners = vec![];
+  for aggregate in aggregates {
+    for entity in &aggregate.entities {
+      owners.push((format!("{}.{}", aggregate.name, entity.name), &entity.fields));
+      for vo in &entity.value_objects {
+        owners.push((format!("{}.{}", aggregate.name, vo.name), &vo.fields));
+      }
+    }
+    for vo in &aggregate.value_objects {
+      owners.push((format!("{}.{}", aggregate.name, vo.name), &vo.fields));
+    }
+  }
+
+  owners
+}
+
+fn diff_fields(as_is: &ContextMap, to_be: &ContextMap, changes: &mut Vec<Change>) {
+  let to_be_owners = field_owners(to_be);
+  for (owner, fields) in field_owners(as_is) {
+    let new_fields = match to_be_owners.iter().find(|(it, _)| it == &owner) {
+      Some((_, new_fields)) => new_fields,
+      None => continue,
+    };
+
+    for field in fields.iter() {
+      match new_fields.iter().find(|it| it.name == field.name) {
+        Some(changed) if changed.type_type != field.type_type => {
+          changes.push(Change::FieldChanged { owner: owner.clone(), field: field.name.clone(), from: field.type_type.clone(), to: changed.type_type.clone() });
+        }
+        Some(_) => {}
+        None => {
+          changes.push(Change::FieldRemoved { owner: owner.clone(), field: field.name.clone(), type_type: field.type_type.clone() });
+        }
+      }
+    }
+
+    for field in new_fields.iter().filter(|it| !fields.iter().any(|field| field.name == it.name)) {
+      changes.push(Change::FieldAdded { owner: owner.clone(), field: field.name.clone(), type_type: field.type_type.clone() });
+    }
+  }
+}
+
+impl Display for Change {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Change::ContextAdded { context } => write!(f, "+ context {}", context),
+      Change::ContextRemoved { context } => write!(f, "- context {}", context),
+      Change::ContextSplit { context, into } => write!(f, "~ context {} is split into {}", context, into.join(", ")),
+      Change::ContextsMerged { contexts, into } => write!(f, "~ contexts {} are merged into {}", contexts.join(", "), into),
+      Change::AggregateAdded { context, aggregate } => write!(f, "+ aggregate {}.{}", context, aggregate),
+      Change::AggregateRemoved { context, aggregate } => write!(f, "- aggregate {}.{}", context, aggregate),
+      Change::AggregateMoved { aggregate, from, to } => write!(f, "~ aggregate {} is moved from {} to {}", aggregate, from, to),
+      Change::RelationAdded { relation } => write!(f, "+ relation {}", relation),
+      Change::RelationRemoved { relation } => write!(f, "- relation {}", relation),
+      Change::RelationChanged { from, to } => write!(f, "~ relation {} is changed to {}", from, to),
+      Change::FieldAdded { owner, field, type_type } => write!(f, "+ field {}.{}: {}", owner, field, type_type),
+      Change::FieldRemoved { owner, field, type_type } => write!(f, "- field {}.{}: {}", owner, field, type_type),
+      Change::FieldChanged { owner, field, from, to } => write!(f, "~ field {}.{}: {} is changed to {}", owner, field, from, to),
+    }
+  }
+}
+
+impl Display for Changeset {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    writeln!(f, "Changeset({} -> {})", self.as_is, self.to_be)?;
+    for change in &self.changes {
+      writeln!(f, "  {}", change)?;
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::{Aggregate, BoundedContext, ConnectionDirection, ContextMap, ContextRelation, ContextRelationType, Entity, Field, TypeExpr};
+  use crate::strategy::changeset::{Change, Changeset};
+
+  fn context(name: &str, aggregates: &[&str]) -> BoundedContext {
+    let mut context = BoundedContext::new(name);
+    context.aggregates = aggregates.iter().map(|it| Aggregate::new(it)).collect();
+    context
+  }
+
+  fn relation(source: &str, target: &str, connection_type: ConnectionDirection) -> ContextRelation {
+    ContextRelation { source: source.to_string(), target: target.to_string(), connection_type, ..Default::default() }
+  }
+
+  #[test]
+  fn split_and_merge_contexts() {
+    let as_is = ContextMap {
+      name: "Shop".to_string(),
+      contexts: vec![context("Sales", &["Order", "Invoice"]), context("Shipping", &["Parcel"]), context("Tracking", &["Route"])],
+      ..Default::default()
+    };
+    let to_be = ContextMap {
+      name: "Shop".to_string(),
+      contexts: vec![context("Sales", &["Order", "Refund"]), context("Billing", &["Invoice"]), context("Logistics", &["Parcel", "Route"])],
+      ..Default::default()
+    };
+
+    let changeset = Changeset::diff(&as_is, &to_be);
+    assert_eq!(changeset.changes, vec![
+      Change::ContextSplit { context: "Sales".to_string(), into: vec!["Sales".to_string(), "Billing".to_string()] },
+      Change::ContextsMerged { contexts: vec!["Shipping".to_string(), "Tracking".to_string()], into: "Logistics".to_string() },
+      Change::AggregateMoved { aggregate: "Invoice".to_string(), from: "Sales".to_string(), to: "Billing".to_string() },
+      Change::AggregateMoved { aggregate: "Parcel".to_string(), from: "Shipping".to_string(), to: "Logistics".to_string() },
+      Change::AggregateMoved { aggregate: "Route".to_string(), from: "Tracking".to_string(), to: "Logistics".to_string() },
+      Change::AggregateAdded { context: "Sales".to_string(), aggregate: "Refund".to_string() },
+    ]);
+  }
+
+  #[test]
+  fn relations_and_fields() {
+    let mut order = Aggregate::new("Order");
+    let mut entity = Entity::new("Order");
+    entity.fields = vec![Field::new("id", TypeExpr::named("String")), Field::new("total", TypeExpr::named("Int")), Field::new("note", TypeExpr::named("String"))];
+    order.entities = vec![entity.clone()];
+    let mut sales = BoundedContext::new("Sales");
+    sales.aggregates = vec![order.clone()];
+
+    let as_is = ContextMap {
+      contexts: vec![sales.clone()],
+      relations: vec![relation("Sales", "Billing", ConnectionDirection::PositiveDirected), relation("Sales", "Legacy", ConnectionDirection::Undirected)],
+      ..Default::default()
+    };
+
+    entity.fields = vec![Field::new("id", TypeExpr::named("String")), Field::new("total", TypeExpr::named("Decimal")), Field::new("currency", TypeExpr::named("String"))];
+    order.entities = vec![entity];
+    sales.aggregates = vec![order];
+    let mut acl = relation("Billing", "Sales", ConnectionDirection::NegativeDirected);
+    acl.target_type = vec![ContextRelationType::OpenHostService];
+    let to_be = ContextMap {
+      contexts: vec![sales],
+      relations: vec![acl, relation("Sales", "Payment", ConnectionDirection::PositiveDirected)],
+      ..Default::default()
+    };
+
+    let changeset = Changeset::diff(&as_is, &to_be);
+    let lines: Vec<String> = changeset.changes.iter().map(|it| it.to_string()).collect();
+    assert_eq!(lines, vec![
+      "~ relation Sales -> Billing is changed to Billing <- Sales(OpenHostService)",
+      "- relation Sales - Legacy",
+      "+ relation Sales -> Payment",
+      "~ field Order.Order.total: Int is changed to Decimal",
+      "- field Order.Order.note: String",
+      "+ field Order.Order.currency: String",
+    ]);
+  }
+
+  #[test]
+  fn duplicated_relations() {
+    let as_is = ContextMap {
+      relations: vec![relation("Sales", "Billing", ConnectionDirection::PositiveDirected), relation("Sales", "Billing", ConnectionDirection::Undirected)],
+      ..Default::default()
+    };
+    let to_be = ContextMap {
+      relations: vec![relation("Sales", "Billing", ConnectionDirection::Undirected)],
+      ..Default::default()
+    };
+
+    let changeset = Changeset::diff(&as_is, &to_be);
+    assert_eq!(changeset.changes, vec![Change::RelationRemoved { relation: "Sales -> Billing".to_string() }]);
+  }
+}
@@ -0,0 +1,65 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::Span;
+
+/// Component, a deployable unit like an application or a service, which packages aggregates and
+/// entities together.
+/// - [`attributes`]: the `key = value` or `key: [a, b]` attributes, in declaration order
+/// - [`aggregates`], [`entities`], [`value_objects`]: the names of used domain objects
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Component {
+  pub name: String,
+  pub description: String,
+  pub component_type: ComponentType,
+  pub attributes: Vec<ComponentAttribute>,
+  pub aggregates: Vec<String>,
+  pub entities: Vec<String>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub value_objects: Vec<String>,
+  #[serde(default, skip_serializing_if = "Span::is_empty")]
+  pub span: Span,
+}
+
+impl Component {
+  pub fn new(name: &str) -> Self {
+    Component { name: name.to_string(), ..Default::default() }
+  }
+
+  /// the first value of attribute
+  pub fn attribute(&self, key: &str) -> Option<&str> {
+    self.attributes.iter()
+      .find(|attr| attr.key == key)
+      .and_then(|attr| attr.value.first())
+      .map(|value| value.as_str())
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct ComponentAttribute {
+  pub key: String,
+  pub value: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum ComponentType {
+  #[default]
+  Application,
+  Service,
+  Module,
+  Package,
+  Entities,
+}
+
+impl ComponentType {
+  pub fn from(str: &str) -> Self {
+    match str.to_lowercase().as_str() {
+      "service" => ComponentType::Service,
+      "module" => ComponentType::Module,
+      "package" => ComponentType::Package,
+      "entities" => ComponentType::Entities,
+      _ => ComponentType::Application,
+    }
+  }
+}
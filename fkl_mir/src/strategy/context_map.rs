@@ -3,9 +3,9 @@ use std::fmt::Display;
 use serde::Deserialize;
 use serde::Serialize;
 
-use crate::{BoundedContext, ConnectionDirection, ContextRelation, Entity, LayeredArchitecture, SourceSets, Step, Struct};
+use crate::{BoundedContext, Command, Component, ConnectionDirection, ContextRelation, Domain, DomainEvent, Entity, Enum, Field, LayeredArchitecture, MethodCall, Saga, Service, SourceSets, Span, Step, Struct, TypeExpr, ValueObject};
 use crate::environment::Environment;
-use crate::implementation::Implementation;
+use crate::implementation::{HttpMethod, Implementation};
 
 ///
 /// Identify each model in play on the project and define its bounded context. This includes
@@ -27,11 +27,39 @@ pub struct ContextMap {
   pub source_sets: Option<SourceSets>,
   pub envs: Vec<Environment>,
   pub structs: HashMap<String, Struct>,
+  /// the top-level value objects, the value objects in entities and aggregates are kept in their owner
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub value_objects: Vec<ValueObject>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub components: Vec<Component>,
+  /// the event catalog, all declared and emitted domain events
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub domain_events: Vec<DomainEvent>,
+  /// the subdomains, in declaration order
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub domains: Vec<Domain>,
+  /// the services, repositories and factories, in declaration order
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub services: Vec<Service>,
+  /// the enums, the ones in aggregates are kept with their owner
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub enums: Vec<Enum>,
+  /// the commands and queries, in declaration order
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub commands: Vec<Command>,
+  /// the sagas, in declaration order
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub sagas: Vec<Saga>,
   // todo: create a symbol table for the context map
 }
 
 impl ContextMap {
+  /// find the entity by name, or by the qualified name like `Sales.Customer` and `Sales.Order.Customer`
   pub fn get_entity(&self, entity_name: &str) -> Option<Entity> {
+    if entity_name.contains('.') {
+      return self.get_qualified_entity(entity_name);
+    }
+
     return self.contexts.iter().find_map(|bc| {
       bc.aggregates.iter().find_map(|aggregate| {
         aggregate.entities.iter().find_map(|entity| {
@@ -41,6 +69,23 @@ impl ContextMap {
     })
   }
 
+  fn get_qualified_entity(&self, qualified_name: &str) -> Option<Entity> {
+    let segments: Vec<&str> = qualified_name.split('.').collect();
+    let (context_name, aggregate_name) = match segments.as_slice() {
+      [context, _] => (*context, None),
+      [context, aggregate, _] => (*context, Some(*aggregate)),
+      _ => return None,
+    };
+
+    let is_named = |name: &str, expected: &str| name.to_lowercase() == expected.to_lowercase();
+    let context = self.contexts.iter().find(|bc| is_named(&bc.name, context_name))?;
+    return context.aggregates.iter()
+      .filter(|aggregate| aggregate_name.is_none_or(|name| is_named(&aggregate.name, name)))
+      .find_map(|aggregate| {
+        aggregate.entities.iter().find_map(|entity| Self::filter_by_name(segments[segments.len() - 1], entity))
+      });
+  }
+
   fn filter_by_name(entity_name: &str, entity: &Entity) -> Option<Entity> {
     if entity.name.to_lowercase() == entity_name.to_lowercase() {
       Some(entity.clone())
@@ -49,9 +94,242 @@ impl ContextMap {
     }
   }
 
+  /// find the value object by name, in top-level declarations first, then in aggregates and entities
+  pub fn get_value_object(&self, name: &str) -> Option<ValueObject> {
+    let is_named = |vo: &&ValueObject| vo.name.to_lowercase() == name.to_lowercase();
+    if let Some(vo) = self.value_objects.iter().find(is_named) {
+      return Some(vo.clone());
+    }
+
+    return self.contexts.iter().find_map(|bc| {
+      bc.aggregates.iter().find_map(|aggregate| {
+        aggregate.value_objects.iter().find(is_named)
+          .or_else(|| aggregate.entities.iter().find_map(|entity| entity.value_objects.iter().find(is_named)))
+      })
+    }).cloned();
+  }
+
+  /// the subdomain which the context belongs to, the first declared one if there are many
+  pub fn domain_of(&self, context_name: &str) -> Option<&Domain> {
+    self.domains.iter().find(|domain| domain.contexts.iter().any(|context| context == context_name))
+  }
+
+  /// the bounded contexts which implement the subdomain
+  pub fn contexts_of(&self, domain_name: &str) -> Vec<&BoundedContext> {
+    match self.domains.iter().find(|domain| domain.name == domain_name) {
+      Some(domain) => self.contexts.iter()
+        .filter(|context| domain.contexts.contains(&context.name))
+        .collect(),
+      None => vec![],
+    }
+  }
+
+  pub fn get_domain_event(&self, name: &str) -> Option<DomainEvent> {
+    self.domain_events.iter().find(|event| event.name == name).cloned()
+  }
+
+  /// the domain events which are published by the aggregate
+  pub fn events_of(&self, aggregate_name: &str) -> Vec<DomainEvent> {
+    self.domain_events.iter()
+      .filter(|event| event.publishers.iter().any(|publisher| publisher == aggregate_name))
+      .cloned()
+      .collect()
+  }
+
+  pub fn get_service(&self, name: &str) -> Option<&Service> {
+    self.services.iter().find(|service| service.name == name)
+  }
+
+  /// the services which are owned by the aggregate
+  pub fn services_of(&self, aggregate_name: &str) -> Vec<&Service> {
+    self.services.iter().filter(|service| service.aggregate == aggregate_name).collect()
+  }
+
+  /// the method calls in flows which are not declared by any service, in `(implementation name, call)` pairs,
+  /// a call without method only need the service to be declared.
+  pub fn unresolved_calls(&self) -> Vec<(String, MethodCall)> {
+    let mut unresolved = vec![];
+    for imp in &self.implementations {
+      if let Implementation::PublishHttpApi(api) = imp {
+        for step in api.flow.iter().flat_map(|flow| flow.steps.iter()) {
+          if let Step::MethodCall(call) = step {
+            let is_declared = match self.get_service(&call.object) {
+              Some(service) => call.method.is_empty() || service.get_method(&call.method).is_some(),
+              None => false,
+            };
+
+            if !is_declared {
+              unresolved.push((api.name.clone(), call.clone()));
+            }
+          }
+        }
+      }
+    }
+
+    unresolved
+  }
+
+  /// find the command or query by name
+  pub fn get_command(&self, name: &str) -> Option<&Command> {
+    self.commands.iter().find(|command| command.name == name)
+  }
+
+  /// the commands and queries which are handled by the aggregate
+  pub fn commands_of(&self, aggregate_name: &str) -> Vec<&Command> {
+    self.commands.iter().filter(|command| command.aggregate == aggregate_name).collect()
+  }
+
+  /// the implementations whose endpoint doesn't match their command or query, in
+  /// `(implementation name, command)` pairs: a query should be `GET` and a command should not, and
+  /// the target aggregate should be the one which handles the command.
+  pub fn mismatched_commands(&self) -> Vec<(String, Command)> {
+    let mut mismatched = vec![];
+    for imp in &self.implementations {
+      if let Implementation::PublishHttpApi(api) = imp {
+        let command = match self.get_command(&api.command) {
+          Some(command) => command,
+          None => continue,
+        };
+
+        let is_get = api.endpoint.method == HttpMethod::GET;
+        let is_other_aggregate = !command.aggregate.is_empty() && !api.target_aggregate.is_empty()
+          && command.aggregate != api.target_aggregate;
+        if is_get != command.is_query() || is_other_aggregate {
+          mismatched.push((api.name.clone(), command.clone()));
+        }
+      }
+    }
+
+    mismatched
+  }
+
+  /// whether there is a relation between the two contexts, in any direction
+  pub fn has_relation(&self, context: &str, other: &str) -> bool {
+    self.relations.iter().any(|relation| {
+      (relation.source == context && relation.target == other) || (relation.source == other && relation.target == context)
+    })
+  }
+
+  pub fn get_saga(&self, name: &str) -> Option<&Saga> {
+    self.sagas.iter().find(|saga| saga.name == name)
+  }
+
+  pub fn get_enum(&self, name: &str) -> Option<&Enum> {
+    self.enums.iter().find(|it| it.name == name)
+  }
+
+  /// the fields whose type is an enum, but the default value is not one of the variants, in
+  /// `(owner name, field)` pairs.
+  pub fn invalid_enum_defaults(&self) -> Vec<(String, Field)> {
+    let mut owners: Vec<(&str, &Vec<Field>)> = vec![];
+    for aggregate in self.contexts.iter().flat_map(|bc| bc.aggregates.iter()) {
+      for entity in &aggregate.entities {
+        owners.push((&entity.name, &entity.fields));
+        entity.value_objects.iter().for_each(|vo| owners.push((&vo.name, &vo.fields)));
+      }
+      aggregate.value_objects.iter().for_each(|vo| owners.push((&vo.name, &vo.fields)));
+    }
+    self.value_objects.iter().for_each(|vo| owners.push((&vo.name, &vo.fields)));
+    self.structs.values().for_each(|struct_| owners.push((&struct_.name, &struct_.fields)));
+    self.domain_events.iter().for_each(|event| owners.push((&event.name, &event.fields)));
+    self.commands.iter().for_each(|command| owners.push((&command.name, &command.fields)));
+
+    let mut invalid = vec![];
+    for (owner, fields) in owners {
+      for field in fields {
+        let type_name = match &field.type_expr {
+          TypeExpr::Optional(inner) => inner.to_string(),
+          _ if field.type_expr.is_empty() => field.type_type.clone(),
+          other => other.to_string(),
+        };
+        if let (Some(enum_), Some(value)) = (self.get_enum(&type_name), &field.initializer) {
+          if !enum_.accepts(value) {
+            invalid.push((owner.to_string(), field.clone()));
+          }
+        }
+      }
+    }
+
+    invalid
+  }
+
   pub fn get_struct(&self, struct_name: &str) -> Option<Struct> {
     self.structs.get(struct_name).map(|s| s.clone())
   }
+
+  /// remove all source spans, for the output which don't need to point back to the source,
+  /// empty spans will be skipped when serializing.
+  pub fn strip_spans(&mut self) {
+    fn strip_fields(fields: &mut [Field]) {
+      fields.iter_mut().for_each(|field| field.span = Span::default());
+    }
+
+    fn strip_value_object(vo: &mut ValueObject) {
+      vo.span = Span::default();
+      strip_fields(&mut vo.fields);
+      strip_fields(&mut vo.constructor);
+    }
+
+    fn strip_entity(entity: &mut Entity) {
+      entity.span = Span::default();
+      entity.identify.span = Span::default();
+      strip_fields(&mut entity.fields);
+      entity.value_objects.iter_mut().for_each(strip_value_object);
+      if let Some(states) = &mut entity.states {
+        states.transitions.iter_mut().for_each(|transition| transition.span = Span::default());
+      }
+    }
+
+    self.contexts.iter_mut().for_each(|context| {
+      context.span = Span::default();
+      context.modules.iter_mut().for_each(|module| module.span = Span::default());
+      context.aggregates.iter_mut().for_each(|aggregate| {
+        aggregate.span = Span::default();
+        aggregate.entities.iter_mut().for_each(strip_entity);
+        aggregate.value_objects.iter_mut().for_each(strip_value_object);
+      });
+    });
+
+    self.value_objects.iter_mut().for_each(strip_value_object);
+    self.components.iter_mut().for_each(|component| component.span = Span::default());
+    self.domains.iter_mut().for_each(|domain| domain.span = Span::default());
+    self.enums.iter_mut().for_each(|it| it.span = Span::default());
+    self.services.iter_mut().for_each(|service| {
+      service.span = Span::default();
+      service.methods.iter_mut().for_each(|method| {
+        method.span = Span::default();
+        strip_fields(&mut method.parameters);
+      });
+    });
+    self.domain_events.iter_mut().for_each(|event| {
+      event.span = Span::default();
+      strip_fields(&mut event.fields);
+    });
+    self.commands.iter_mut().for_each(|command| {
+      command.span = Span::default();
+      strip_fields(&mut command.fields);
+    });
+    self.sagas.iter_mut().for_each(|saga| {
+      saga.span = Span::default();
+      saga.steps.iter_mut().for_each(|step| step.span = Span::default());
+    });
+
+    self.relations.iter_mut().for_each(|relation| relation.span = Span::default());
+
+    self.implementations.iter_mut().for_each(|implementation| {
+      if let Implementation::PublishHttpApi(api) = implementation {
+        api.span = Span::default();
+        strip_fields(&mut api.endpoint.path_params);
+        strip_fields(&mut api.endpoint.query_params);
+        strip_fields(&mut api.endpoint.headers);
+      }
+    });
+
+    self.structs.values_mut().for_each(|struct_| {
+      struct_.span = Span::default();
+      strip_fields(&mut struct_.fields);
+    });
+  }
 }
 
 #[allow(dead_code)]
@@ -71,6 +349,25 @@ impl Default for ContextState {
   }
 }
 
+impl ContextState {
+  /// from the declared state, like `state: AsIs;`
+  pub fn from(str: &str) -> Self {
+    match str {
+      "AsIs" => ContextState::AsIs,
+      _ => ContextState::ToBe,
+    }
+  }
+}
+
+impl Display for ContextState {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      ContextState::AsIs => write!(f, "AsIs"),
+      ContextState::ToBe => write!(f, "ToBe"),
+    }
+  }
+}
+
 impl Display for ContextMap {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     writeln!(f, "ContextMap({})", self.name)?;
@@ -84,6 +381,13 @@ impl Display for ContextMap {
       writeln!(f, "  Relation({} {} {}) ", relation.source, rel, relation.target)?;
     }
 
+    for domain in &self.domains {
+      writeln!(f, "  {}({})", domain.subdomain_type, domain.name)?;
+      for context in &domain.contexts {
+        writeln!(f, "    Context({})", context)?;
+      }
+    }
+
     for context in &self.contexts {
       writeln!(f, "  BoundedContext({})", context.name)?;
       for aggregate in &context.aggregates {
@@ -93,10 +397,54 @@ impl Display for ContextMap {
           entity.fields.iter().for_each(|field| {
             writeln!(f, "        Field({})", field.name).unwrap();
           });
+          for vo in &entity.value_objects {
+            writeln!(f, "        ValueObject({})", vo.name)?;
+          }
+        }
+        for vo in &aggregate.value_objects {
+          writeln!(f, "      ValueObject({})", vo.name)?;
+        }
+        for event in &aggregate.domain_events {
+          writeln!(f, "      DomainEvent({})", event)?;
+        }
+      }
+      for module in &context.modules {
+        writeln!(f, "    Module({})", module.name)?;
+        for aggregate in &module.aggregates {
+          writeln!(f, "      Aggregate({})", aggregate)?;
         }
       }
     }
 
+    for enum_ in &self.enums {
+      writeln!(f, "  Enum({}) {{ {} }}", enum_.name, enum_.variants.join(", "))?;
+    }
+
+    for service in &self.services {
+      writeln!(f, "  {}({})", service.service_type, service.name)?;
+      for method in &service.methods {
+        writeln!(f, "    Method({})", method.name)?;
+      }
+    }
+
+    for command in &self.commands {
+      writeln!(f, "  {}({})", command.command_type, command.name)?;
+    }
+
+    for saga in &self.sagas {
+      writeln!(f, "  Saga({})", saga.name)?;
+      for step in &saga.steps {
+        writeln!(f, "    Step({}) in {}", step.name, step.context)?;
+      }
+    }
+
+    for component in &self.components {
+      writeln!(f, "  Component({})", component.name)?;
+      for aggregate in &component.aggregates {
+        writeln!(f, "    Aggregate({})", aggregate)?;
+      }
+    }
+
     for imp in &self.implementations {
       match imp {
         Implementation::PublishHttpApi(api) => {
@@ -163,9 +511,12 @@ mod tests {
               description: "".to_string(),
               identify: Default::default(),
               fields: vec![],
+              ..Default::default()
             }],
+            ..Default::default()
           }
         ],
+        ..Default::default()
       }],
       relations: vec![],
       implementations: vec![],
@@ -173,6 +524,14 @@ mod tests {
       source_sets: None,
       envs: vec![],
       structs: Default::default(),
+      value_objects: vec![],
+      components: vec![],
+      domain_events: vec![],
+      domains: vec![],
+      services: vec![],
+      enums: vec![],
+      commands: vec![],
+      sagas: vec![],
     };
     let output = format!("{}", context_map);
     assert_eq!(output, r#"ContextMap(Ticket)
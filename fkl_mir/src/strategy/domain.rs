@@ -1,10 +1,29 @@
+use std::fmt::{Display, Formatter};
+
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::Span;
+
+/// # Domain
+/// A sphere of knowledge, which is divided into subdomains by strategic design, every subdomain is
+/// implemented by one or more bounded contexts.
+/// - [`contexts`]: the names of bounded contexts which implement the subdomain
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct Domain {
-  pub subdomain_type: SubDomainType
+  pub name: String,
+  pub description: String,
+  pub subdomain_type: SubDomainType,
+  pub contexts: Vec<String>,
+  #[serde(default, skip_serializing_if = "Span::is_empty")]
+  pub span: Span,
+}
+
+impl Domain {
+  pub fn new(name: &str, subdomain_type: SubDomainType) -> Self {
+    Domain { name: name.to_string(), subdomain_type, ..Default::default() }
+  }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -19,3 +38,24 @@ impl Default for SubDomainType {
     SubDomainType::Supporting
   }
 }
+
+impl SubDomainType {
+  /// from the declaration keyword, like `CoreDomain`, or the type name, like `Core`
+  pub fn from(str: &str) -> Self {
+    match str.to_lowercase().as_str() {
+      "core" | "coredomain" => SubDomainType::Core,
+      "generic" | "genericdomain" => SubDomainType::Generic,
+      _ => SubDomainType::Supporting,
+    }
+  }
+}
+
+impl Display for SubDomainType {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      SubDomainType::Core => write!(f, "CoreDomain"),
+      SubDomainType::Generic => write!(f, "GenericDomain"),
+      SubDomainType::Supporting => write!(f, "SupportingDomain"),
+    }
+  }
+}
@@ -1,3 +1,6 @@
 pub mod domain;
 pub mod context_map;
 pub mod bounded_context;
+pub mod component;
+pub mod saga;
+pub mod changeset;
@@ -0,0 +1,64 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::{Annotation, Span};
+
+/// Saga, a long-running business process across bounded contexts, which is started by events and
+/// undone by the compensations of finished steps when a step fails.
+/// - [`context`]: the context which orchestrates the saga, empty if not declared
+/// - [`triggers`]: the names of domain events which start the saga
+/// - [`steps`]: the steps in running order
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct Saga {
+  pub name: String,
+  pub description: String,
+  #[serde(default, skip_serializing_if = "String::is_empty")]
+  pub context: String,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub triggers: Vec<String>,
+  pub steps: Vec<SagaStep>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub annotations: Vec<Annotation>,
+  #[serde(default, skip_serializing_if = "Span::is_empty")]
+  pub span: Span,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct SagaStep {
+  pub name: String,
+  pub description: String,
+  pub context: String,
+  /// the action which undoes the step, empty if the step needn't be compensated
+  #[serde(default, skip_serializing_if = "String::is_empty")]
+  pub compensation: String,
+  #[serde(default, skip_serializing_if = "Span::is_empty")]
+  pub span: Span,
+}
+
+impl Saga {
+  pub fn new(name: &str) -> Self {
+    Saga { name: name.to_string(), ..Default::default() }
+  }
+
+  /// the orchestrating context, or the context of first step if it's not declared
+  pub fn owner(&self) -> &str {
+    if !self.context.is_empty() {
+      return &self.context;
+    }
+
+    self.steps.first().map(|step| step.context.as_str()).unwrap_or_default()
+  }
+
+  /// the contexts which the saga involves, the owner first, then in the order of steps
+  pub fn contexts(&self) -> Vec<&str> {
+    let mut contexts: Vec<&str> = vec![];
+    let steps = self.steps.iter().map(|step| step.context.as_str());
+    for context in Some(self.owner()).into_iter().chain(steps) {
+      if !context.is_empty() && !contexts.contains(&context) {
+        contexts.push(context);
+      }
+    }
+
+    contexts
+  }
+}
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use crate::{Aggregate, BoundedContext, ContextMap, Datasource, Entity, Environment, Implementation, SourceSet, ValueObject};
+use crate::{Aggregate, BoundedContext, ContextMap, Datasource, Entity, Enum, Environment, Implementation, SourceSet, Span, ValueObject};
 use crate::tactic::struct_::Struct;
 
 /// SymbolType combines all DDD types and some other top level types
@@ -12,6 +12,7 @@ pub enum SymbolType {
   Entity(Entity),
   ValueObject(ValueObject),
   Struct(Struct),
+  Enum(Enum),
   Implementation(Implementation),
   Environment(Environment),
   SourceSet(SourceSet),
@@ -29,19 +30,126 @@ impl SymbolTable {
   }
 
   pub fn add(&mut self, symbol_type: SymbolType) {
-    let name = Symbol::name(&symbol_type);
-    self.symbols.insert(name.clone(), Symbol { name, symbol_type });
+    self.add_scoped(&[], symbol_type);
+  }
+
+  /// add the symbol which is declared in the scope, like `["Sales", "Order"]` for the entities of
+  /// aggregate `Order` in context `Sales`
+  pub fn add_scoped(&mut self, scope: &[String], symbol_type: SymbolType) {
+    let symbol = Symbol::scoped(scope, symbol_type);
+    self.symbols.insert(symbol.name.clone(), symbol);
   }
 
   pub fn get(&self, name: &str) -> Option<&Symbol> {
     self.symbols.get(name)
   }
+
+  /// add the context, and its aggregates, entities and value objects in the scope of their owners
+  pub fn add_context(&mut self, context: &BoundedContext) {
+    let context_scope = vec![context.name.clone()];
+    for aggregate in &context.aggregates {
+      let aggregate_scope = vec![context.name.clone(), aggregate.name.clone()];
+      for entity in &aggregate.entities {
+        let entity_scope = [aggregate_scope.clone(), vec![entity.name.clone()]].concat();
+        for vo in &entity.value_objects {
+          self.add_scoped(&entity_scope, SymbolType::ValueObject(vo.clone()));
+        }
+        self.add_scoped(&aggregate_scope, SymbolType::Entity(entity.clone()));
+      }
+      for vo in &aggregate.value_objects {
+        self.add_scoped(&aggregate_scope, SymbolType::ValueObject(vo.clone()));
+      }
+      self.add_scoped(&context_scope, SymbolType::Aggregate(aggregate.clone()));
+    }
+    self.add(SymbolType::BoundedContext(context.clone()));
+  }
+
+  /// resolve the reference, which can be qualified like `Sales.Customer` or `Sales.Order.Customer`,
+  /// the candidates closer to the scope of reference win, the ones in the same aggregate first, then
+  /// in the same context, and the candidates with the same span are the same declaration.
+  pub fn resolve(&self, reference: &str, scope: &[String], accept: impl Fn(&Symbol) -> bool) -> Resolution<'_> {
+    let segments: Vec<&str> = reference.split('.').collect();
+    let mut candidates: Vec<(usize, &Symbol)> = self.symbols.values()
+      .filter(|symbol| is_path_of(&segments, &symbol.path()) && accept(symbol))
+      .map(|symbol| (common_prefix(&symbol.scope, scope), symbol))
+      .collect();
+    candidates.sort_by(|(a, x), (b, y)| b.cmp(a).then_with(|| x.qualified_name().cmp(&y.qualified_name())));
+
+    let closest = match candidates.first() {
+      Some((closest, _)) => *closest,
+      None => return Resolution::NotFound,
+    };
+
+    let mut found: Vec<&Symbol> = vec![];
+    for (_, symbol) in candidates.into_iter().filter(|(score, _)| *score == closest) {
+      let span = symbol.span();
+      let is_same = |it: &&Symbol| span.is_some_and(|span| !span.is_empty() && it.span() == Some(span));
+      if !found.iter().any(is_same) {
+        found.push(symbol);
+      }
+    }
+
+    match found.len() {
+      1 => Resolution::Found(found[0]),
+      _ => Resolution::Ambiguous(found),
+    }
+  }
+}
+
+impl From<&ContextMap> for SymbolTable {
+  fn from(context_map: &ContextMap) -> Self {
+    let mut table = SymbolTable::new();
+    context_map.contexts.iter().for_each(|context| table.add_context(context));
+    for vo in &context_map.value_objects {
+      table.add(SymbolType::ValueObject(vo.clone()));
+    }
+    for struct_ in context_map.structs.values() {
+      table.add(SymbolType::Struct(struct_.clone()));
+    }
+    for enum_ in &context_map.enums {
+      table.add(SymbolType::Enum(enum_.clone()));
+    }
+    for implementation in &context_map.implementations {
+      table.add(SymbolType::Implementation(implementation.clone()));
+    }
+    for env in &context_map.envs {
+      table.add(SymbolType::Environment(env.clone()));
+    }
+
+    table
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Resolution<'a> {
+  Found(&'a Symbol),
+  /// the closest candidates, which are declared in different scopes
+  Ambiguous(Vec<&'a Symbol>),
+  NotFound,
+}
+
+/// the last segment is the name of symbol, and the others are its owners in order, the owners can be
+/// omitted, like `Sales.Customer` for `Sales.Order.Customer`
+fn is_path_of(segments: &[&str], path: &[String]) -> bool {
+  let (owners, scope) = match (segments.split_last(), path.split_last()) {
+    (Some((name, owners)), Some((last, scope))) if name == last => (owners, scope),
+    _ => return false,
+  };
+
+  let mut scope = scope.iter();
+  owners.iter().all(|owner| scope.any(|it| it == owner))
+}
+
+fn common_prefix(a: &[String], b: &[String]) -> usize {
+  a.iter().zip(b.iter()).take_while(|(a, b)| a == b).count()
 }
 
 /// Symbol is a DDD type or a top level type
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Symbol {
   pub name: String,
+  /// the names of owners, from the outermost, empty for the top level symbols
+  pub scope: Vec<String>,
   pub symbol_type: SymbolType,
 }
 
@@ -51,24 +159,69 @@ impl Symbol {
   /// `Entity Ticket {}` will have symbol name `entity_ticket`
   /// `ValueObject Ticket {}` will have symbol name `value_object_ticket`
   pub fn new(symbol_type: SymbolType) -> Self {
-    let name = Self::name(&symbol_type);
-    Symbol { name, symbol_type }
-  }
-
-  fn name(symbol_type: &SymbolType) -> String {
-    let name = match &symbol_type {
-      SymbolType::ContextMap(map) => format!("context_map_{}", map.name),
-      SymbolType::BoundedContext(bc) => format!("bounded_context_{}", bc.name),
-      SymbolType::Aggregate(aggregate) => format!("aggregate_{}", aggregate.name),
-      SymbolType::Entity(entity) => format!("entity_{}", entity.name),
-      SymbolType::ValueObject(vo) => format!("value_object_{}", vo.name),
-      SymbolType::Struct(struct_) => format!("struct_{}", struct_.name),
-      SymbolType::Implementation(implementation) => format!("implementation_{}", implementation.name()),
-      SymbolType::Environment(environment) => format!("environment_{}", environment.name),
-      SymbolType::SourceSet(source_set) => format!("source_set_{}", source_set.name),
-      SymbolType::DataSource(data_source) => format!("data_source_{}", data_source.name()),
-    };
-    name
+    Self::scoped(&[], symbol_type)
+  }
+
+  /// the scoped symbol name is qualified by its owners, like `entity_Sales.Order.Customer`
+  pub fn scoped(scope: &[String], symbol_type: SymbolType) -> Self {
+    let mut symbol = Symbol { name: "".to_string(), scope: scope.to_vec(), symbol_type };
+    symbol.name = format!("{}_{}", Self::prefix(&symbol.symbol_type), symbol.qualified_name());
+    symbol
+  }
+
+  fn prefix(symbol_type: &SymbolType) -> &'static str {
+    match &symbol_type {
+      SymbolType::ContextMap(_) => "context_map",
+      SymbolType::BoundedContext(_) => "bounded_context",
+      SymbolType::Aggregate(_) => "aggregate",
+      SymbolType::Entity(_) => "entity",
+      SymbolType::ValueObject(_) => "value_object",
+      SymbolType::Struct(_) => "struct",
+      SymbolType::Enum(_) => "enum",
+      SymbolType::Implementation(_) => "implementation",
+      SymbolType::Environment(_) => "environment",
+      SymbolType::SourceSet(_) => "source_set",
+      SymbolType::DataSource(_) => "data_source",
+    }
+  }
+
+  /// the declared name, like `Customer`
+  pub fn short_name(&self) -> String {
+    match &self.symbol_type {
+      SymbolType::ContextMap(map) => map.name.clone(),
+      SymbolType::BoundedContext(bc) => bc.name.clone(),
+      SymbolType::Aggregate(aggregate) => aggregate.name.clone(),
+      SymbolType::Entity(entity) => entity.name.clone(),
+      SymbolType::ValueObject(vo) => vo.name.clone(),
+      SymbolType::Struct(struct_) => struct_.name.clone(),
+      SymbolType::Enum(enum_) => enum_.name.clone(),
+      SymbolType::Implementation(implementation) => implementation.name(),
+      SymbolType::Environment(environment) => environment.name.clone(),
+      SymbolType::SourceSet(source_set) => source_set.name.clone(),
+      SymbolType::DataSource(data_source) => data_source.name(),
+    }
+  }
+
+  /// the name qualified by owners, like `Sales.Order.Customer`
+  pub fn qualified_name(&self) -> String {
+    self.path().join(".")
+  }
+
+  /// the names of owners and the symbol, like `["Sales", "Order", "Customer"]`
+  pub fn path(&self) -> Vec<String> {
+    [self.scope.clone(), vec![self.short_name()]].concat()
+  }
+
+  pub fn span(&self) -> Option<&Span> {
+    match &self.symbol_type {
+      SymbolType::BoundedContext(bc) => Some(&bc.span),
+      SymbolType::Aggregate(aggregate) => Some(&aggregate.span),
+      SymbolType::Entity(entity) => Some(&entity.span),
+      SymbolType::ValueObject(vo) => Some(&vo.span),
+      SymbolType::Struct(struct_) => Some(&struct_.span),
+      SymbolType::Enum(enum_) => Some(&enum_.span),
+      _ => None,
+    }
   }
 }
 
@@ -82,6 +235,7 @@ mod tests {
     let context_map = ContextMap::default();
     let symbol = Symbol {
       name: "context_map".to_string(),
+      scope: vec![],
       symbol_type: SymbolType::ContextMap(context_map),
     };
     symbol_table.symbols.insert(symbol.name.clone(), symbol);
@@ -110,4 +264,58 @@ mod tests {
 
     assert_eq!(symbol_table.symbols.get("context_map_demo").unwrap().name, "context_map_demo");
   }
+
+  fn context(name: &str, aggregates: Vec<Aggregate>) -> BoundedContext {
+    BoundedContext { name: name.to_string(), aggregates, ..Default::default() }
+  }
+
+  fn aggregate(name: &str, entities: Vec<Entity>) -> Aggregate {
+    Aggregate { name: name.to_string(), entities, ..Default::default() }
+  }
+
+  fn entity(name: &str, line: usize) -> Entity {
+    Entity { name: name.to_string(), span: Span::new(line * 10, line * 10 + 5, line, 1), ..Default::default() }
+  }
+
+  #[test]
+  fn resolve_qualified_names() {
+    let mut context_map = ContextMap::default();
+    context_map.contexts = vec![
+      context("Sales", vec![aggregate("Order", vec![entity("Customer", 1), entity("OrderItem", 2)])]),
+      context("Billing", vec![
+        aggregate("Invoice", vec![entity("Customer", 3)]),
+        // the same declaration which is used by the two contexts
+        aggregate("Payment", vec![entity("OrderItem", 2)]),
+      ]),
+    ];
+
+    let table = SymbolTable::from(&context_map);
+    let is_entity = |symbol: &Symbol| matches!(symbol.symbol_type, SymbolType::Entity(_));
+    assert!(table.get("entity_Sales.Order.Customer").is_some());
+
+    match table.resolve("Customer", &[], is_entity) {
+      Resolution::Ambiguous(candidates) => {
+        let names: Vec<String> = candidates.iter().map(|it| it.qualified_name()).collect();
+        assert_eq!(names, vec!["Billing.Invoice.Customer", "Sales.Order.Customer"]);
+      }
+      _ => panic!("should be ambiguous"),
+    }
+
+    let scope = vec!["Billing".to_string()];
+    match table.resolve("Customer", &scope, is_entity) {
+      Resolution::Found(symbol) => assert_eq!(symbol.qualified_name(), "Billing.Invoice.Customer"),
+      _ => panic!("should be found in the context of reference"),
+    }
+
+    match table.resolve("Sales.Customer", &scope, is_entity) {
+      Resolution::Found(symbol) => assert_eq!(symbol.qualified_name(), "Sales.Order.Customer"),
+      _ => panic!("should be found by the qualified name"),
+    }
+
+    assert!(matches!(table.resolve("Order.Customer", &scope, is_entity), Resolution::Found(_)));
+    assert!(matches!(table.resolve("Billing.Order.Customer", &scope, is_entity), Resolution::NotFound));
+    assert!(matches!(table.resolve("OrderItem", &[], is_entity), Resolution::Found(_)));
+    assert_eq!(context_map.get_entity("Billing.Customer").unwrap().span.line, 3);
+    assert_eq!(context_map.get_entity("sales.order.customer").unwrap().span.line, 1);
+  }
 }
@@ -1,6 +1,6 @@
 use serde::Deserialize;
 use serde::Serialize;
-use crate::Entity;
+use crate::{Annotation, Entity, Span, ValueObject};
 
 /// Cluster the entities and value objects into aggregates and define boundaries around each.
 /// Choose one entity to be the root of each aggregate, and allow external objects to hold
@@ -12,11 +12,20 @@ pub struct Aggregate {
   pub name: String,
   pub description: String,
   pub entities: Vec<Entity>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub value_objects: Vec<ValueObject>,
+  /// the names of domain events which are emitted by the aggregate, see [`crate::ContextMap::domain_events`]
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub domain_events: Vec<String>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub annotations: Vec<Annotation>,
+  #[serde(default, skip_serializing_if = "Span::is_empty")]
+  pub span: Span,
 }
 
 impl Aggregate {
   pub fn new(name: &str) -> Self {
-    Aggregate { name: name.to_string(), description: "".to_string(), entities: vec![] }
+    Aggregate { name: name.to_string(), description: "".to_string(), entities: vec![], value_objects: vec![], domain_events: vec![], annotations: vec![], span: Default::default() }
   }
 }
 
@@ -1,9 +1,33 @@
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::{Annotation, Span, TypeExpr, Validation};
+
+/// - [`type_type`]: the type as written in source
+/// - [`type_expr`]: the structured type, which should be used instead of parsing [`type_type`] again
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 pub struct Field {
   pub name: String,
   pub initializer: Option<String>,
   pub type_type: String,
+  #[serde(default, skip_serializing_if = "TypeExpr::is_empty")]
+  pub type_expr: TypeExpr,
+  /// the validation constraints, like `@required` or `@length(1, 64)`
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub validations: Vec<Validation>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub annotations: Vec<Annotation>,
+  #[serde(default, skip_serializing_if = "Span::is_empty")]
+  pub span: Span,
+}
+
+impl Field {
+  pub fn new(name: &str, type_expr: TypeExpr) -> Self {
+    Field {
+      name: name.to_string(),
+      type_type: type_expr.to_string(),
+      type_expr,
+      ..Default::default()
+    }
+  }
 }
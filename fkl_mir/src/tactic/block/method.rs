@@ -1,5 +1,16 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::Span;
+use crate::tactic::block::Field;
+
+/// The method signature of a service, the [`return_type`] is `None` for the method returns nothing.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 pub struct Method {
     pub name: String,
-    // pub arguments: Vec<Argument>,
-    // pub return_type: Type,
+    pub parameters: Vec<Field>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub return_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Span::is_empty")]
+    pub span: Span,
 }
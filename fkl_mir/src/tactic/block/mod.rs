@@ -1,5 +1,7 @@
 pub mod field;
 pub mod method;
+pub mod type_expr;
 
 pub use field::*;
 pub use method::*;
+pub use type_expr::*;
@@ -0,0 +1,94 @@
+use std::fmt::{Display, Formatter};
+
+use serde::Deserialize;
+use serde::Serialize;
+
+/// The structured type of field built by the parser, the generic `Optional<T>` is normalized to [`TypeExpr::Optional`].
+/// - `Order` is `Named("Order")`
+/// - `List<Map<String, Order>>` is `Generic("List", [Generic("Map", [Named("String"), Named("Order")])])`
+/// - `Order[]` is `Array(Named("Order"))`
+/// - `Order?` and `Optional<Order>` are `Optional(Named("Order"))`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum TypeExpr {
+  Named(String),
+  Generic(String, Vec<TypeExpr>),
+  Array(Box<TypeExpr>),
+  Optional(Box<TypeExpr>),
+}
+
+impl Default for TypeExpr {
+  fn default() -> Self {
+    TypeExpr::Named("".to_string())
+  }
+}
+
+impl TypeExpr {
+  pub fn named(name: &str) -> TypeExpr {
+    TypeExpr::Named(name.to_string())
+  }
+
+  pub fn is_empty(&self) -> bool {
+    *self == TypeExpr::default()
+  }
+
+  /// the name of type without generic arguments, array and optional, like `Order` for `Order[]?`,
+  /// and `List` for `List<Order>`
+  pub fn name(&self) -> &str {
+    match self {
+      TypeExpr::Named(name) => name,
+      TypeExpr::Generic(name, _) => name,
+      TypeExpr::Array(inner) => inner.name(),
+      TypeExpr::Optional(inner) => inner.name(),
+    }
+  }
+
+  /// all the type names in expression, like `List`, `Map`, `String` and `Order` for `List<Map<String, Order>>`
+  pub fn names(&self) -> Vec<&str> {
+    match self {
+      TypeExpr::Named(name) => vec![name.as_str()],
+      TypeExpr::Generic(name, args) => {
+        let mut names = vec![name.as_str()];
+        args.iter().for_each(|arg| names.extend(arg.names()));
+        names
+      }
+      TypeExpr::Array(inner) => inner.names(),
+      TypeExpr::Optional(inner) => inner.names(),
+    }
+  }
+}
+
+impl Display for TypeExpr {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      TypeExpr::Named(name) => write!(f, "{}", name),
+      TypeExpr::Generic(name, args) => {
+        let args = args.iter().map(|arg| arg.to_string()).collect::<Vec<String>>().join(", ");
+        write!(f, "{}<{}>", name, args)
+      }
+      TypeExpr::Array(inner) => write!(f, "{}[]", inner),
+      TypeExpr::Optional(inner) => write!(f, "{}?", inner),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::TypeExpr;
+
+  #[test]
+  fn nested_generic() {
+    let type_expr = TypeExpr::Generic("List".to_string(), vec![
+      TypeExpr::Generic("Map".to_string(), vec![TypeExpr::named("String"), TypeExpr::named("Order")])
+    ]);
+    assert_eq!(type_expr.to_string(), "List<Map<String, Order>>");
+    assert_eq!(type_expr.name(), "List");
+    assert_eq!(type_expr.names(), vec!["List", "Map", "String", "Order"]);
+  }
+
+  #[test]
+  fn optional_and_array() {
+    let type_expr = TypeExpr::Optional(Box::new(TypeExpr::Array(Box::new(TypeExpr::named("Order")))));
+    assert_eq!(type_expr.to_string(), "Order[]?");
+    assert_eq!(type_expr.name(), "Order");
+  }
+}
@@ -0,0 +1,64 @@
+use std::fmt::{Display, Formatter};
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::{Annotation, Span};
+use crate::tactic::block::Field;
+
+/// Command or Query of CQRS, a command changes the state of its aggregate and emits domain events,
+/// a query only reads it.
+/// - [`fields`]: the payload of command or query
+/// - [`aggregate`]: the name of the aggregate which handles it, empty if not declared
+/// - [`events`]: the names of domain events which are emitted by handling it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct Command {
+  pub name: String,
+  pub description: String,
+  pub command_type: CommandType,
+  #[serde(default, skip_serializing_if = "String::is_empty")]
+  pub aggregate: String,
+  pub fields: Vec<Field>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub events: Vec<String>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub annotations: Vec<Annotation>,
+  #[serde(default, skip_serializing_if = "Span::is_empty")]
+  pub span: Span,
+}
+
+impl Command {
+  pub fn new(name: &str, command_type: CommandType) -> Self {
+    Command { name: name.to_string(), command_type, ..Default::default() }
+  }
+
+  pub fn is_query(&self) -> bool {
+    self.command_type == CommandType::Query
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum CommandType {
+  #[default]
+  Command,
+  Query,
+}
+
+impl CommandType {
+  /// from the declaration keyword, like `Query`
+  pub fn from(str: &str) -> Self {
+    match str {
+      "Query" | "query" => CommandType::Query,
+      _ => CommandType::Command,
+    }
+  }
+}
+
+impl Display for CommandType {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      CommandType::Command => write!(f, "Command"),
+      CommandType::Query => write!(f, "Query"),
+    }
+  }
+}
@@ -0,0 +1,27 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::{Annotation, Span};
+use crate::tactic::block::Field;
+
+/// Domain Event, something happened in the domain that domain experts care about, which is
+/// published by aggregates.
+/// - [`fields`]: the payload of event
+/// - [`publishers`]: the names of aggregates which emit the event
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct DomainEvent {
+  pub name: String,
+  pub description: String,
+  pub fields: Vec<Field>,
+  pub publishers: Vec<String>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub annotations: Vec<Annotation>,
+  #[serde(default, skip_serializing_if = "Span::is_empty")]
+  pub span: Span,
+}
+
+impl DomainEvent {
+  pub fn new(name: &str) -> Self {
+    DomainEvent { name: name.to_string(), ..Default::default() }
+  }
+}
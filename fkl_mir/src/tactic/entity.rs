@@ -1,6 +1,7 @@
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::{Annotation, Span, StateMachine, ValueObject};
 use crate::tactic::block::Field;
 
 /// Entity Object
@@ -10,11 +11,29 @@ pub struct Entity {
   pub description: String,
   pub identify: Field,
   pub fields: Vec<Field>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub value_objects: Vec<ValueObject>,
+  /// the lifecycle, declared by the `states` block
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub states: Option<StateMachine>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub annotations: Vec<Annotation>,
+  #[serde(default, skip_serializing_if = "Span::is_empty")]
+  pub span: Span,
 }
 
 impl Entity {
   pub fn new(name: &str) -> Self {
-    Entity { name: name.to_string(), description: "".to_string(), identify: Field::default(), fields: vec![] }
+    Entity {
+      name: name.to_string(),
+      description: "".to_string(),
+      identify: Field::default(),
+      fields: vec![],
+      value_objects: vec![],
+      states: None,
+      annotations: vec![],
+      span: Default::default(),
+    }
   }
 }
 
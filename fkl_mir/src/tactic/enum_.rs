@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Annotation, Span};
+
+/// Enum type, which can be used as the type of fields, the default value of field should be one of
+/// the variants, like `Open` or `Status.Open`.
+/// - [`owner`]: the name of aggregate which declares it, empty for top-level one
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct Enum {
+    pub name: String,
+    pub description: String,
+    pub variants: Vec<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub owner: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<Annotation>,
+    #[serde(default, skip_serializing_if = "Span::is_empty")]
+    pub span: Span,
+}
+
+impl Enum {
+    pub fn new(name: &str) -> Self {
+        Enum { name: name.to_string(), ..Default::default() }
+    }
+
+    /// check the value is one of the variants, with or without the enum name prefix
+    pub fn accepts(&self, value: &str) -> bool {
+        let variant = match value.split_once('.') {
+            Some((name, variant)) if name == self.name => variant,
+            Some(_) => return false,
+            None => value,
+        };
+
+        self.variants.iter().any(|it| it == variant)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Enum;
+
+    #[test]
+    fn accepts_variant() {
+        let mut status = Enum::new("Status");
+        status.variants = vec!["Open".to_string(), "Closed".to_string()];
+
+        assert!(status.accepts("Open"));
+        assert!(status.accepts("Status.Closed"));
+        assert!(!status.accepts("Pending"));
+        assert!(!status.accepts("Priority.Open"));
+    }
+}
@@ -4,9 +4,14 @@ pub use block::method::Method;
 pub mod domain_object;
 pub mod aggregate;
 pub mod entity;
+pub mod state_machine;
 pub mod value_object;
+pub mod domain_event;
 pub mod service;
+pub mod command;
 pub mod struct_;
+pub mod enum_;
+pub mod module;
 
 pub mod block;
 
@@ -0,0 +1,29 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::Span;
+
+/// Module, a named group of aggregates inside a bounded context, it's also the unit of packaging
+/// and isolation in code generation and guarding.
+/// - [`aggregates`]: the names of aggregates, which are also in the aggregates of context
+/// - [`dependencies`]: the other modules of the same context this module may depend on
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct Module {
+  pub name: String,
+  pub description: String,
+  pub aggregates: Vec<String>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub dependencies: Vec<String>,
+  #[serde(default, skip_serializing_if = "Span::is_empty")]
+  pub span: Span,
+}
+
+impl Module {
+  pub fn new(name: &str) -> Self {
+    Module { name: name.to_string(), ..Default::default() }
+  }
+
+  pub fn contains(&self, aggregate: &str) -> bool {
+    self.aggregates.iter().any(|name| name == aggregate)
+  }
+}
@@ -1,7 +1,69 @@
+use std::fmt::{Display, Formatter};
+
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::{Annotation, Span};
+use crate::tactic::block::Method;
+
+/// Service, the operations which don't belong to any entity or value object, like domain services,
+/// application services, repositories and factories.
+/// - [`aggregate`]: the name of the aggregate which owns the service, empty if not declared
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 pub struct Service {
+  pub name: String,
+  pub description: String,
+  pub service_type: ServiceType,
+  #[serde(default, skip_serializing_if = "String::is_empty")]
+  pub aggregate: String,
+  pub methods: Vec<Method>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub annotations: Vec<Annotation>,
+  #[serde(default, skip_serializing_if = "Span::is_empty")]
+  pub span: Span,
+}
+
+impl Service {
+  pub fn new(name: &str, service_type: ServiceType) -> Self {
+    Service { name: name.to_string(), service_type, ..Default::default() }
+  }
+
+  pub fn get_method(&self, name: &str) -> Option<&Method> {
+    self.methods.iter().find(|method| method.name == name)
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum ServiceType {
+  #[default]
+  Service,
+  DomainService,
+  ApplicationService,
+  Repository,
+  Factory,
+}
+
+impl ServiceType {
+  /// from the declaration keyword, like `Repository`
+  pub fn from(str: &str) -> Self {
+    match str {
+      "DomainService" => ServiceType::DomainService,
+      "ApplicationService" => ServiceType::ApplicationService,
+      "Repository" => ServiceType::Repository,
+      "Factory" => ServiceType::Factory,
+      _ => ServiceType::Service,
+    }
+  }
+}
 
+impl Display for ServiceType {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      ServiceType::Service => write!(f, "Service"),
+      ServiceType::DomainService => write!(f, "DomainService"),
+      ServiceType::ApplicationService => write!(f, "ApplicationService"),
+      ServiceType::Repository => write!(f, "Repository"),
+      ServiceType::Factory => write!(f, "Factory"),
+    }
+  }
 }
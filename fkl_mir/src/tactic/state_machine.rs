@@ -0,0 +1,106 @@
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::Span;
+
+/// the lifecycle of entity, like `Draft -> Submitted on Submit`.
+/// - [`initial`]: the initial state, which is the source state of the first transition
+/// - [`states`]: all states in the order of first appearance
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct StateMachine {
+  pub initial: String,
+  pub states: Vec<String>,
+  pub transitions: Vec<Transition>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct Transition {
+  pub from: String,
+  pub to: String,
+  /// the command or event which triggers the transition, empty if not declared
+  #[serde(default, skip_serializing_if = "String::is_empty")]
+  pub trigger: String,
+  #[serde(default, skip_serializing_if = "Span::is_empty")]
+  pub span: Span,
+}
+
+impl StateMachine {
+  pub fn new(transitions: Vec<Transition>) -> Self {
+    let mut states: Vec<String> = vec![];
+    for transition in &transitions {
+      for state in [&transition.from, &transition.to] {
+        if !states.contains(state) {
+          states.push(state.clone());
+        }
+      }
+    }
+
+    StateMachine {
+      initial: states.first().cloned().unwrap_or_default(),
+      states,
+      transitions,
+    }
+  }
+
+  pub fn transitions_from(&self, state: &str) -> Vec<&Transition> {
+    self.transitions.iter().filter(|transition| transition.from == state).collect()
+  }
+
+  /// the states which have no outgoing transitions
+  pub fn final_states(&self) -> Vec<&String> {
+    self.states.iter().filter(|state| self.transitions_from(state).is_empty()).collect()
+  }
+
+  /// the states which can't be reached from the initial state
+  pub fn unreachable_states(&self) -> Vec<&String> {
+    let mut reached: Vec<&str> = vec![];
+    let mut pending: Vec<&str> = vec![&self.initial];
+    while let Some(state) = pending.pop() {
+      if reached.contains(&state) {
+        continue;
+      }
+
+      reached.push(state);
+      pending.extend(self.transitions_from(state).iter().map(|transition| transition.to.as_str()));
+    }
+
+    self.states.iter().filter(|state| !reached.contains(&state.as_str())).collect()
+  }
+
+  /// the triggers in the order of first appearance
+  pub fn triggers(&self) -> Vec<&String> {
+    let mut triggers: Vec<&String> = vec![];
+    for transition in &self.transitions {
+      if !transition.trigger.is_empty() && !triggers.contains(&&transition.trigger) {
+        triggers.push(&transition.trigger);
+      }
+    }
+
+    triggers
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::tactic::state_machine::{StateMachine, Transition};
+
+  fn transition(from: &str, to: &str, trigger: &str) -> Transition {
+    Transition { from: from.to_string(), to: to.to_string(), trigger: trigger.to_string(), span: Default::default() }
+  }
+
+  #[test]
+  fn unreachable_states() {
+    let machine = StateMachine::new(vec![
+      transition("Draft", "Submitted", "Submit"),
+      transition("Submitted", "Approved", "Approve"),
+      transition("Submitted", "Draft", "Reject"),
+      transition("Archived", "Deleted", ""),
+    ]);
+
+    assert_eq!(machine.initial, "Draft");
+    assert_eq!(machine.states, vec!["Draft", "Submitted", "Approved", "Archived", "Deleted"]);
+    assert_eq!(machine.final_states(), vec!["Approved", "Deleted"]);
+    assert_eq!(machine.unreachable_states(), vec!["Archived", "Deleted"]);
+    assert_eq!(machine.triggers(), vec!["Submit", "Approve", "Reject"]);
+  }
+}
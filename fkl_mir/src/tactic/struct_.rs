@@ -1,9 +1,13 @@
 use serde::{Deserialize, Serialize};
 
-use crate::Field;
+use crate::{Annotation, Field, Span};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 pub struct Struct {
     pub name: String,
     pub fields: Vec<Field>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<Annotation>,
+    #[serde(default, skip_serializing_if = "Span::is_empty")]
+    pub span: Span,
 }
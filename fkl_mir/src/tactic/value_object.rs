@@ -1,9 +1,29 @@
 use serde::{Deserialize, Serialize};
 
+use crate::{Annotation, Span};
 use crate::tactic::block::Field;
 
+/// Value Object, an immutable object which is defined by its attributes, and embedded in
+/// an entity or aggregate.
+/// - [`fields`]: the struct fields, or the constructor parameters if no struct is declared
+/// - [`owner`]: the name of the entity or aggregate which declares or uses it, empty for top-level one
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
 pub struct ValueObject {
     pub name: String,
+    pub description: String,
     pub fields: Vec<Field>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub constructor: Vec<Field>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub owner: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<Annotation>,
+    #[serde(default, skip_serializing_if = "Span::is_empty")]
+    pub span: Span,
+}
+
+impl ValueObject {
+    pub fn new(name: &str) -> Self {
+        ValueObject { name: name.to_string(), ..Default::default() }
+    }
 }
@@ -0,0 +1,400 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use fkl_mir::{ContextMap, SymbolTable};
+
+use crate::parser::ast::FklDeclaration;
+use crate::parser::parse_result::{ErrorKind, ParseError, SyntaxError};
+use crate::parser::recovery::{parse_with_recovery, RecoveredParse};
+use crate::semantic::Diagnostic;
+use crate::transform::{check_lowered, line_starts, MirTransform};
+
+/// The files known by the database.
+/// - [`dir`]: the directory which the file names in spans and errors are relative to
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SourceRoot {
+  pub dir: PathBuf,
+  pub files: BTreeSet<PathBuf>,
+}
+
+/// The files of entry and all files it includes.
+/// - [`files`]: in the order of first include
+/// - [`declarations`]: `(file, index)` of declarations in lowering order, the included file is
+///   lowered at the place of its first `include`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Program {
+  pub files: Vec<PathBuf>,
+  pub declarations: Vec<(PathBuf, usize)>,
+}
+
+/// A top-level declaration with the line starts before its end, the edits after it will not change
+/// its lowering.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceDeclaration {
+  pub declaration: FklDeclaration,
+  pub line_starts: Vec<usize>,
+}
+
+/// # Compiler
+/// The memoized queries of compiling fkl files, only the queries depend on the changed files will be
+/// recomputed, the unchanged declarations will not be lowered again.
+#[salsa::query_group(CompilerStorage)]
+pub trait Compiler: salsa::Database {
+  #[salsa::input]
+  fn source_text(&self, file: PathBuf) -> Arc<String>;
+  #[salsa::input]
+  fn source_root(&self) -> Arc<SourceRoot>;
+
+  /// the file name for display, relative to the directory of source root if possible
+  fn file_name(&self, file: PathBuf) -> String;
+  fn parsed_file(&self, file: PathBuf) -> Arc<RecoveredParse>;
+  /// the path of `include` declarations and the files they are resolved to
+  fn file_includes(&self, file: PathBuf) -> Arc<Vec<(String, PathBuf)>>;
+  fn source_declaration(&self, file: PathBuf, index: usize) -> Arc<SourceDeclaration>;
+  fn lower_declaration(&self, file: PathBuf, index: usize) -> Arc<MirTransform>;
+
+  fn program(&self, entry: PathBuf) -> Result<Arc<Program>, ErrorKind>;
+  fn lowered_program(&self, entry: PathBuf) -> Result<Arc<MirTransform>, ErrorKind>;
+  fn context_map(&self, entry: PathBuf) -> Result<Arc<ContextMap>, ErrorKind>;
+  fn symbol_table(&self, entry: PathBuf) -> Result<Arc<SymbolTable>, ErrorKind>;
+  fn diagnostics(&self, entry: PathBuf) -> Result<Arc<Vec<Diagnostic>>, ErrorKind>;
+  /// the syntax errors of all files in program, the declarations before and after them are still
+  /// lowered
+  fn syntax_errors(&self, entry: PathBuf) -> Result<Arc<Vec<SyntaxError>>, ErrorKind>;
+}
+
+fn file_name(db: &dyn Compiler, file: PathBuf) -> String {
+  let root = db.source_root();
+  match file.strip_prefix(&root.dir) {
+    Ok(relative) if !root.dir.as_os_str().is_empty() => relative.display().to_string(),
+    _ => file.display().to_string(),
+  }
+}
+
+fn parsed_file(db: &dyn Compiler, file: PathBuf) -> Arc<RecoveredParse> {
+  Arc::new(parse_with_recovery(&db.source_text(file)))
+}
+
+fn file_includes(db: &dyn Compiler, file: PathBuf) -> Arc<Vec<(String, PathBuf)>> {
+  let includes = db.parsed_file(file.clone()).decls.iter()
+    .filter_map(|decl| match decl {
+      FklDeclaration::Include(include) => Some((include.path.clone(), resolve_include(&file, &include.path))),
+      _ => None,
+    })
+    .collect();
+
+  Arc::new(includes)
+}
+
+fn source_declaration(db: &dyn Compiler, file: PathBuf, index: usize) -> Arc<SourceDeclaration> {
+  let declaration = db.parsed_file(file.clone()).decls[index].clone();
+  let end = declaration.loc().1;
+  let line_starts = line_starts(&db.source_text(file)).into_iter()
+    .take_while(|start| *start <= end)
+    .collect();
+
+  Arc::new(SourceDeclaration { declaration, line_starts })
+}
+
+fn lower_declaration(db: &dyn Compiler, file: PathBuf, index: usize) -> Arc<MirTransform> {
+  let source = db.source_declaration(file.clone(), index);
+  let lowered = MirTransform::lower_alone(Some(db.file_name(file)), source.line_starts.clone(), &source.declaration);
+  Arc::new(lowered)
+}
+
+fn program(db: &dyn Compiler, entry: PathBuf) -> Result<Arc<Program>, ErrorKind> {
+  if !db.source_root().files.contains(&entry) {
+    return Err(ErrorKind::Msg(format!("cannot read file {}", db.file_name(entry))));
+  }
+
+  let mut program = Program::default();
+  let mut stack: Vec<PathBuf> = vec![];
+  visit(db, &entry, &mut stack, &mut program)?;
+  Ok(Arc::new(program))
+}
+
+/// visit the declarations in lowering order, same to [`crate::resolve::include_graph::IncludeGraph`]
+/// the include cycle is reported with the full chain.
+fn visit(db: &dyn Compiler, file: &PathBuf, stack: &mut Vec<PathBuf>, program: &mut Program) -> Result<(), ErrorKind> {
+  if let Some(index) = stack.iter().position(|it| it == file) {
+    let chain = stack[index..].iter()
+      .chain(std::iter::once(file))
+      .map(|it| db.file_name(it.clone()))
+      .collect();
+    return Err(ErrorKind::IncludeCycle(chain));
+  }
+
+  if program.files.contains(file) {
+    return Ok(());
+  }
+
+  program.files.push(file.clone());
+  stack.push(file.clone());
+
+  let root = db.source_root();
+  let includes = db.file_includes(file.clone());
+  let mut includes = includes.iter();
+  for (index, decl) in db.parsed_file(file.clone()).decls.iter().enumerate() {
+    if !matches!(decl, FklDeclaration::Include(_)) {
+      program.declarations.push((file.clone(), index));
+      continue;
+    }
+
+    if let Some((path, resolved)) = includes.next() {
+      if !root.files.contains(resolved) {
+        return Err(ErrorKind::Msg(format!("cannot resolve include \"{}\" in {}", path, db.file_name(file.clone()))));
+      }
+      visit(db, resolved, stack, program)?;
+    }
+  }
+
+  stack.pop();
+  Ok(())
+}
+
+fn lowered_program(db: &dyn Compiler, entry: PathBuf) -> Result<Arc<MirTransform>, ErrorKind> {
+  let program = db.program(entry)?;
+
+  let mut transform = MirTransform::new();
+  for (file, index) in &program.declarations {
+    let source = db.source_declaration(file.clone(), *index);
+    transform.merge(&source.declaration, &db.lower_declaration(file.clone(), *index));
+  }
+
+  Ok(Arc::new(transform))
+}
+
+fn context_map(db: &dyn Compiler, entry: PathBuf) -> Result<Arc<ContextMap>, ErrorKind> {
+  let lowered = db.lowered_program(entry)?;
+  Ok(Arc::new(lowered.as_ref().clone().finish()))
+}
+
+fn symbol_table(db: &dyn Compiler, entry: PathBuf) -> Result<Arc<SymbolTable>, ErrorKind> {
+  let context_map = db.context_map(entry)?;
+  Ok(Arc::new(SymbolTable::from(context_map.as_ref())))
+}
+
+fn diagnostics(db: &dyn Compiler, entry: PathBuf) -> Result<Arc<Vec<Diagnostic>>, ErrorKind> {
+  let lowered = db.lowered_program(entry.clone())?;
+  let context_map = db.context_map(entry)?;
+  let mut diagnostics = lowered.diagnostics.clone();
+  diagnostics.extend(lowered.check_modules());
+  Ok(Arc::new(check_lowered(&lowered.symbols, diagnostics, &context_map)))
+}
+
+fn syntax_errors(db: &dyn Compiler, entry: PathBuf) -> Result<Arc<Vec<SyntaxError>>, ErrorKind> {
+  let program = db.program(entry)?;
+
+  let mut errors = vec![];
+  for file in &program.files {
+    for error in &db.parsed_file(file.clone()).errors {
+      let mut error = error.clone();
+      error.file = Some(db.file_name(file.clone()));
+      errors.push(error);
+    }
+  }
+
+  Ok(Arc::new(errors))
+}
+
+/// resolve the include path by the path of including file, without access the disk
+fn resolve_include(file: &Path, include: &str) -> PathBuf {
+  let mut resolved = PathBuf::new();
+  for component in file.parent().unwrap_or(Path::new("")).join(include).components() {
+    match component {
+      Component::CurDir => {}
+      Component::ParentDir => {
+        resolved.pop();
+      }
+      _ => resolved.push(component),
+    }
+  }
+
+  resolved
+}
+
+/// # Compiler Database
+/// The storage of [`Compiler`] queries, for the editor and watch mode which compile the same files
+/// again and again.
+/// ```rust,no_run
+/// use std::path::Path;
+/// use fkl_parser::{Compiler, CompilerDatabase};
+///
+/// let mut db = CompilerDatabase::default();
+/// let entry = db.load(Path::new("main.fkl")).unwrap();
+/// let context_map = db.context_map(entry.clone()).unwrap();
+///
+/// db.set_file(&entry, "Context Cinema {}");
+/// let context_map = db.context_map(entry).unwrap();
+/// ```
+#[salsa::database(CompilerStorage)]
+pub struct CompilerDatabase {
+  storage: salsa::Storage<Self>,
+  // the executed queries, only used for testing the reuse
+  logs: Option<Arc<Mutex<Vec<String>>>>,
+}
+
+impl Default for CompilerDatabase {
+  fn default() -> Self {
+    let mut db = CompilerDatabase { storage: Default::default(), logs: None };
+    db.set_source_root(Arc::new(SourceRoot::default()));
+    db
+  }
+}
+
+impl salsa::Database for CompilerDatabase {
+  fn salsa_event(&self, event: salsa::Event) {
+    if let Some(logs) = &self.logs {
+      if let salsa::EventKind::WillExecute { database_key } = event.kind {
+        logs.lock().unwrap().push(format!("{:?}", database_key.debug(self)));
+      }
+    }
+  }
+}
+
+impl CompilerDatabase {
+  /// read the entry file and all files it includes from the disk, the loaded files will not be read
+  /// again, update them by [`CompilerDatabase::set_file`]. returns the path of entry for queries.
+  pub fn load(&mut self, entry: &Path) -> Result<PathBuf, ParseError> {
+    let entry = entry.canonicalize()
+      .map_err(|e| ParseError::msg(format!("cannot read file {}: {}", entry.display(), e)))?;
+
+    if self.source_root().dir.as_os_str().is_empty() {
+      let mut root = self.source_root().as_ref().clone();
+      root.dir = entry.parent().unwrap_or(Path::new("")).to_path_buf();
+      self.set_source_root(Arc::new(root));
+    }
+
+    let mut pending = vec![entry.clone()];
+    while let Some(path) = pending.pop() {
+      if self.source_root().files.contains(&path) {
+        continue;
+      }
+
+      // the missing include is reported by the program query
+      let code = match fs::read_to_string(&path) {
+        Ok(code) => code,
+        Err(_) => continue,
+      };
+
+      self.set_file(&path, &code);
+      pending.extend(self.file_includes(path).iter().map(|(_, resolved)| resolved.clone()));
+    }
+
+    Ok(entry)
+  }
+
+  /// set the source code of file, like the unsaved content in editor
+  pub fn set_file(&mut self, path: &Path, code: &str) {
+    self.set_source_text(path.to_path_buf(), Arc::new(code.to_string()));
+
+    let root = self.source_root();
+    if !root.files.contains(path) {
+      let mut root = root.as_ref().clone();
+      root.files.insert(path.to_path_buf());
+      self.set_source_root(Arc::new(root));
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::fs;
+  use std::path::PathBuf;
+  use std::sync::{Arc, Mutex};
+
+  use fkl_mir::{Resolution, SymbolType};
+
+  use crate::database::{Compiler, CompilerDatabase};
+  use crate::parser::parse_result::ErrorKind;
+  use crate::transform::MirTransform;
+
+  fn take_logs(logs: &Arc<Mutex<Vec<String>>>) -> Vec<String> {
+    std::mem::take(&mut *logs.lock().unwrap())
+  }
+
+  #[test]
+  fn same_as_file_lowering() {
+    let temp = tempfile::tempdir().unwrap();
+    let dir = temp.path();
+    fs::write(dir.join("main.fkl"), r#"include "./sales.fkl"
+
+ContextMap Shop {
+  Sales <-> Payment;
+}
+
+Context Payment {
+  Aggregate Payment;
+}
+"#).unwrap();
+    fs::write(dir.join("sales.fkl"), r#"Context Sales {
+  Aggregate Order;
+}
+
+Aggregate Order {
+  Entity Order;
+  DomainEvent OrderCreated;
+}
+
+Entity Order {
+  Struct {
+    id: String;
+    customer: Customer;
+  }
+}
+"#).unwrap();
+
+    let mut db = CompilerDatabase::default();
+    let entry = db.load(&dir.join("main.fkl")).unwrap();
+
+    let (context_map, diagnostics) = MirTransform::mir_from_file_with_diagnostics(&entry).unwrap();
+    assert_eq!(db.context_map(entry.clone()).unwrap().as_ref(), &context_map);
+    assert_eq!(db.diagnostics(entry.clone()).unwrap().as_ref(), &diagnostics);
+    let table = db.symbol_table(entry).unwrap();
+    assert!(matches!(table.resolve("Sales.Order", &[], |it| matches!(it.symbol_type, SymbolType::Aggregate(_))), Resolution::Found(_)));
+  }
+
+  #[test]
+  fn only_lower_changed_declarations() {
+    let logs = Arc::new(Mutex::new(vec![]));
+    let mut db = CompilerDatabase { logs: Some(logs.clone()), ..Default::default() };
+
+    let entry = PathBuf::from("/fkl/main.fkl");
+    db.set_file(&entry, "Context Sales {\n  Aggregate Order;\n}\n\nAggregate Order {\n}\n");
+    assert_eq!(db.context_map(entry.clone()).unwrap().contexts[0].aggregates[0].name, "Order");
+    take_logs(&logs);
+
+    db.set_file(&entry, "Context Sales {\n  Aggregate Order;\n}\n\nAggregate Order {\n  Entity Item;\n}\n");
+    let context_map = db.context_map(entry).unwrap();
+    assert_eq!(context_map.contexts[0].aggregates[0].entities[0].name, "Item");
+
+    let lowered: Vec<String> = take_logs(&logs).into_iter()
+      .filter(|log| log.starts_with("lower_declaration"))
+      .collect();
+    assert_eq!(lowered.len(), 1);
+    assert!(lowered[0].contains(", 1)"));
+  }
+
+  #[test]
+  fn syntax_errors_and_include_cycle() {
+    let mut db = CompilerDatabase::default();
+    let main = PathBuf::from("/fkl/main.fkl");
+    let common = PathBuf::from("/fkl/common.fkl");
+    db.set_file(&main, "include \"./common.fkl\"\n\nContext Sales {\n  Aggregat Order;\n}\n");
+    db.set_file(&common, "Context Payment {}\n");
+
+    let errors = db.syntax_errors(main.clone()).unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].file, Some("/fkl/main.fkl".to_string()));
+    assert_eq!(db.context_map(main.clone()).unwrap().contexts.len(), 2);
+
+    db.set_file(&common, "include \"./main.fkl\"\n");
+    assert_eq!(db.program(main).unwrap_err(), ErrorKind::IncludeCycle(vec![
+      "/fkl/main.fkl".to_string(),
+      "/fkl/common.fkl".to_string(),
+      "/fkl/main.fkl".to_string(),
+    ]));
+  }
+}
@@ -0,0 +1,239 @@
+/// the multi-char punctuations, the longest first
+const PUNCTS: [&str; 4] = ["<->", "->", "<-", "::"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+  /// identifier, keyword or number
+  Word,
+  String,
+  InlineDoc,
+  LineComment,
+  BlockComment,
+  Punct,
+}
+
+/// the token of lossless syntax tree, the whitespaces are kept as the line breaks and spaces before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+  pub kind: TokenKind,
+  pub text: String,
+  pub newlines: usize,
+  pub spaced: bool,
+}
+
+impl Token {
+  pub fn is_comment(&self) -> bool {
+    matches!(self.kind, TokenKind::LineComment | TokenKind::BlockComment)
+  }
+
+  pub fn is_punct(&self, puncts: &[&str]) -> bool {
+    self.kind == TokenKind::Punct && puncts.contains(&self.text.as_str())
+  }
+}
+
+/// a top-level declaration with its leading comments, and the trailing comment in the same line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CstDeclaration {
+  pub keyword: String,
+  pub tokens: Vec<Token>,
+}
+
+/// Pest drops the comments, so the formatter works on a lossless tree of tokens, which are grouped
+/// by the top-level declarations.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Cst {
+  pub declarations: Vec<CstDeclaration>,
+  /// the comments after the last declaration
+  pub trailing: Vec<Token>,
+}
+
+impl Cst {
+  pub fn parse(code: &str) -> Cst {
+    let mut cst = Cst::default();
+    let mut current: Option<CstDeclaration> = None;
+    let mut depth: usize = 0;
+    let mut complete = true;
+
+    for token in tokenize(code) {
+      if depth == 0 && complete {
+        if token.is_comment() {
+          match current.as_mut() {
+            Some(decl) if token.newlines == 0 && cst.trailing.is_empty() => decl.tokens.push(token),
+            _ => cst.trailing.push(token),
+          }
+          continue;
+        }
+
+        // the annotations start a declaration, and its keyword is the first word after them
+        if token.kind == TokenKind::Word || token.is_punct(&["@"]) {
+          if let Some(decl) = current.take() {
+            cst.declarations.push(decl);
+          }
+
+          let mut tokens: Vec<Token> = cst.trailing.drain(..).collect();
+          let keyword = if token.kind == TokenKind::Word { token.text.clone() } else { "".to_string() };
+          tokens.push(token);
+          current = Some(CstDeclaration { keyword, tokens });
+          complete = false;
+          continue;
+        }
+      }
+
+      if let Some(decl) = current.as_mut() {
+        let after_at = decl.tokens.last().is_some_and(|it| it.is_punct(&["@"]));
+        if decl.keyword.is_empty() && depth == 0 && token.kind == TokenKind::Word && !after_at {
+          decl.keyword = token.text.clone();
+          decl.tokens.push(token);
+          continue;
+        }
+
+        if token.is_punct(&["{", "(", "["]) {
+          depth += 1;
+        } else if token.is_punct(&["}", ")", "]"]) {
+          depth = depth.saturating_sub(1);
+        }
+
+        complete = depth == 0 && (token.is_punct(&["}"]) || is_braceless_end(decl, &token));
+        decl.tokens.push(token);
+      }
+    }
+
+    if let Some(decl) = current.take() {
+      cst.declarations.push(decl);
+    }
+
+    cst
+  }
+}
+
+/// the `include` and `ValueObject` declarations can end without braces
+fn is_braceless_end(decl: &CstDeclaration, token: &Token) -> bool {
+  match decl.keyword.as_str() {
+    "include" => token.kind == TokenKind::String,
+    "ValueObject" | "VO" => token.kind == TokenKind::Word,
+    _ => false,
+  }
+}
+
+pub fn tokenize(code: &str) -> Vec<Token> {
+  let mut tokens = vec![];
+  let mut pos = 0;
+  let mut newlines = 0;
+  let mut spaced = false;
+
+  while let Some(ch) = code[pos..].chars().next() {
+    let rest = &code[pos..];
+    if ch.is_whitespace() {
+      if ch == '\n' {
+        newlines += 1;
+      }
+      spaced = true;
+      pos += ch.len_utf8();
+      continue;
+    }
+
+    let (kind, len) = if rest.starts_with("\"\"\"") {
+      (TokenKind::InlineDoc, delimited_len(rest, "\"\"\""))
+    } else if rest.starts_with("//") {
+      (TokenKind::LineComment, rest.find('\n').unwrap_or(rest.len()))
+    } else if rest.starts_with("/*") {
+      (TokenKind::BlockComment, block_comment_len(rest))
+    } else if ch == '"' || ch == '\'' {
+      (TokenKind::String, delimited_len(rest, &ch.to_string()))
+    } else if ch.is_alphanumeric() || ch == '_' {
+      (TokenKind::Word, rest.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(rest.len()))
+    } else {
+      let punct = PUNCTS.iter().find(|punct| rest.starts_with(*punct));
+      (TokenKind::Punct, punct.map(|punct| punct.len()).unwrap_or(ch.len_utf8()))
+    };
+
+    let text = match kind {
+      TokenKind::LineComment => rest[..len].trim_end(),
+      _ => &rest[..len],
+    };
+
+    tokens.push(Token { kind, text: text.to_string(), newlines, spaced });
+    pos += len;
+    newlines = 0;
+    spaced = false;
+  }
+
+  tokens
+}
+
+fn delimited_len(rest: &str, quote: &str) -> usize {
+  match rest[quote.len()..].find(quote) {
+    Some(index) => quote.len() + index + quote.len(),
+    None => rest.len(),
+  }
+}
+
+/// the block comments can be nested
+fn block_comment_len(rest: &str) -> usize {
+  let mut depth = 0;
+  let mut pos = 0;
+  while let Some(ch) = rest[pos..].chars().next() {
+    if rest[pos..].starts_with("/*") {
+      depth += 1;
+      pos += 2;
+    } else if rest[pos..].starts_with("*/") {
+      depth -= 1;
+      pos += 2;
+      if depth == 0 {
+        return pos;
+      }
+    } else {
+      pos += ch.len_utf8();
+    }
+  }
+
+  rest.len()
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::formatter::cst::{Cst, tokenize, TokenKind};
+
+  #[test]
+  fn tokenize_keeps_comments() {
+    let tokens = tokenize("Context A { // the context\n  /* a /* nested */ comment */\n}");
+    let kinds: Vec<TokenKind> = tokens.iter().map(|token| token.kind).collect();
+    assert_eq!(kinds, vec![
+      TokenKind::Word,
+      TokenKind::Word,
+      TokenKind::Punct,
+      TokenKind::LineComment,
+      TokenKind::BlockComment,
+      TokenKind::Punct,
+    ]);
+    assert_eq!(tokens[4].text, "/* a /* nested */ comment */");
+    assert_eq!(tokens[4].newlines, 1);
+  }
+
+  #[test]
+  fn tokenize_multi_char_punct() {
+    let texts: Vec<String> = tokenize("A <-> B; via S::call(); List<Map<String, Order>>").into_iter()
+      .map(|token| token.text)
+      .collect();
+    assert_eq!(texts, vec!["A", "<->", "B", ";", "via", "S", "::", "call", "(", ")", ";", "List", "<", "Map", "<", "String", ",", "Order", ">", ">"]);
+  }
+
+  #[test]
+  fn group_declarations() {
+    let cst = Cst::parse(r#"include "a.fkl"
+// the money
+ValueObject Money
+Context A {
+  Aggregate B;
+} // end of A
+
+// the end
+"#);
+
+    let keywords: Vec<&str> = cst.declarations.iter().map(|decl| decl.keyword.as_str()).collect();
+    assert_eq!(keywords, vec!["include", "ValueObject", "Context"]);
+    assert_eq!(cst.declarations[1].tokens[0].text, "// the money");
+    assert_eq!(cst.declarations[2].tokens.last().unwrap().text, "// end of A");
+    assert_eq!(cst.trailing[0].text, "// the end");
+  }
+}
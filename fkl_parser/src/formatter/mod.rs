@@ -0,0 +1,359 @@
+use fkl_mir::ContextMap;
+
+use crate::formatter::cst::{Cst, Token, TokenKind};
+use crate::parser::parse;
+use crate::ParseError;
+use crate::transform::MirTransform;
+
+pub mod cst;
+
+const INDENT: &str = "  ";
+
+/// the top-level declarations are ordered as the `declaration` rule in grammar, the unknown ones last.
+/// the contexts of ContextMap and Context are lowered in the order of declarations, so they are in one group.
+const DECLARATION_ORDER: [&[&str]; 17] = [
+  &["ContextMap", "Context"],
+  &["CoreDomain", "SupportingDomain", "GenericDomain", "SubDomain"],
+  &["Module"],
+  &["Aggregate"],
+  &["Entity"],
+  &["ValueObject", "VO"],
+  &["struct", "Struct"],
+  &["DomainEvent"],
+  &["Command", "Query"],
+  &["ApplicationService", "DomainService", "Service", "Repository", "Factory"],
+  &["enum", "Enum"],
+  &["Component"],
+  &["impl"],
+  &["Saga"],
+  &["layered"],
+  &["SourceSet"],
+  &["env"],
+];
+
+/// reprint the fkl code with canonical indentation, declaration ordering and spacing, the comments
+/// and inline docs are kept. the code with syntax errors is not formatted.
+///
+/// the declarations are not moved across `include`, since the included files are lowered in place,
+/// and they are kept in source order if the ordering changes the lowered model.
+pub fn format(code: &str) -> Result<String, ParseError> {
+  parse(code)?;
+
+  let cst = Cst::parse(code);
+  let mut ordered = cst.clone();
+  for group in ordered.declarations.split_mut(|decl| decl.keyword == "include") {
+    group.sort_by_key(|decl| declaration_rank(&decl.keyword));
+  }
+
+  let output = print_cst(&ordered);
+  if lowered(&output) == lowered(code) {
+    return Ok(output);
+  }
+
+  Ok(print_cst(&cst))
+}
+
+/// the lowered model without spans, which are changed by formatting
+fn lowered(code: &str) -> Option<ContextMap> {
+  let mut context_map = MirTransform::mir(code).ok()?;
+  context_map.strip_spans();
+  Some(context_map)
+}
+
+fn print_cst(cst: &Cst) -> String {
+  let mut output = String::new();
+  let blocks = cst.declarations.iter().map(|decl| &decl.tokens).chain(Some(&cst.trailing));
+  for tokens in blocks.filter(|tokens| !tokens.is_empty()) {
+    if !output.is_empty() {
+      output.push('\n');
+    }
+    print_tokens(tokens, &mut output);
+    output.push('\n');
+  }
+
+  output
+}
+
+fn declaration_rank(keyword: &str) -> usize {
+  DECLARATION_ORDER.iter()
+    .position(|keywords| keywords.contains(&keyword))
+    .unwrap_or(DECLARATION_ORDER.len())
+}
+
+fn print_tokens(tokens: &[Token], output: &mut String) {
+  let mut depth: usize = 0;
+  let mut prev: Option<&Token> = None;
+  for token in tokens {
+    if token.is_punct(&["}", ")", "]"]) {
+      depth = depth.saturating_sub(1);
+    }
+
+    if let Some(prev) = prev {
+      match line_breaks(prev, token) {
+        0 => {
+          if has_space(prev, token) {
+            output.push(' ');
+          }
+        }
+        breaks => {
+          output.push_str(&"\n".repeat(breaks));
+          output.push_str(&INDENT.repeat(depth));
+        }
+      }
+    }
+
+    output.push_str(&token.text);
+    if token.is_punct(&["{", "(", "["]) {
+      depth += 1;
+    }
+    prev = Some(token);
+  }
+}
+
+/// the line breaks before token, keep at most one blank line of source.
+fn line_breaks(prev: &Token, next: &Token) -> usize {
+  let origin = next.newlines.min(2);
+  if prev.kind == TokenKind::LineComment {
+    return origin.max(1);
+  }
+
+  // the comment in the same line stays
+  if next.is_comment() && next.newlines == 0 {
+    return 0;
+  }
+
+  if next.is_punct(&["{"]) && !prev.is_comment() {
+    return 0;
+  }
+
+  if prev.is_punct(&["{"]) {
+    return if next.is_punct(&["}"]) { 0 } else { 1 };
+  }
+
+  if next.is_punct(&["}"]) {
+    return 1;
+  }
+
+  if prev.is_punct(&[";"]) || (prev.is_punct(&["}"]) && !next.is_punct(&[",", ";", ")", "]"])) {
+    return origin.max(1);
+  }
+
+  origin
+}
+
+/// the space between two tokens in the same line
+fn has_space(prev: &Token, next: &Token) -> bool {
+  if next.is_comment() || prev.is_comment() {
+    return true;
+  }
+
+  if next.is_punct(&[",", ";", ":", ")", "]", "?", ".", "::", "<", ">"]) || prev.is_punct(&["(", "[", ".", "::", "@", "<"]) {
+    return false;
+  }
+
+  if prev.is_punct(&["{"]) && next.is_punct(&["}"]) {
+    return false;
+  }
+
+  if next.is_punct(&["("]) && (prev.kind == TokenKind::Word || prev.is_punct(&[">"])) {
+    return false;
+  }
+
+  if prev.is_punct(&[",", ":", "=", "->", "<-", "<->"]) || next.is_punct(&["{", "=", "->", "<-", "<->"]) {
+    return true;
+  }
+
+  next.spaced
+}
+
+#[cfg(test)]
+mod tests {
+  use std::fs;
+  use std::path::PathBuf;
+
+  use crate::formatter::{format, lowered};
+
+  #[test]
+  fn canonical_indent_and_spacing() {
+    let code = r#"Context   Ordering{
+Aggregate Order{
+      """ the order """
+  Entity Order {
+    struct {
+      id : String;
+      items:List<Map< String,Order >>;   code: String @length( 1,20 );
+    }
+  }
+}
+}
+"#;
+
+    assert_eq!(format(code).unwrap(), r#"Context Ordering {
+  Aggregate Order {
+    """ the order """
+    Entity Order {
+      struct {
+        id: String;
+        items: List<Map<String, Order>>;
+        code: String @length(1, 20);
+      }
+    }
+  }
+}
+"#);
+  }
+
+  #[test]
+  fn keep_comments_and_order_declarations() {
+    let code = r#"// the implementation
+impl PlaceOrder {
+  endpoint { POST "/orders"; }
+
+  flow {
+    via OrderService::placeOrder(request: PlaceOrderRequest) receive order: Order; // place
+  }
+}
+/* the map */
+ContextMap Mall {
+  Ordering [acl]->Payment;
+
+
+  Ordering<->Shipping
+}
+"#;
+
+    assert_eq!(format(code).unwrap(), r#"/* the map */
+ContextMap Mall {
+  Ordering [acl] -> Payment;
+
+  Ordering <-> Shipping
+}
+
+// the implementation
+impl PlaceOrder {
+  endpoint {
+    POST "/orders";
+  }
+
+  flow {
+    via OrderService::placeOrder(request: PlaceOrderRequest) receive order: Order; // place
+  }
+}
+"#);
+  }
+
+  #[test]
+  fn idempotent() {
+    let code = r#"
+Context A {  Aggregate B {} Aggregate C; }
+ValueObject Money
+Entity D {
+  constructor(
+    amount: BigDecimal,
+    currency: String
+  )
+}
+"#;
+
+    let formatted = format(code).unwrap();
+    assert_eq!(formatted, r#"Context A {
+  Aggregate B {}
+  Aggregate C;
+}
+
+Entity D {
+  constructor(
+    amount: BigDecimal,
+    currency: String
+  )
+}
+
+ValueObject Money
+"#);
+    assert_eq!(format(&formatted).unwrap(), formatted);
+  }
+
+  #[test]
+  fn annotations_before_declarations() {
+    let code = r#"Entity Order {
+  Struct {
+    @column( "order_id" )   id: String;
+  }
+}
+@owner("sales")   @stability(beta)
+Context Sales {
+  @table(orders) Aggregate Order {}
+}
+"#;
+
+    assert_eq!(format(code).unwrap(), r#"@owner("sales") @stability(beta)
+Context Sales {
+  @table(orders) Aggregate Order {}
+}
+
+Entity Order {
+  Struct {
+    @column("order_id") id: String;
+  }
+}
+"#);
+  }
+
+  #[test]
+  fn not_format_invalid_code() {
+    assert!(format("Context A {").is_err());
+  }
+
+  #[test]
+  fn keep_order_of_contexts_and_includes() {
+    let code = r#"Entity Invoice {}
+Context Billing {
+  Aggregate Invoice;
+}
+ContextMap Shop {
+  Sales -> Billing;
+}
+include "./common.fkl"
+Context Sales {
+  Aggregate Order;
+}
+"#;
+
+    assert_eq!(format(code).unwrap(), r#"Context Billing {
+  Aggregate Invoice;
+}
+
+ContextMap Shop {
+  Sales -> Billing;
+}
+
+Entity Invoice {}
+
+include "./common.fkl"
+
+Context Sales {
+  Aggregate Order;
+}
+"#);
+  }
+
+  #[test]
+  fn same_model_after_format() {
+    let mut dirs = vec![];
+    for dir in ["test_data/parse", "test_data/include", "test_data/include/cinema"] {
+      dirs.push([env!("CARGO_MANIFEST_DIR"), dir].iter().collect::<PathBuf>());
+    }
+
+    for dir in dirs {
+      for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_none_or(|ext| ext != "fkl") {
+          continue;
+        }
+
+        let code = fs::read_to_string(&path).unwrap();
+        assert_eq!(lowered(&format(&code).unwrap()), lowered(&code), "{}", path.display());
+      }
+    }
+  }
+}
@@ -3,14 +3,24 @@ extern crate pest;
 #[macro_use]
 extern crate pest_derive;
 
+use std::path::{Path, PathBuf};
+
 use fkl_mir::ContextMap;
+pub use database::{Compiler, CompilerDatabase, Program, SourceRoot};
 pub use parser::parse as ast_parse;
+pub use parser::parse_type_expr;
+pub use parser::parse_with_recovery as ast_parse_with_recovery;
+pub use parser::recovery::RecoveredParse;
+pub use parser::parse_result::{ErrorKind, ParseError, SyntaxError};
+pub use semantic::{Diagnostic, Severity};
 
-use crate::parser::parse_result::ParseError;
 use crate::transform::MirTransform;
 
+mod database;
+mod formatter;
 mod parser;
 mod resolve;
+mod semantic;
 mod transform;
 mod tests;
 mod testing;
@@ -28,3 +38,79 @@ mod testing;
 pub fn parse(code: &str) -> Result<ContextMap, ParseError> {
   MirTransform::mir(code)
 }
+
+/// compile the fkl file, and all files it includes, into one ContextMap. the include path is
+/// relative to the including file, and the include cycle will be reported with the full chain.
+/// ```rust,no_run
+/// use std::path::Path;
+/// use fkl_parser::parse_file;
+///
+/// let context_map = parse_file(Path::new("main.fkl")).unwrap();
+/// ```
+pub fn parse_file(path: &Path) -> Result<ContextMap, ParseError> {
+  MirTransform::mir_from_file(path)
+}
+
+/// compile the entry files of workspace and their dependencies in order, into one ContextMap.
+pub fn parse_files(paths: &[PathBuf]) -> Result<ContextMap, ParseError> {
+  MirTransform::mir_from_files(paths)
+}
+
+/// compile the fkl source code with error recovery, returns the ContextMap of all valid declarations
+/// and all syntax errors in one pass.
+/// ```rust
+/// use fkl_parser::parse_with_recovery;
+///
+/// let source = r#"Context Cinema {
+///   Aggregat Cinema;
+/// }
+/// "#;
+///
+/// let (context_map, errors) = parse_with_recovery(source);
+/// assert_eq!(context_map.contexts.len(), 1);
+/// assert_eq!(errors.len(), 1);
+/// ```
+pub fn parse_with_recovery(code: &str) -> (ContextMap, Vec<SyntaxError>) {
+  MirTransform::mir_with_recovery(code)
+}
+
+/// compile the fkl source code, and check the semantic problems like undefined references,
+/// duplicate declarations and unknown field types.
+/// ```rust
+/// use fkl_parser::{check, Severity};
+///
+/// let source = r#"Context Cinema {
+///   Aggregate Cinema;
+/// }
+/// "#;
+///
+/// let (_, diagnostics) = check(source).unwrap();
+/// assert_eq!(diagnostics[0].severity, Severity::Warning);
+/// assert_eq!(diagnostics[0].message, "aggregate `Cinema` is not declared, an empty aggregate is created");
+/// ```
+pub fn check(code: &str) -> Result<(ContextMap, Vec<Diagnostic>), ParseError> {
+  MirTransform::mir_with_diagnostics(code)
+}
+
+/// compile the fkl file with all included files, and check the semantic problems of them as a whole.
+pub fn check_file(path: &Path) -> Result<(ContextMap, Vec<Diagnostic>), ParseError> {
+  MirTransform::mir_from_file_with_diagnostics(path)
+}
+
+/// compile the entry files of workspace, and check them as a whole.
+pub fn check_files(paths: &[PathBuf]) -> Result<(ContextMap, Vec<Diagnostic>), ParseError> {
+  MirTransform::mir_from_files_with_diagnostics(paths)
+}
+
+/// format the fkl source code with canonical indentation, declaration ordering and spacing, the
+/// comments and inline docs are kept.
+/// ```rust
+/// use fkl_parser::format;
+///
+/// let formatted = format("Context Cinema{Aggregate Cinema;}").unwrap();
+/// assert_eq!(formatted, "Context Cinema {\n  Aggregate Cinema;\n}\n");
+/// ```
+pub fn format(code: &str) -> Result<String, ParseError> {
+  formatter::format(code)
+}
+
@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 use pest::Span;
 
+use fkl_mir::TypeExpr;
+
 // todo: add Loc support
 #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash, Clone, Copy, Default)]
 pub struct Loc(pub usize, pub usize);
@@ -20,23 +22,57 @@ pub struct Identifier {
 // strategy DDD
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::large_enum_variant)]
 pub enum FklDeclaration {
   None,
   Include(IncludeDecl),
   ContextMap(ContextMapDecl),
   BoundedContext(BoundedContextDecl),
+  Module(ModuleDecl),
   Aggregate(AggregateDecl),
   Entity(EntityDecl),
   ValueObject(ValueObjectDecl),
+  DomainEvent(DomainEventDecl),
+  Command(CommandDecl),
+  Service(ServiceDecl),
+  Enum(EnumDecl),
   Implementation(ImplementationDecl),
+  Saga(SagaDecl),
   Struct(StructDecl),
-  // Domain(DomainDecl),
+  Domain(DomainDecl),
   Component(ComponentDecl),
   Layered(LayeredDecl),
   SourceSets(SourceSetsDecl),
   Env(EnvDecl),
 }
 
+impl FklDeclaration {
+  pub fn loc(&self) -> Loc {
+    match self {
+      FklDeclaration::None => Loc::default(),
+      FklDeclaration::Include(decl) => decl.loc,
+      FklDeclaration::ContextMap(decl) => decl.loc,
+      FklDeclaration::BoundedContext(decl) => decl.loc,
+      FklDeclaration::Module(decl) => decl.loc,
+      FklDeclaration::Aggregate(decl) => decl.loc,
+      FklDeclaration::Entity(decl) => decl.loc,
+      FklDeclaration::ValueObject(decl) => decl.loc,
+      FklDeclaration::DomainEvent(decl) => decl.loc,
+      FklDeclaration::Command(decl) => decl.loc,
+      FklDeclaration::Service(decl) => decl.loc,
+      FklDeclaration::Enum(decl) => decl.loc,
+      FklDeclaration::Implementation(decl) => decl.loc,
+      FklDeclaration::Saga(decl) => decl.loc,
+      FklDeclaration::Struct(decl) => decl.loc,
+      FklDeclaration::Domain(decl) => decl.loc,
+      FklDeclaration::Component(decl) => decl.loc,
+      FklDeclaration::Layered(decl) => decl.loc,
+      FklDeclaration::SourceSets(decl) => decl.loc,
+      FklDeclaration::Env(decl) => decl.loc,
+    }
+  }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct IncludeDecl {
   pub path: String,
@@ -61,23 +97,57 @@ pub struct UniqueWord {
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct ContextMapDecl {
   pub name: Identifier,
+  /// `AsIs` or `ToBe`, empty if not declared
+  pub state: String,
   pub contexts: Vec<BoundedContextDecl>,
   pub relations: Vec<ContextRelation>,
   pub loc: Loc,
 }
 
+/// `CoreDomain`, `SupportingDomain`, `GenericDomain` or `SubDomain` with a `type` attribute
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DomainDecl {
+  pub name: String,
+  pub domain_type: String,
+  pub inline_doc: String,
+  pub contexts: Vec<Identifier>,
+  pub attributes: Vec<AttributeDefinition>,
+  pub loc: Loc,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct BoundedContextDecl {
   pub name: String,
   pub domain_events: Vec<DomainEventDecl>,
   pub aggregates: Vec<AggregateDecl>,
   pub used_domain_objects: Vec<UsedDomainObject>,
+  pub modules: Vec<ModuleDecl>,
+  /// the annotations before declaration, like `@owner("sales")`
+  pub annotations: Vec<AttributeDefinition>,
+  /// the context is only introduced by the relations of context map, like `A` in `A -> B;`
+  pub from_relation: bool,
+  pub loc: Loc,
+}
+
+/// a top-level module is attached to the context of its `context` attribute.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ModuleDecl {
+  pub name: String,
+  pub inline_doc: String,
+  pub context: String,
+  pub aggregates: Vec<AggregateDecl>,
+  pub used_domain_objects: Vec<UsedDomainObject>,
+  pub attributes: Vec<AttributeDefinition>,
   pub loc: Loc,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct DomainEventDecl {
   pub name: String,
+  pub inline_doc: String,
+  pub fields: Vec<VariableDefinition>,
+  /// the annotations before declaration, like `@owner("sales")`
+  pub annotations: Vec<AttributeDefinition>,
   pub loc: Loc,
 }
 
@@ -88,6 +158,7 @@ pub struct ContextRelation {
   pub direction: RelationDirection,
   pub source_types: Vec<String>,
   pub target_types: Vec<String>,
+  pub loc: Loc,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -109,14 +180,37 @@ impl Default for RelationDirection {
 
 // tactic DDD
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct DomainServiceDecl {
+/// `Service`, `DomainService`, `ApplicationService`, `Repository` or `Factory`, the owner aggregate is
+/// from the `aggregate` attribute, or the aggregate which the service is declared in.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ServiceDecl {
   pub name: String,
+  pub service_type: String,
+  pub inline_doc: String,
+  pub aggregate: String,
+  pub methods: Vec<MethodDecl>,
+  pub attributes: Vec<AttributeDefinition>,
+  /// the annotations before declaration, like `@owner("sales")`
+  pub annotations: Vec<AttributeDefinition>,
+  pub loc: Loc,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct ApplicationServiceDecl {
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct EnumDecl {
   pub name: String,
+  pub inline_doc: String,
+  pub variants: Vec<Identifier>,
+  /// the annotations before declaration, like `@owner("sales")`
+  pub annotations: Vec<AttributeDefinition>,
+  pub loc: Loc,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MethodDecl {
+  pub name: String,
+  pub parameters: Vec<VariableDefinition>,
+  pub return_type: Option<String>,
+  pub loc: Loc,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
@@ -127,6 +221,26 @@ pub struct AggregateDecl {
   pub entities: Vec<EntityDecl>,
   pub value_objects: Vec<ValueObjectDecl>,
   pub domain_events: Vec<DomainEventDecl>,
+  pub services: Vec<ServiceDecl>,
+  pub enums: Vec<EnumDecl>,
+  pub commands: Vec<CommandDecl>,
+  /// the annotations before declaration, like `@owner("sales")`
+  pub annotations: Vec<AttributeDefinition>,
+  pub loc: Loc,
+}
+
+/// `Command` or `Query`, the aggregate is from the `aggregate` attribute, or the aggregate which it
+/// is declared in.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CommandDecl {
+  pub name: String,
+  pub command_type: String,
+  pub inline_doc: String,
+  pub aggregate: Option<Identifier>,
+  pub fields: Vec<VariableDefinition>,
+  pub domain_events: Vec<DomainEventDecl>,
+  /// the annotations before declaration, like `@owner("sales")`
+  pub annotations: Vec<AttributeDefinition>,
   pub loc: Loc,
 }
 
@@ -135,15 +249,31 @@ pub struct StructDecl {
   pub name: String,
   pub inline_doc: String,
   pub fields: Vec<VariableDefinition>,
+  /// the annotations before declaration, like `@owner("sales")`
+  pub annotations: Vec<AttributeDefinition>,
   pub loc: Loc
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct UsedDomainObject {
   pub name: String,
+  pub object_type: UsedDomainObjectType,
   pub loc: Loc
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UsedDomainObjectType {
+  Aggregate,
+  Entity,
+  ValueObject,
+}
+
+impl Default for UsedDomainObjectType {
+  fn default() -> Self {
+    UsedDomainObjectType::Entity
+  }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct EntityDecl {
   pub name: String,
@@ -152,6 +282,18 @@ pub struct EntityDecl {
   pub inline_doc: String,
   pub fields: Vec<VariableDefinition>,
   pub value_objects: Vec<ValueObjectDecl>,
+  pub transitions: Vec<TransitionDecl>,
+  /// the annotations before declaration, like `@owner("sales")`
+  pub annotations: Vec<AttributeDefinition>,
+  pub loc: Loc,
+}
+
+/// `Draft -> Submitted on Submit;` in the `states` block of entity
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TransitionDecl {
+  pub from: String,
+  pub to: String,
+  pub trigger: String,
   pub loc: Loc,
 }
 
@@ -159,7 +301,20 @@ pub struct EntityDecl {
 pub struct VariableDefinition {
   pub name: String,
   pub type_type: String,
+  /// the structured type of `type_type`, built from the parsed `param_type`
+  pub type_expr: TypeExpr,
   pub initializer: Option<String>,
+  pub constraints: Vec<ConstraintDecl>,
+  /// the annotations before declaration, like `@owner("sales")`
+  pub annotations: Vec<AttributeDefinition>,
+  pub loc: Loc,
+}
+
+/// the validation constraint of field, like `@length(1, 64)`, the string args are unquoted
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ConstraintDecl {
+  pub name: String,
+  pub args: Vec<String>,
   pub loc: Loc,
 }
 
@@ -175,6 +330,9 @@ pub struct ValueObjectDecl {
   pub name: String,
   pub inline_doc: String,
   pub fields: Vec<VariableDefinition>,
+  pub constructor: Vec<VariableDefinition>,
+  /// the annotations before declaration, like `@owner("sales")`
+  pub annotations: Vec<AttributeDefinition>,
   pub loc: Loc,
 }
 
@@ -188,7 +346,36 @@ pub struct ImplementationDecl {
   // can be file path or url
   pub endpoint: EndpointDecl,
   pub target: Option<ImplementationTarget>,
+  pub command: Option<ImplementationTarget>,
   pub flow: Option<FlowDecl>,
+  /// the annotations before declaration, like `@owner("sales")`
+  pub annotations: Vec<AttributeDefinition>,
+  pub loc: Loc,
+}
+
+/// a business process across contexts, the `context` attribute is the orchestrating context, and
+/// the `trigger` attribute is the events which start it.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SagaDecl {
+  pub name: String,
+  pub inline_doc: String,
+  pub context: String,
+  pub triggers: Vec<String>,
+  pub steps: Vec<SagaStepDecl>,
+  pub attributes: Vec<AttributeDefinition>,
+  /// the annotations before declaration, like `@owner("sales")`
+  pub annotations: Vec<AttributeDefinition>,
+  pub loc: Loc,
+}
+
+/// the step of saga, runs in the context of `context` attribute, and is undone by the `compensate` action
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SagaStepDecl {
+  pub name: String,
+  pub inline_doc: String,
+  pub context: String,
+  pub compensation: String,
+  pub attributes: Vec<AttributeDefinition>,
   pub loc: Loc,
 }
 
@@ -205,6 +392,8 @@ pub enum ImplementationTargetType {
   Aggregate,
   Entity,
   ValueObject,
+  Command,
+  Query,
 }
 
 impl Default for ImplementationTargetType {
@@ -232,9 +421,13 @@ pub struct EndpointDecl {
   pub name: String,
   pub method: String,
   pub uri: String,
+  pub path_params: Vec<VariableDefinition>,
+  pub query_params: Vec<VariableDefinition>,
+  pub headers: Vec<VariableDefinition>,
   pub authorization: Option<AuthorizationDecl>,
   pub request: Option<HttpRequestDecl>,
   pub response: Option<HttpResponseDecl>,
+  pub responses: Vec<HttpResponseVariantDecl>,
   pub loc: Loc,
 }
 
@@ -258,6 +451,16 @@ pub struct HttpResponseDecl {
   pub loc: Loc,
 }
 
+/// the response of status code, the content type is empty if not declared
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HttpResponseVariantDecl {
+  pub status: u16,
+  pub type_type: String,
+  pub type_expr: TypeExpr,
+  pub content_type: String,
+  pub loc: Loc,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct FlowDecl {
   pub inline_doc: String,
@@ -1,5 +1,8 @@
 pub(crate)  mod ast;
 pub(crate) mod parse_result;
 pub(crate) mod parser;
+pub(crate) mod recovery;
 
 pub use parser::parse;
+pub use parser::parse_type_expr;
+pub use recovery::parse_with_recovery;
@@ -1,24 +1,86 @@
 use std::error::Error as StdError;
 use std::fmt;
 
-#[derive(Debug)]
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ErrorKind {
   Msg(String),
   // Json(serde_json::Error),
   // todo: for includes file
   Io(std::io::ErrorKind),
-  // SyntaxError(SyntaxError),
+  SyntaxError(Box<SyntaxError>),
+  /// the include chain of files which form a cycle, the first file is the same as the last one
+  IncludeCycle(Vec<String>),
   // InvalidToken
   // ExtraToken
 }
 
-// #[derive(Debug)]
-// pub struct SyntaxError {
-//   line: String,
-//   message: String,
-//   location: (usize),
-//   line_col: (usize, usize),
-// }
+/// A syntax error with enough location info for CLI, wasm and editor diagnostics.
+/// - [`location`]: byte range `(start, end)` in the source, `start == end` for a single position
+/// - [`line_col`]: 1-based `(line, column)` of the start position
+/// - [`expected`]: the grammar rules which could be accepted at the position
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct SyntaxError {
+  pub file: Option<String>,
+  pub location: (usize, usize),
+  pub line_col: (usize, usize),
+  pub end_line_col: (usize, usize),
+  pub expected: Vec<String>,
+  pub line: String,
+  pub message: String,
+}
+
+impl SyntaxError {
+  /// render the error with a code snippet and caret, same to the pest style:
+  /// ```text
+  ///  --> main.fkl:2:1
+  ///   |
+  /// 2 | imple CinemaCreatedEvent {
+  ///   | ^---
+  ///   |
+  ///   = expected EOI or declaration
+  /// ```
+  pub fn render(&self) -> String {
+    let (line, col) = self.line_col;
+    let line_no = line.to_string();
+    let spacing = " ".repeat(line_no.len());
+
+    let position = match &self.file {
+      Some(file) => format!("{}:{}:{}", file, line, col),
+      None => format!("{}:{}", line, col),
+    };
+
+    let mut caret = " ".repeat(col.saturating_sub(1));
+    let (end_line, end_col) = self.end_line_col;
+    if end_line == line && end_col > col {
+      caret.push('^');
+      if end_col - col > 1 {
+        caret.push_str(&"-".repeat(end_col - col - 2));
+        caret.push('^');
+      }
+    } else {
+      caret.push_str("^---");
+    }
+
+    format!(
+      "{s}--> {p}\n{s} |\n{l} | {line}\n{s} | {caret}\n{s} |\n{s} = {message}",
+      s = spacing,
+      p = position,
+      l = line_no,
+      line = self.line,
+      caret = caret,
+      message = self.message
+    )
+  }
+}
+
+impl fmt::Display for SyntaxError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}", self.render())
+  }
+}
 
 #[derive(Debug)]
 pub struct ParseError {
@@ -33,9 +95,8 @@ impl fmt::Display for ParseError {
       ErrorKind::Io(ref io_error) => {
         write!(f, "Io error while writing rendered value to output: {:?}", io_error)
       }
-      // ErrorKind::SyntaxError(token) => {
-      //   write!(f, "Unrecognized token `{:?}`", token)
-      // }
+      ErrorKind::SyntaxError(ref error) => write!(f, "{}", error),
+      ErrorKind::IncludeCycle(ref chain) => write!(f, "include cycle detected: {}", chain.join(" -> ")),
     }
   }
 }
@@ -56,12 +117,35 @@ impl ParseError {
     Self { kind: ErrorKind::Io(error.kind()), source: Some(Box::new(error)) }
   }
 
-  // pub fn syntax_error(error: SyntaxError) -> ParseError {
-  //   ParseError {
-  //     kind: ErrorKind::SyntaxError(error),
-  //     source: None,
-  //   }
-  // }
+  pub fn syntax_error(error: SyntaxError) -> ParseError {
+    ParseError {
+      kind: ErrorKind::SyntaxError(Box::new(error)),
+      source: None,
+    }
+  }
+
+  pub fn include_cycle(chain: Vec<String>) -> ParseError {
+    ParseError {
+      kind: ErrorKind::IncludeCycle(chain),
+      source: None,
+    }
+  }
+
+  pub fn as_syntax_error(&self) -> Option<&SyntaxError> {
+    match &self.kind {
+      ErrorKind::SyntaxError(error) => Some(error),
+      _ => None,
+    }
+  }
+
+  /// attach the file path to the syntax error, for display like `main.fkl:2:1`
+  pub fn with_file(mut self, file: impl ToString) -> Self {
+    if let ErrorKind::SyntaxError(ref mut error) = self.kind {
+      error.file = Some(file.to_string());
+    }
+
+    self
+  }
 
   // pub fn json(value: serde_json::Error) -> Self {
   //   Self { kind: ErrorKind::Json(value), source: None }
@@ -74,6 +158,12 @@ impl From<std::io::Error> for ParseError {
   }
 }
 
+impl From<ErrorKind> for ParseError {
+  fn from(kind: ErrorKind) -> Self {
+    Self { kind, source: None }
+  }
+}
+
 impl From<&str> for ParseError {
   fn from(e: &str) -> Self {
     Self::msg(e)
@@ -102,4 +192,24 @@ mod tests {
 
     test_send_sync::<super::ParseError>();
   }
+
+  #[test]
+  fn render_syntax_error_with_file() {
+    let error = super::SyntaxError {
+      file: Some("main.fkl".to_string()),
+      location: (1, 6),
+      line_col: (2, 1),
+      end_line_col: (2, 6),
+      expected: vec!["EOI".to_string(), "declaration".to_string()],
+      line: "imple CinemaCreatedEvent {".to_string(),
+      message: "expected EOI or declaration".to_string(),
+    };
+
+    assert_eq!(error.render(), r#" --> main.fkl:2:1
+  |
+2 | imple CinemaCreatedEvent {
+  | ^---^
+  |
+  = expected EOI or declaration"#);
+  }
 }
@@ -2,12 +2,12 @@ use std::collections::HashMap;
 use std::hash::Hash;
 
 use indexmap::IndexMap;
-use pest::error::Error;
+use pest::error::{Error, ErrorVariant, InputLocation, LineColLocation};
 use pest::iterators::{Pair, Pairs};
 
-use fkl_mir::default_config;
-use crate::parser::ast::{AggregateDecl, AttributeDefinition, AuthorizationDecl, BoundedContextDecl, ComponentDecl, ContextMapDecl, ContextRelation, CustomDecl, DatasourceDecl, DomainEventDecl, EndpointDecl, EntityDecl, EnvDecl, FklDeclaration, FlowDecl, HttpRequestDecl, HttpResponseDecl, Identifier, ImplementationDecl, ImplementationTarget, ImplementationTargetType, IncludeDecl, LayerDecl, LayeredDecl, LayerRelationDecl, Loc, MessageDecl, MethodCallDecl, RelationDirection, ServerDecl, SourceSetDecl, SourceSetsDecl, StepDecl, StructDecl, UsedDomainObject, ValueObjectDecl, VariableDefinition};
-use crate::parser::parse_result::{ParseError, ParseResult};
+use fkl_mir::{default_config, TypeExpr};
+use crate::parser::ast::{AggregateDecl, AttributeDefinition, AuthorizationDecl, BoundedContextDecl, CommandDecl, ComponentDecl, ConstraintDecl, ContextMapDecl, ContextRelation, CustomDecl, DatasourceDecl, DomainDecl, DomainEventDecl, EndpointDecl, EnumDecl, EntityDecl, EnvDecl, FklDeclaration, FlowDecl, HttpRequestDecl, HttpResponseDecl, HttpResponseVariantDecl, Identifier, ImplementationDecl, ImplementationTarget, ImplementationTargetType, IncludeDecl, LayerDecl, LayeredDecl, LayerRelationDecl, Loc, MessageDecl, MethodCallDecl, MethodDecl, ModuleDecl, RelationDirection, SagaDecl, SagaStepDecl, ServerDecl, ServiceDecl, SourceSetDecl, SourceSetsDecl, StepDecl, StructDecl, TransitionDecl, UsedDomainObject, UsedDomainObjectType, ValueObjectDecl, VariableDefinition};
+use crate::parser::parse_result::{ParseError, ParseResult, SyntaxError};
 use crate::pest::Parser;
 
 #[derive(Parser)]
@@ -38,7 +38,7 @@ pub fn parse(code: &str) -> ParseResult<Vec<FklDeclaration>> {
       //   }
       // });
       // return Err(ParseError::msg(fancy_e));
-      return Err(ParseError::msg(e.to_string()));
+      return Err(ParseError::syntax_error(to_syntax_error(e)));
     }
     Ok(pairs) => {
       Ok(consume_declarations(pairs))
@@ -46,11 +46,51 @@ pub fn parse(code: &str) -> ParseResult<Vec<FklDeclaration>> {
   }
 }
 
-fn inner_parse(code: &str) -> Result<Pairs<Rule>, Error<Rule>> {
+pub(crate) fn to_syntax_error(e: Error<Rule>) -> SyntaxError {
+  let location = match e.location {
+    InputLocation::Pos(pos) => (pos, pos),
+    InputLocation::Span(span) => span,
+  };
+
+  let (line_col, end_line_col) = match e.line_col {
+    LineColLocation::Pos(pos) => (pos, pos),
+    LineColLocation::Span(start, end) => (start, end),
+  };
+
+  let expected = match &e.variant {
+    ErrorVariant::ParsingError { positives, .. } => {
+      positives.iter().map(|rule| format!("{:?}", rule)).collect()
+    }
+    ErrorVariant::CustomError { .. } => vec![],
+  };
+
+  SyntaxError {
+    file: e.path().map(|path| path.to_string()),
+    location,
+    line_col,
+    end_line_col,
+    expected,
+    line: e.line().to_string(),
+    message: e.variant.message().to_string(),
+  }
+}
+
+/// parse the standalone type expression, like `List<Map<String, Order>>` or `Order[]?`
+pub fn parse_type_expr(code: &str) -> ParseResult<TypeExpr> {
+  let pairs = FklParser::parse(Rule::type_declaration, code)
+    .map_err(|e| ParseError::syntax_error(to_syntax_error(e)))?;
+  let type_expr = pairs.filter(|pair| pair.as_rule() == Rule::param_type)
+    .map(consume_type_expr)
+    .next()
+    .unwrap_or_default();
+  Ok(type_expr)
+}
+
+pub(crate) fn inner_parse(code: &str) -> Result<Pairs<Rule>, Error<Rule>> {
   FklParser::parse(Rule::declarations, code)
 }
 
-fn consume_declarations(pairs: Pairs<Rule>) -> Vec<FklDeclaration> {
+pub(crate) fn consume_declarations(pairs: Pairs<Rule>) -> Vec<FklDeclaration> {
   pairs.filter(|pair| {
     return pair.as_rule() == Rule::declaration;
   }).map(|pair| {
@@ -63,6 +103,14 @@ fn consume_declarations(pairs: Pairs<Rule>) -> Vec<FklDeclaration> {
         Rule::context_decl => {
           decl = FklDeclaration::BoundedContext(consume_context(p));
         }
+        Rule::domain_decl => {
+          decl = FklDeclaration::Domain(consume_domain(p));
+        }
+        Rule::ext_module_decl => {
+          if let Some(module) = p.into_inner().next() {
+            decl = FklDeclaration::Module(consume_module(module));
+          }
+        }
         Rule::aggregate_decl => {
           decl = FklDeclaration::Aggregate(consume_aggregate(p));
         }
@@ -78,9 +126,24 @@ fn consume_declarations(pairs: Pairs<Rule>) -> Vec<FklDeclaration> {
         Rule::implementation_decl => {
           decl = FklDeclaration::Implementation(consume_implementation(p));
         }
+        Rule::saga_decl => {
+          decl = FklDeclaration::Saga(consume_saga(p));
+        }
         Rule::struct_decl => {
           decl = FklDeclaration::Struct(consume_struct(p));
         }
+        Rule::domain_event_decl => {
+          decl = FklDeclaration::DomainEvent(consume_domain_event(p));
+        }
+        Rule::command_decl => {
+          decl = FklDeclaration::Command(consume_command(p));
+        }
+        Rule::service_decl => {
+          decl = FklDeclaration::Service(consume_service(p));
+        }
+        Rule::enum_decl => {
+          decl = FklDeclaration::Enum(consume_enum(p));
+        }
         Rule::layered_decl => {
           decl = FklDeclaration::Layered(consume_layered(p));
         }
@@ -106,7 +169,7 @@ fn consume_include(pair: Pair<Rule>) -> IncludeDecl {
   for p in pair.into_inner() {
     match p.as_rule() {
       Rule::string => {
-        path = p.as_str().to_string();
+        path = parse_string(p.as_str());
       }
       _ => println!("unreachable content rule: {:?}", p.as_rule())
     };
@@ -119,6 +182,7 @@ fn consume_context_map(pair: Pair<Rule>) -> ContextMapDecl {
   let mut context_decl_map: IndexMap<String, BoundedContextDecl> = IndexMap::new();
   let mut identify = Identifier::default();
   let mut relations: Vec<ContextRelation> = Vec::new();
+  let mut state = String::new();
   let span = pair.as_span().clone();
 
   for p in pair.into_inner() {
@@ -135,6 +199,13 @@ fn consume_context_map(pair: Pair<Rule>) -> ContextMapDecl {
         let context_relation = consume_context_node(&mut context_decl_map, p);
         relations.push(context_relation);
       }
+      Rule::context_state_decl => {
+        for inner in p.into_inner() {
+          if inner.as_rule() == Rule::context_state {
+            state = inner.as_str().to_string();
+          }
+        }
+      }
       _ => println!("unreachable context_map rule: {:?}", p.as_rule())
     };
   }
@@ -148,16 +219,54 @@ fn consume_context_map(pair: Pair<Rule>) -> ContextMapDecl {
   return ContextMapDecl {
     loc: Loc::from_pair(span),
     name: identify,
+    state,
     contexts,
     relations,
   };
 }
 
+fn consume_domain(pair: Pair<Rule>) -> DomainDecl {
+  let mut domain = DomainDecl::default();
+  domain.loc = Loc::from_pair(pair.as_span());
+  for p in pair.into_inner() {
+    match p.as_rule() {
+      Rule::domain_type => {
+        domain.domain_type = p.as_str().to_string();
+      }
+      Rule::identifier => {
+        domain.name = p.as_str().to_string();
+      }
+      Rule::inline_doc => {
+        domain.inline_doc = parse_inline_doc(p);
+      }
+      Rule::used_contexts_decl => {
+        for inner in p.into_inner() {
+          match inner.as_rule() {
+            Rule::identifier => {
+              domain.contexts.push(Identifier {
+                name: inner.as_str().to_string(),
+                loc: Loc::from_pair(inner.as_span()),
+              });
+            }
+            _ => println!("unreachable used_contexts rule: {:?}", inner.as_rule())
+          }
+        }
+      }
+      Rule::attr_decl => {
+        domain.attributes.push(consume_attribute(p));
+      }
+      _ => println!("unreachable domain rule: {:?}", p.as_rule())
+    };
+  }
+  return domain;
+}
+
 fn consume_context_node(context_decl_map: &mut IndexMap<String, BoundedContextDecl>, pair: Pair<Rule>) -> ContextRelation {
   let mut names: Vec<String> = vec![];
   let mut direction: RelationDirection = RelationDirection::Undirected;
   let mut source_type: Vec<String> = vec![];
   let mut target_type: Vec<String> = vec![];
+  let relation_loc = Loc::from_pair(pair.as_span());
 
   for p in pair.into_inner() {
     let loc = Loc::from_pair(p.as_span());
@@ -165,12 +274,15 @@ fn consume_context_node(context_decl_map: &mut IndexMap<String, BoundedContextDe
       Rule::left_id | Rule::right_id => {
         let context_name = p.as_str().to_string();
         names.push(context_name.clone());
-        context_decl_map.insert(context_name.clone(), BoundedContextDecl {
+        context_decl_map.entry(context_name.clone()).or_insert_with(|| BoundedContextDecl {
           name: context_name,
           domain_events: vec![],
           aggregates: vec![],
           used_domain_objects: vec![],
+          modules: vec![],
           loc,
+          annotations: vec![],
+          from_relation: true,
         });
       }
       Rule::rel_symbol => {
@@ -213,6 +325,7 @@ fn consume_context_node(context_decl_map: &mut IndexMap<String, BoundedContextDe
     direction,
     source_types: source_type,
     target_types: target_type,
+    loc: relation_loc,
   };
   context_relation
 }
@@ -247,12 +360,50 @@ fn consume_context(pair: Pair<Rule>) -> BoundedContextDecl {
         let vec = consume_use_domain_object(p);
         context.used_domain_objects = [context.used_domain_objects, vec].concat();
       }
+      Rule::module_decl => {
+        let mut module = consume_module(p);
+        module.context = context.name.clone();
+        context.modules.push(module);
+      }
+      Rule::annotation_decl => {
+        context.annotations.push(consume_annotation(p));
+      }
       _ => println!("unreachable context rule: {:?}", p.as_rule())
     };
   }
   return context;
 }
 
+fn consume_module(pair: Pair<Rule>) -> ModuleDecl {
+  let mut module = ModuleDecl::default();
+  module.loc = Loc::from_pair(pair.as_span());
+  for p in pair.into_inner() {
+    match p.as_rule() {
+      Rule::identifier => {
+        module.name = p.as_str().to_string();
+      }
+      Rule::inline_doc => {
+        module.inline_doc = parse_inline_doc(p);
+      }
+      Rule::aggregate_decl => {
+        module.aggregates.push(consume_aggregate(p));
+      }
+      Rule::used_domain_objects_decl => {
+        module.used_domain_objects = [module.used_domain_objects, consume_use_domain_object(p)].concat();
+      }
+      Rule::attr_decl => {
+        let attribute = consume_attribute(p);
+        if let ("context", Some(name)) = (attribute.key.as_str(), attribute.value.first()) {
+          module.context = name.clone();
+        }
+        module.attributes.push(attribute);
+      }
+      _ => println!("unreachable module rule: {:?}", p.as_rule())
+    };
+  }
+  return module;
+}
+
 fn consume_aggregate(pair: Pair<Rule>) -> AggregateDecl {
   let mut aggregate = AggregateDecl::default();
   aggregate.loc = Loc::from_pair(pair.as_span());
@@ -270,8 +421,31 @@ fn consume_aggregate(pair: Pair<Rule>) -> AggregateDecl {
       Rule::used_domain_objects_decl => {
         aggregate.used_domain_objects = [aggregate.used_domain_objects, consume_use_domain_object(p)].concat();
       }
+      Rule::domain_event_decl => {
+        aggregate.domain_events.push(consume_domain_event(p));
+      }
       Rule::used_domain_event_decl => {
-        aggregate.domain_events = consume_use_domain_events(p);
+        aggregate.domain_events.extend(consume_use_domain_events(p));
+      }
+      Rule::value_object_decl => {
+        aggregate.value_objects.push(consume_value_object(p));
+      }
+      Rule::service_decl => {
+        let mut service = consume_service(p);
+        if service.aggregate.is_empty() {
+          service.aggregate = aggregate.name.clone();
+        }
+        aggregate.services.push(service);
+      }
+      Rule::enum_decl => {
+        aggregate.enums.push(consume_enum(p));
+      }
+      Rule::command_decl => {
+        let mut command = consume_command(p);
+        if command.aggregate.is_none() {
+          command.aggregate = Some(Identifier { name: aggregate.name.clone(), loc: Default::default() });
+        }
+        aggregate.commands.push(command);
       }
       Rule::struct_decl => {
         let default_struct = consume_struct(p);
@@ -283,9 +457,14 @@ fn consume_aggregate(pair: Pair<Rule>) -> AggregateDecl {
           inline_doc: "".to_string(),
           fields,
           value_objects: vec![],
+          transitions: vec![],
+          annotations: vec![],
           loc: default_struct.loc,
         });
       }
+      Rule::annotation_decl => {
+        aggregate.annotations.push(consume_annotation(p));
+      }
       _ => println!("unreachable aggregate rule: {:?}", p.as_rule())
     };
   }
@@ -301,7 +480,10 @@ pub fn consume_use_domain_events(pair: Pair<Rule>) -> Vec<DomainEventDecl> {
         let loc = Loc::from_pair(p.as_span());
         domain_events.push(DomainEventDecl {
           name: p.as_str().to_string(),
-          loc
+          inline_doc: "".to_string(),
+          fields: vec![],
+          loc,
+          annotations: vec![],
         });
       }
       _ => println!("unreachable use_domain_events rule: {:?}", p.as_rule())
@@ -311,6 +493,142 @@ pub fn consume_use_domain_events(pair: Pair<Rule>) -> Vec<DomainEventDecl> {
   return domain_events;
 }
 
+fn consume_domain_event(pair: Pair<Rule>) -> DomainEventDecl {
+  let mut event = DomainEventDecl::default();
+  event.loc = Loc::from_pair(pair.as_span());
+  for p in pair.into_inner() {
+    match p.as_rule() {
+      Rule::identifier => {
+        event.name = p.as_str().to_string();
+      }
+      Rule::struct_decl => {
+        event.fields = consume_struct_decl(p);
+      }
+      Rule::inline_doc => {
+        event.inline_doc = parse_inline_doc(p);
+      }
+      Rule::annotation_decl => {
+        event.annotations.push(consume_annotation(p));
+      }
+      _ => println!("unreachable domain_event rule: {:?}", p.as_rule())
+    };
+  }
+  return event;
+}
+
+fn consume_command(pair: Pair<Rule>) -> CommandDecl {
+  let mut command = CommandDecl::default();
+  command.loc = Loc::from_pair(pair.as_span());
+  for p in pair.into_inner() {
+    match p.as_rule() {
+      Rule::command_type => {
+        command.command_type = p.as_str().to_string();
+      }
+      Rule::identifier => {
+        command.name = p.as_str().to_string();
+      }
+      Rule::inline_doc => {
+        command.inline_doc = parse_inline_doc(p);
+      }
+      Rule::struct_decl => {
+        command.fields = consume_struct_decl(p);
+      }
+      Rule::command_aggregate => {
+        if let Some(name) = p.into_inner().next() {
+          command.aggregate = Some(Identifier { name: name.as_str().to_string(), loc: Loc::from_pair(name.as_span()) });
+        }
+      }
+      Rule::used_domain_event_decl => {
+        command.domain_events.extend(consume_use_domain_events(p));
+      }
+      Rule::annotation_decl => {
+        command.annotations.push(consume_annotation(p));
+      }
+      _ => println!("unreachable command rule: {:?}", p.as_rule())
+    };
+  }
+  return command;
+}
+
+fn consume_service(pair: Pair<Rule>) -> ServiceDecl {
+  let mut service = ServiceDecl::default();
+  service.loc = Loc::from_pair(pair.as_span());
+  for p in pair.into_inner() {
+    match p.as_rule() {
+      Rule::service_type => {
+        service.service_type = p.as_str().to_string();
+      }
+      Rule::identifier => {
+        service.name = p.as_str().to_string();
+      }
+      Rule::inline_doc => {
+        service.inline_doc = parse_inline_doc(p);
+      }
+      Rule::method_decl => {
+        service.methods.push(consume_method(p));
+      }
+      Rule::attr_decl => {
+        let attribute = consume_attribute(p);
+        if let ("aggregate", Some(name)) = (attribute.key.as_str(), attribute.value.first()) {
+          service.aggregate = name.clone();
+        }
+        service.attributes.push(attribute);
+      }
+      Rule::annotation_decl => {
+        service.annotations.push(consume_annotation(p));
+      }
+      _ => println!("unreachable service rule: {:?}", p.as_rule())
+    };
+  }
+  return service;
+}
+
+fn consume_enum(pair: Pair<Rule>) -> EnumDecl {
+  let mut enum_decl = EnumDecl::default();
+  enum_decl.loc = Loc::from_pair(pair.as_span());
+  for p in pair.into_inner() {
+    match p.as_rule() {
+      Rule::identifier => {
+        enum_decl.name = p.as_str().to_string();
+      }
+      Rule::inline_doc => {
+        enum_decl.inline_doc = parse_inline_doc(p);
+      }
+      Rule::enum_variant => {
+        enum_decl.variants.push(Identifier {
+          name: p.as_str().to_string(),
+          loc: Loc::from_pair(p.as_span()),
+        });
+      }
+      Rule::annotation_decl => {
+        enum_decl.annotations.push(consume_annotation(p));
+      }
+      _ => println!("unreachable enum rule: {:?}", p.as_rule())
+    };
+  }
+  return enum_decl;
+}
+
+fn consume_method(pair: Pair<Rule>) -> MethodDecl {
+  let mut method = MethodDecl::default();
+  method.loc = Loc::from_pair(pair.as_span());
+  for p in pair.into_inner() {
+    match p.as_rule() {
+      Rule::identifier => {
+        method.name = p.as_str().to_string();
+      }
+      Rule::parameters_decl => {
+        method.parameters = consume_parameters(p);
+      }
+      Rule::param_type => {
+        method.return_type = Some(p.as_str().trim().to_string());
+      }
+      _ => println!("unreachable method rule: {:?}", p.as_rule())
+    };
+  }
+  return method;
+}
+
 fn consume_entity(pair: Pair<Rule>) -> EntityDecl {
   let mut entity = EntityDecl::default();
   entity.loc = Loc::from_pair(pair.as_span());
@@ -331,20 +649,56 @@ fn consume_entity(pair: Pair<Rule>) -> EntityDecl {
       Rule::value_object_decl => {
         entity.value_objects.push(consume_value_object(p));
       }
+      Rule::states_decl => {
+        entity.transitions = p.into_inner().map(|transition| consume_transition(transition)).collect();
+      }
+      Rule::annotation_decl => {
+        entity.annotations.push(consume_annotation(p));
+      }
       _ => println!("unreachable entity rule: {:?}", p.as_rule())
     };
   }
   return entity;
 }
 
+fn consume_transition(pair: Pair<Rule>) -> TransitionDecl {
+  let mut transition = TransitionDecl::default();
+  transition.loc = Loc::from_pair(pair.as_span());
+  for p in pair.into_inner() {
+    match p.as_rule() {
+      Rule::state_name => {
+        if transition.from.is_empty() {
+          transition.from = p.as_str().to_string();
+        } else {
+          transition.to = p.as_str().to_string();
+        }
+      }
+      Rule::transition_trigger => {
+        transition.trigger = p.as_str().to_string();
+      }
+      _ => println!("unreachable transition rule: {:?}", p.as_rule())
+    };
+  }
+  return transition;
+}
+
 fn consume_use_domain_object(pair: Pair<Rule>) -> Vec<UsedDomainObject> {
   let mut used_domain_objects: Vec<UsedDomainObject> = vec![];
+  let mut object_type = UsedDomainObjectType::default();
   for p in pair.into_inner() {
     match p.as_rule() {
-      Rule::identifier => {
+      Rule::domain_object_type => {
+        object_type = match p.as_str() {
+          "Aggregate" => UsedDomainObjectType::Aggregate,
+          "VO" | "ValueObject" => UsedDomainObjectType::ValueObject,
+          _ => UsedDomainObjectType::Entity,
+        };
+      }
+      Rule::qualified_name => {
         let loc = Loc::from_pair(p.as_span());
         used_domain_objects.push(UsedDomainObject {
           name: p.as_str().to_string(),
+          object_type: object_type.clone(),
           loc
         });
       }
@@ -389,6 +743,8 @@ fn consume_struct_decl(pair: Pair<Rule>) -> Vec<VariableDefinition> {
       Rule::fields_decl => {
         fields = consume_fields_decl(p);
       }
+      // the annotations of inner struct are ignored, the owner has its own annotations
+      Rule::annotation_decl => {}
       _ => println!("unreachable struct rule: {:?}", p.as_rule())
     };
   }
@@ -416,18 +772,108 @@ fn consume_parameter(pair: Pair<Rule>) -> VariableDefinition {
       Rule::identifier => {
         field.name = p.as_str().to_string();
       }
-      Rule::param_type => {
-        field.type_type = p.as_str().to_string();
+      Rule::field_type => {
+        for inner in p.into_inner() {
+          match inner.as_rule() {
+            Rule::param_type => {
+              field.type_type = inner.as_str().trim().to_string();
+              field.type_expr = consume_type_expr(inner);
+            }
+            Rule::constraint_decl => {
+              field.constraints.push(consume_constraint(inner));
+            }
+            _ => println!("unreachable field type rule: {:?}", inner.as_rule())
+          };
+        }
       }
       Rule::value => {
         field.initializer = Some(p.as_str().to_string());
       }
+      Rule::annotation_decl => {
+        field.annotations.push(consume_annotation(p));
+      }
       _ => println!("unreachable parameter rule: {:?}", p.as_rule())
     };
   }
   return field;
 }
 
+/// the type with its generic arguments and suffixes, the `Optional<T>` is normalized to `T?`
+fn consume_type_expr(pair: Pair<Rule>) -> TypeExpr {
+  let mut type_expr = TypeExpr::default();
+  for p in pair.into_inner() {
+    match p.as_rule() {
+      Rule::type_atom => {
+        type_expr = consume_type_atom(p);
+      }
+      Rule::type_suffix => {
+        type_expr = match p.as_str() {
+          "?" => TypeExpr::Optional(Box::new(type_expr)),
+          _ => TypeExpr::Array(Box::new(type_expr)),
+        };
+      }
+      _ => println!("unreachable type rule: {:?}", p.as_rule())
+    };
+  }
+  return type_expr;
+}
+
+fn consume_type_atom(pair: Pair<Rule>) -> TypeExpr {
+  let mut name = String::new();
+  let mut args: Vec<TypeExpr> = vec![];
+  for p in pair.into_inner() {
+    match p.as_rule() {
+      Rule::qualified_name => {
+        name = p.as_str().to_string();
+      }
+      Rule::type_args => {
+        args.extend(p.into_inner().map(consume_type_expr));
+      }
+      _ => println!("unreachable type atom rule: {:?}", p.as_rule())
+    };
+  }
+
+  return match args.len() {
+    0 => TypeExpr::Named(name),
+    1 if name == "Optional" => TypeExpr::Optional(Box::new(args.remove(0))),
+    _ => TypeExpr::Generic(name, args),
+  };
+}
+
+fn consume_constraint(pair: Pair<Rule>) -> ConstraintDecl {
+  let mut constraint = ConstraintDecl::default();
+  constraint.loc = Loc::from_pair(pair.as_span());
+  for p in pair.into_inner() {
+    match p.as_rule() {
+      Rule::identifier => {
+        constraint.name = p.as_str().to_string();
+      }
+      Rule::constraint_args => {
+        for arg in p.into_inner() {
+          consume_constraint_arg(arg, &mut constraint.args);
+        }
+      }
+      Rule::constraint_arg => consume_constraint_arg(p, &mut constraint.args),
+      _ => println!("unreachable constraint rule: {:?}", p.as_rule())
+    };
+  }
+  return constraint;
+}
+
+fn consume_constraint_arg(pair: Pair<Rule>, args: &mut Vec<String>) {
+  for inner in pair.into_inner() {
+    match inner.as_rule() {
+      Rule::string => args.push(parse_string(inner.as_str())),
+      _ => args.push(inner.as_str().to_string()),
+    }
+  }
+}
+
+fn consume_annotation(pair: Pair<Rule>) -> AttributeDefinition {
+  let constraint = consume_constraint(pair);
+  AttributeDefinition { key: constraint.name, value: constraint.args, loc: constraint.loc }
+}
+
 fn consume_value_object(pair: Pair<Rule>) -> ValueObjectDecl {
   let mut value_object = ValueObjectDecl::default();
   value_object.loc = Loc::from_pair(pair.as_span());
@@ -438,11 +884,14 @@ fn consume_value_object(pair: Pair<Rule>) -> ValueObjectDecl {
         value_object.name = p.as_str().to_string();
       }
       Rule::constructor_decl => {
-        value_object.fields = consume_constructor_decl(p);
+        value_object.constructor = consume_constructor_decl(p);
       }
       Rule::struct_decl => {
         value_object.fields = consume_struct_decl(p);
       }
+      Rule::annotation_decl => {
+        value_object.annotations.push(consume_annotation(p));
+      }
       _ => println!("unreachable value_object rule: {:?}", p.as_rule())
     };
   }
@@ -537,12 +986,78 @@ fn consume_implementation(pair: Pair<Rule>) -> ImplementationDecl {
       Rule::set_target_object => {
         implementation.target = Some(consume_set_target_object(p));
       }
+      Rule::set_command => {
+        implementation.command = Some(consume_set_target_object(p));
+      }
+      Rule::annotation_decl => {
+        implementation.annotations.push(consume_annotation(p));
+      }
       _ => println!("unreachable implementation rule: {:?}", p.as_rule())
     };
   }
   return implementation;
 }
 
+fn consume_saga(pair: Pair<Rule>) -> SagaDecl {
+  let mut saga = SagaDecl::default();
+  saga.loc = Loc::from_pair(pair.as_span());
+
+  for p in pair.into_inner() {
+    match p.as_rule() {
+      Rule::identifier => {
+        saga.name = p.as_str().to_string();
+      }
+      Rule::inline_doc => {
+        saga.inline_doc = parse_inline_doc(p);
+      }
+      Rule::saga_step_decl => {
+        saga.steps.push(consume_saga_step(p));
+      }
+      Rule::attr_decl => {
+        let attribute = consume_attribute(p);
+        match attribute.key.as_str() {
+          "context" => saga.context = attribute.value.first().cloned().unwrap_or_default(),
+          "trigger" => saga.triggers = attribute.value.clone(),
+          _ => {}
+        }
+        saga.attributes.push(attribute);
+      }
+      Rule::annotation_decl => {
+        saga.annotations.push(consume_annotation(p));
+      }
+      _ => println!("unreachable saga rule: {:?}", p.as_rule())
+    };
+  }
+  return saga;
+}
+
+fn consume_saga_step(pair: Pair<Rule>) -> SagaStepDecl {
+  let mut step = SagaStepDecl::default();
+  step.loc = Loc::from_pair(pair.as_span());
+
+  for p in pair.into_inner() {
+    match p.as_rule() {
+      Rule::identifier => {
+        step.name = p.as_str().to_string();
+      }
+      Rule::inline_doc => {
+        step.inline_doc = parse_inline_doc(p);
+      }
+      Rule::attr_decl => {
+        let attribute = consume_attribute(p);
+        match attribute.key.as_str() {
+          "context" => step.context = attribute.value.first().cloned().unwrap_or_default(),
+          "compensate" => step.compensation = attribute.value.first().cloned().unwrap_or_default(),
+          _ => {}
+        }
+        step.attributes.push(attribute);
+      }
+      _ => println!("unreachable saga step rule: {:?}", p.as_rule())
+    };
+  }
+  return step;
+}
+
 fn consume_set_target_object(pair: Pair<Rule>) -> ImplementationTarget {
   let mut target = ImplementationTarget::default();
   target.loc = Loc::from_pair(pair.as_span());
@@ -557,6 +1072,14 @@ fn consume_set_target_object(pair: Pair<Rule>) -> ImplementationTarget {
         target.target_type = ImplementationTargetType::Entity;
         target.name = p.as_str().to_string();
       }
+      Rule::set_command_name => {
+        target.target_type = ImplementationTargetType::Command;
+        target.name = p.as_str().to_string();
+      }
+      Rule::set_query_name => {
+        target.target_type = ImplementationTargetType::Query;
+        target.name = p.as_str().to_string();
+      }
       _ => println!("unreachable set_target_object rule: {:?}", p.as_rule())
     };
   }
@@ -585,6 +1108,9 @@ fn consume_endpoint(pair: Pair<Rule>) -> EndpointDecl {
           }
         }
       }
+      Rule::endpoint_params_decl => {
+        consume_endpoint_params(p, &mut endpoint);
+      }
       Rule::request_body => {
         for inner in p.into_inner() {
           match inner.as_rule() {
@@ -605,12 +1131,35 @@ fn consume_endpoint(pair: Pair<Rule>) -> EndpointDecl {
       Rule::http_response_decl => {
         endpoint.response = Some(consume_http_response(p));
       }
+      Rule::http_responses_decl => {
+        endpoint.responses = p.into_inner().map(consume_http_response_variant).collect();
+      }
       _ => println!("unreachable endpoint rule: {:?}", p.as_rule())
     };
   }
   return endpoint;
 }
 
+fn consume_endpoint_params(pair: Pair<Rule>, endpoint: &mut EndpointDecl) {
+  let mut params_type = "";
+  for p in pair.into_inner() {
+    match p.as_rule() {
+      Rule::endpoint_params_type => {
+        params_type = p.as_str();
+      }
+      Rule::fields_decl => {
+        let params = consume_fields_decl(p);
+        match params_type {
+          "path" => endpoint.path_params.extend(params),
+          "query" => endpoint.query_params.extend(params),
+          _ => endpoint.headers.extend(params),
+        }
+      }
+      _ => println!("unreachable endpoint_params rule: {:?}", p.as_rule())
+    };
+  }
+}
+
 fn consume_struct(pair: Pair<Rule>) -> StructDecl {
   let mut struct_decl = StructDecl::default();
   struct_decl.loc = Loc::from_pair(pair.as_span());
@@ -626,6 +1175,9 @@ fn consume_struct(pair: Pair<Rule>) -> StructDecl {
       Rule::fields_decl => {
         struct_decl.fields = consume_fields_decl(p);
       }
+      Rule::annotation_decl => {
+        struct_decl.annotations.push(consume_annotation(p));
+      }
       _ => println!("unreachable struct rule: {:?}", p.as_rule())
     };
   }
@@ -653,6 +1205,28 @@ fn consume_authorization(pair: Pair<Rule>) -> AuthorizationDecl {
   return authorization;
 }
 
+fn consume_http_response_variant(pair: Pair<Rule>) -> HttpResponseVariantDecl {
+  let mut variant = HttpResponseVariantDecl::default();
+  variant.loc = Loc::from_pair(pair.as_span());
+
+  for p in pair.into_inner() {
+    match p.as_rule() {
+      Rule::status_code => {
+        variant.status = p.as_str().parse().unwrap_or_default();
+      }
+      Rule::param_type => {
+        variant.type_type = p.as_str().trim().to_string();
+        variant.type_expr = consume_type_expr(p);
+      }
+      Rule::content_type => {
+        variant.content_type = parse_string(p.as_str());
+      }
+      _ => println!("unreachable http_response_variant rule: {:?}", p.as_rule())
+    };
+  }
+  return variant;
+}
+
 fn consume_http_response(pair: Pair<Rule>) -> HttpResponseDecl {
   let mut response = HttpResponseDecl::default();
   response.loc = Loc::from_pair(pair.as_span());
@@ -995,11 +1569,13 @@ fn parse_inline_doc(pair: Pair<Rule>) -> String {
 
 #[cfg(test)]
 mod tests {
+  use fkl_mir::TypeExpr;
+
   use crate::parser::ast::*;
   use crate::parser::ast::ImplementationTargetType::Aggregate;
   use crate::parser::ast::RelationDirection::{BiDirected, PositiveDirected};
   use crate::parser::ast::StepDecl::{Message, MethodCall};
-  use crate::parser::parser::parse;
+  use crate::parser::parser::{parse, parse_type_expr};
 
   #[test]
   fn parse_context_map() {
@@ -1019,25 +1595,32 @@ Context ShoppingCarContext {
         name: "".to_string(),
         loc: Default::default(),
       },
+      state: "".to_string(),
       contexts: vec![
         BoundedContextDecl {
           name: "MallContext".to_string(),
           domain_events: vec![],
           aggregates: vec![],
           used_domain_objects: vec![],
-          loc: Loc(76, 87),
+          annotations: vec![],
+          from_relation: true,
+          loc: Loc(38, 49),
+          modules: vec![],
         },
         BoundedContextDecl {
           name: "ShoppingCarContext".to_string(),
           domain_events: vec![],
           aggregates: vec![],
           used_domain_objects: vec![],
-          loc: Loc(53, 71),
+          annotations: vec![],
+          from_relation: true,
+          loc: Loc(16, 34),
+          modules: vec![],
         },
       ],
       relations: vec![
-        ContextRelation { source: "ShoppingCarContext".to_string(), target: "MallContext".to_string(), direction: PositiveDirected, source_types: vec![], target_types: vec![] },
-        ContextRelation { source: "ShoppingCarContext".to_string(), target: "MallContext".to_string(), direction: BiDirected, source_types: vec![], target_types: vec![] },
+        ContextRelation { source: "ShoppingCarContext".to_string(), target: "MallContext".to_string(), direction: PositiveDirected, source_types: vec![], target_types: vec![], loc: Loc(16, 50) },
+        ContextRelation { source: "ShoppingCarContext".to_string(), target: "MallContext".to_string(), direction: BiDirected, source_types: vec![], target_types: vec![], loc: Loc(53, 88) },
       ],
       loc: Loc(1, 90),
     }));
@@ -1062,6 +1645,10 @@ just for test
       entities: vec![],
       value_objects: vec![],
       domain_events: vec![],
+      services: vec![],
+      enums: vec![],
+      commands: vec![],
+      annotations: vec![],
       loc: Loc(1, 63),
     }));
   }
@@ -1089,20 +1676,32 @@ Aggregate ShoppingCart {
           VariableDefinition {
             name: "name".to_string(),
             type_type: "String".to_string(),
+            type_expr: TypeExpr::Named("String".to_string()),
             initializer: None,
+            annotations: vec![],
             loc: Loc(61, 73),
+            constraints: vec![],
           },
           VariableDefinition {
             name: "price".to_string(),
             type_type: "Money".to_string(),
+            type_expr: TypeExpr::Named("Money".to_string()),
             initializer: None,
+            annotations: vec![],
             loc: Loc(75, 87),
+            constraints: vec![],
           }],
         value_objects: vec![],
+        transitions: vec![],
+        annotations: vec![],
         loc: Loc(28, 92),
       }],
       value_objects: vec![],
       domain_events: vec![],
+      services: vec![],
+      enums: vec![],
+      commands: vec![],
+      annotations: vec![],
       loc: Loc(1, 94),
     }))
   }
@@ -1243,6 +1842,41 @@ Entity SalesPerson {
 ").unwrap();
   }
 
+  #[test]
+  fn sub_domain() {
+    let decls = parse(r#"
+CoreDomain Ticketing {
+  Context Reservation, Cinema;
+}
+
+SubDomain Identity {
+  type: Generic;
+  Context User;
+}
+"#).unwrap();
+
+    assert_eq!(decls[0], FklDeclaration::Domain(DomainDecl {
+      name: "Ticketing".to_string(),
+      domain_type: "CoreDomain".to_string(),
+      inline_doc: "".to_string(),
+      contexts: vec![
+        Identifier { name: "Reservation".to_string(), loc: Loc(34, 45) },
+        Identifier { name: "Cinema".to_string(), loc: Loc(47, 53) },
+      ],
+      attributes: vec![],
+      loc: Loc(1, 56),
+    }));
+
+    match &decls[1] {
+      FklDeclaration::Domain(domain) => {
+        assert_eq!(domain.domain_type, "SubDomain");
+        assert_eq!(domain.attributes[0].value, vec!["Generic".to_string()]);
+        assert_eq!(domain.contexts[0].name, "User");
+      }
+      _ => panic!("should be domain"),
+    }
+  }
+
   #[test]
   fn basic_vo_inline_aggregate() {
     let decls = parse(r#"Context Cart {
@@ -1278,54 +1912,77 @@ Entity SalesPerson {
                 name: "CartId".to_string(),
                 inline_doc: "".to_string(),
                 fields: vec![],
+                constructor: vec![],
+                annotations: vec![],
                 loc: Loc(58, 83),
               },
               ValueObjectDecl {
                 name: "CartStatus".to_string(),
                 inline_doc: "".to_string(),
                 fields: vec![],
+                constructor: vec![],
+                annotations: vec![],
                 loc: Loc(83, 112),
               },
               ValueObjectDecl {
                 name: "CartItem".to_string(),
                 inline_doc: "".to_string(),
                 fields: vec![],
+                constructor: vec![],
+                annotations: vec![],
                 loc: Loc(112, 139),
               },
               ValueObjectDecl {
                 name: "CartItemQuantity".to_string(),
                 inline_doc: "".to_string(),
                 fields: vec![],
+                constructor: vec![],
+                annotations: vec![],
                 loc: Loc(139, 174),
               },
               ValueObjectDecl {
                 name: "CartItemPrice".to_string(),
                 inline_doc: "".to_string(),
                 fields: vec![],
+                constructor: vec![],
+                annotations: vec![],
                 loc: Loc(174, 206),
               },
               ValueObjectDecl {
                 name: "CartItemTotal".to_string(),
                 inline_doc: "".to_string(),
                 fields: vec![],
+                constructor: vec![],
+                annotations: vec![],
                 loc: Loc(206, 238),
               },
               ValueObjectDecl {
                 name: "CartTotal".to_string(),
                 inline_doc: "".to_string(),
                 fields: vec![],
+                constructor: vec![],
+                annotations: vec![],
                 loc: Loc(238, 264),
               },
             ],
+            transitions: vec![],
+            annotations: vec![],
             loc: Loc(38, 265),
           }],
           value_objects: vec![],
           domain_events: vec![],
+          services: vec![],
+          enums: vec![],
+          commands: vec![],
+          annotations: vec![],
           loc: Loc(17, 269),
         }
       ],
       used_domain_objects: vec![],
+      annotations: vec![],
       loc: Loc(0, 271),
+      modules: vec![],
+      from_relation: false,
     }));
   }
 
@@ -1356,7 +2013,7 @@ Component SalesComponent {
         },
       ],
       used_domain_objects: vec![
-        UsedDomainObject { name: "SalesOrder".to_string(), loc: Loc(87, 97)  },
+        UsedDomainObject { name: "SalesOrder".to_string(), object_type: UsedDomainObjectType::Aggregate, loc: Loc(87, 97) },
       ],
       loc: Loc(1, 100),
     }));
@@ -1376,9 +2033,10 @@ ContextMap Mall {
         name: "Mall".to_string(),
         loc: Loc(11, 15),
       },
+      state: "".to_string(),
       contexts: vec![
-        BoundedContextDecl { name: "OrderContext".to_string(), domain_events: vec![], aggregates: vec![], used_domain_objects: vec![], loc: Loc(65, 77) },
-        BoundedContextDecl { name: "SalesContext".to_string(), domain_events: vec![], aggregates: vec![], used_domain_objects: vec![], loc: Loc(20, 32)  },
+        BoundedContextDecl { name: "OrderContext".to_string(), domain_events: vec![], aggregates: vec![], used_domain_objects: vec![], loc: Loc(65, 77), modules: vec![], annotations: vec![], from_relation: true },
+        BoundedContextDecl { name: "SalesContext".to_string(), domain_events: vec![], aggregates: vec![], used_domain_objects: vec![], loc: Loc(20, 32), modules: vec![], annotations: vec![], from_relation: true },
       ],
       relations: vec![ContextRelation {
         source: "SalesContext".to_string(),
@@ -1386,6 +2044,7 @@ ContextMap Mall {
         direction: BiDirected,
         source_types: vec!["OHS".to_string()],
         target_types: vec!["ACL".to_string(), "OHS".to_string()],
+        loc: Loc(21, 80),
       }],
       loc: Loc(1, 89),
     });
@@ -1421,23 +2080,28 @@ ContextMap Mall {
       identify: VariableDefinition {
         name: "".to_string(),
         type_type: "".to_string(),
+        type_expr: TypeExpr::default(),
         initializer: None,
+        annotations: vec![],
         loc: Default::default(),
+        constraints: vec![],
       },
       inline_doc: "".to_string(),
       fields: vec![
-        VariableDefinition { name: "id".to_string(), type_type: "String".to_string(), initializer: None, loc: Loc(37, 47) },
-        VariableDefinition { name: "token".to_string(), type_type: "UUID".to_string(), initializer: None, loc: Loc(53, 64) },
-        VariableDefinition { name: "status".to_string(), type_type: "ReservationStatus".to_string(), initializer: Some("ReservationStatus.OPEN".to_string()), loc: Loc(70, 120) },
-        VariableDefinition { name: "expiresAt".to_string(), type_type: "LocalDateTime".to_string(), initializer: None, loc: Loc(126, 150) },
-        VariableDefinition { name: "createdAt".to_string(), type_type: "LocalDateTime".to_string(), initializer: None, loc: Loc(156, 180) },
-        VariableDefinition { name: "screeningId".to_string(), type_type: "String".to_string(), initializer: None, loc: Loc(186, 205) },
-        VariableDefinition { name: "screeningStartTime".to_string(), type_type: "LocalDateTime".to_string(), initializer: None, loc: Loc(211, 244) },
-        VariableDefinition { name: "name".to_string(), type_type: "String".to_string(), initializer: None, loc: Loc(250, 262) },
-        VariableDefinition { name: "surname".to_string(), type_type: "String".to_string(), initializer: None, loc: Loc(268, 283) },
-        VariableDefinition { name: "tickets".to_string(), type_type: "Set<Ticket>".to_string(), initializer: None, loc: Loc(289, 309) },
-        VariableDefinition { name: "totalPrice".to_string(), type_type: "BigDecimal".to_string(), initializer: None, loc: Loc(315, 337) }],
+        VariableDefinition { name: "id".to_string(), type_type: "String".to_string(), type_expr: TypeExpr::Named("String".to_string()), initializer: None, loc: Loc(37, 47), constraints: vec![], annotations: vec![] },
+        VariableDefinition { name: "token".to_string(), type_type: "UUID".to_string(), type_expr: TypeExpr::Named("UUID".to_string()), initializer: None, loc: Loc(53, 64), constraints: vec![], annotations: vec![] },
+        VariableDefinition { name: "status".to_string(), type_type: "ReservationStatus".to_string(), type_expr: TypeExpr::Named("ReservationStatus".to_string()), initializer: Some("ReservationStatus.OPEN".to_string()), loc: Loc(70, 120), constraints: vec![], annotations: vec![] },
+        VariableDefinition { name: "expiresAt".to_string(), type_type: "LocalDateTime".to_string(), type_expr: TypeExpr::Named("LocalDateTime".to_string()), initializer: None, loc: Loc(126, 150), constraints: vec![], annotations: vec![] },
+        VariableDefinition { name: "createdAt".to_string(), type_type: "LocalDateTime".to_string(), type_expr: TypeExpr::Named("LocalDateTime".to_string()), initializer: None, loc: Loc(156, 180), constraints: vec![], annotations: vec![] },
+        VariableDefinition { name: "screeningId".to_string(), type_type: "String".to_string(), type_expr: TypeExpr::Named("String".to_string()), initializer: None, loc: Loc(186, 205), constraints: vec![], annotations: vec![] },
+        VariableDefinition { name: "screeningStartTime".to_string(), type_type: "LocalDateTime".to_string(), type_expr: TypeExpr::Named("LocalDateTime".to_string()), initializer: None, loc: Loc(211, 244), constraints: vec![], annotations: vec![] },
+        VariableDefinition { name: "name".to_string(), type_type: "String".to_string(), type_expr: TypeExpr::Named("String".to_string()), initializer: None, loc: Loc(250, 262), constraints: vec![], annotations: vec![] },
+        VariableDefinition { name: "surname".to_string(), type_type: "String".to_string(), type_expr: TypeExpr::Named("String".to_string()), initializer: None, loc: Loc(268, 283), constraints: vec![], annotations: vec![] },
+        VariableDefinition { name: "tickets".to_string(), type_type: "Set<Ticket>".to_string(), type_expr: TypeExpr::Generic("Set".to_string(), vec![TypeExpr::Named("Ticket".to_string())]), initializer: None, loc: Loc(289, 309), constraints: vec![], annotations: vec![] },
+        VariableDefinition { name: "totalPrice".to_string(), type_type: "BigDecimal".to_string(), type_expr: TypeExpr::Named("BigDecimal".to_string()), initializer: None, loc: Loc(315, 337), constraints: vec![], annotations: vec![] }],
       value_objects: vec![],
+      transitions: vec![],
+      annotations: vec![],
       loc: Loc(0, 344),
     }));
   }
@@ -1458,12 +2122,16 @@ Aggregate Cinema {
         name: "Cinema".to_string(),
         inline_doc: "".to_string(),
         used_domain_objects: vec![
-          UsedDomainObject { name: "Cinema".to_string(), loc: Loc(68, 74) },
-          UsedDomainObject { name: "ScreeningRoom".to_string(), loc: Loc(76, 89) },
-          UsedDomainObject { name: "Seat".to_string(), loc: Loc(91, 95) }],
+          UsedDomainObject { name: "Cinema".to_string(), object_type: UsedDomainObjectType::Entity, loc: Loc(68, 74) },
+          UsedDomainObject { name: "ScreeningRoom".to_string(), object_type: UsedDomainObjectType::Entity, loc: Loc(76, 89) },
+          UsedDomainObject { name: "Seat".to_string(), object_type: UsedDomainObjectType::Entity, loc: Loc(91, 95) }],
         entities: vec![],
         value_objects: vec![],
         domain_events: vec![],
+        services: vec![],
+        enums: vec![],
+        commands: vec![],
+        annotations: vec![],
         loc: Loc(40, 98),
       })
     );
@@ -1496,6 +2164,9 @@ struct Cinema {
         name: "".to_string(),
         method: "GET".to_string(),
         uri: "/book/{id}".to_string(),
+        path_params: vec![],
+        query_params: vec![],
+        headers: vec![],
         authorization: Some(AuthorizationDecl {
           auth_type: "Basic".to_string(),
           username: Some("admin".to_string()),
@@ -1507,10 +2178,13 @@ struct Cinema {
           name: "Cinema".to_string(),
           loc: Loc(104, 121),
         }),
+        responses: vec![],
         loc: Loc(29, 125),
       },
       target: None,
       flow: None,
+      command: None,
+      annotations: vec![],
       loc: Loc(1, 127),
     }));
 
@@ -1518,12 +2192,13 @@ struct Cinema {
       name: "Cinema".to_string(),
       inline_doc: "".to_string(),
       fields: vec![
-        VariableDefinition { name: "id".to_string(), type_type: "String".to_string(), initializer: None, loc: Loc(147, 157) },
-        VariableDefinition { name: "name".to_string(), type_type: "String".to_string(), initializer: None, loc: Loc(161, 173) },
-        VariableDefinition { name: "address".to_string(), type_type: "String".to_string(), initializer: None, loc: Loc(177, 192) },
-        VariableDefinition { name: "rooms".to_string(), type_type: "Set<ScreeningRoom>".to_string(), initializer: None, loc: Loc(196, 221) },
+        VariableDefinition { name: "id".to_string(), type_type: "String".to_string(), type_expr: TypeExpr::Named("String".to_string()), initializer: None, loc: Loc(147, 157), constraints: vec![], annotations: vec![] },
+        VariableDefinition { name: "name".to_string(), type_type: "String".to_string(), type_expr: TypeExpr::Named("String".to_string()), initializer: None, loc: Loc(161, 173), constraints: vec![], annotations: vec![] },
+        VariableDefinition { name: "address".to_string(), type_type: "String".to_string(), type_expr: TypeExpr::Named("String".to_string()), initializer: None, loc: Loc(177, 192), constraints: vec![], annotations: vec![] },
+        VariableDefinition { name: "rooms".to_string(), type_type: "Set<ScreeningRoom>".to_string(), type_expr: TypeExpr::Generic("Set".to_string(), vec![TypeExpr::Named("ScreeningRoom".to_string())]), initializer: None, loc: Loc(196, 221), constraints: vec![], annotations: vec![] },
       ],
-      loc: Loc(129, 224)
+      annotations: vec![],
+      loc: Loc(129, 224),
     }));
   }
 
@@ -1547,6 +2222,23 @@ imple CinemaCreatedEvent {
     };
   }
 
+  #[test]
+  fn structured_syntax_error() {
+    let error = parse(r#"
+imple CinemaCreatedEvent {
+
+}"#).unwrap_err();
+
+    let syntax_error = error.as_syntax_error().unwrap();
+    assert_eq!(syntax_error.location, (1, 1));
+    assert_eq!(syntax_error.line_col, (2, 1));
+    assert_eq!(syntax_error.expected, vec!["EOI".to_string(), "declaration".to_string()]);
+    assert_eq!(syntax_error.line, "imple CinemaCreatedEvent {");
+
+    let error = error.with_file("cinema.fkl");
+    assert!(format!("{}", error).starts_with(" --> cinema.fkl:2:1"));
+  }
+
   #[test]
   fn impl_with_flow() {
     let decls = parse(r#"impl CinemaUpdated {
@@ -1577,6 +2269,9 @@ imple CinemaCreatedEvent {
         name: "".to_string(),
         method: "POST".to_string(),
         uri: "/book/{id}".to_string(),
+        path_params: vec![],
+        query_params: vec![],
+        headers: vec![],
         authorization: Some(AuthorizationDecl {
           auth_type: "Basic".to_string(),
           username: Some("admin".to_string()),
@@ -1591,6 +2286,7 @@ imple CinemaCreatedEvent {
           name: "Cinema".to_string(),
           loc: Loc(175, 192),
         }),
+        responses: vec![],
         loc: Loc(48, 198),
       },
       target: Some(ImplementationTarget {
@@ -1610,8 +2306,11 @@ imple CinemaCreatedEvent {
             return_type: Some(VariableDefinition {
               name: "user".to_string(),
               type_type: "User".to_string(),
+              type_expr: TypeExpr::Named("User".to_string()),
               initializer: None,
+              annotations: vec![],
               loc: Loc(259, 278),
+              constraints: vec![],
             }),
             loc: Loc(219, 278),
           }),
@@ -1622,14 +2321,20 @@ imple CinemaCreatedEvent {
             arguments: vec![VariableDefinition {
               name: "user".to_string(),
               type_type: "User".to_string(),
+              type_expr: TypeExpr::Named("User".to_string()),
               initializer: None,
+              annotations: vec![],
               loc: Loc(303, 313),
+              constraints: vec![],
             }],
             return_type: Some(VariableDefinition {
               name: "user".to_string(),
               type_type: "User".to_string(),
+              type_expr: TypeExpr::Named("User".to_string()),
               initializer: None,
+              annotations: vec![],
               loc: Loc(323, 333),
+              constraints: vec![],
             }),
             loc: Loc(278, 334),
           }),
@@ -1642,6 +2347,8 @@ imple CinemaCreatedEvent {
         ],
         loc: Loc(204, 403),
       }),
+      command: None,
+      annotations: vec![],
       loc: Loc(0, 405),
     }));
   }
@@ -1796,13 +2503,158 @@ imple CinemaCreatedEvent {
       entities: vec![],
       value_objects: vec![],
       domain_events: vec![
-        DomainEventDecl { name: "UserCreated".to_string(), loc: Loc(31, 42) },
-        DomainEventDecl { name: "UserUpdated".to_string(), loc: Loc(44, 55) },
+        DomainEventDecl { name: "UserCreated".to_string(), inline_doc: "".to_string(), fields: vec![], annotations: vec![], loc: Loc(31, 42) },
+        DomainEventDecl { name: "UserUpdated".to_string(), inline_doc: "".to_string(), fields: vec![], annotations: vec![], loc: Loc(44, 55) },
       ],
+      services: vec![],
+      enums: vec![],
+      commands: vec![],
+      annotations: vec![],
       loc: Loc(0, 58),
     }));
   }
 
+  #[test]
+  fn entity_states() {
+    let decls = parse(r#"Entity Order {
+  states {
+    Draft -> Submitted on Submit;
+    Submitted -> Approved
+  }
+}"#).unwrap();
+
+    let entity = match &decls[0] {
+      FklDeclaration::Entity(entity) => entity,
+      _ => panic!("entity expected"),
+    };
+    assert_eq!(entity.transitions, vec![
+      TransitionDecl { from: "Draft".to_string(), to: "Submitted".to_string(), trigger: "Submit".to_string(), loc: Loc(30, 59) },
+      TransitionDecl { from: "Submitted".to_string(), to: "Approved".to_string(), trigger: "".to_string(), loc: Loc(64, 88) },
+    ]);
+  }
+
+  #[test]
+  fn command_decl() {
+    let decls = parse(r#"Aggregate Order {
+  Query FindOrders {
+    struct {
+      status: String;
+    }
+  }
+}
+
+Command PlaceOrder {
+  aggregate: Order;
+  struct {
+    items: List<OrderItem>;
+  }
+  DomainEvent OrderPlaced;
+}"#).unwrap();
+
+    let aggregate = match &decls[0] {
+      FklDeclaration::Aggregate(aggregate) => aggregate,
+      _ => panic!("aggregate expected"),
+    };
+    assert_eq!(aggregate.commands[0].name, "FindOrders");
+    assert_eq!(aggregate.commands[0].command_type, "Query");
+    assert_eq!(aggregate.commands[0].aggregate, Some(Identifier { name: "Order".to_string(), loc: Default::default() }));
+
+    assert_eq!(decls[1], FklDeclaration::Command(CommandDecl {
+      name: "PlaceOrder".to_string(),
+      command_type: "Command".to_string(),
+      inline_doc: "".to_string(),
+      aggregate: Some(Identifier { name: "Order".to_string(), loc: Loc(121, 126) }),
+      fields: vec![VariableDefinition {
+        name: "items".to_string(),
+        type_type: "List<OrderItem>".to_string(),
+        type_expr: TypeExpr::Generic("List".to_string(), vec![TypeExpr::Named("OrderItem".to_string())]),
+        initializer: None,
+        constraints: vec![],
+        annotations: vec![],
+        loc: Loc(143, 165),
+      }],
+      domain_events: vec![
+        DomainEventDecl { name: "OrderPlaced".to_string(), inline_doc: "".to_string(), fields: vec![], annotations: vec![], loc: Loc(185, 196) },
+      ],
+      annotations: vec![],
+      loc: Loc(87, 199),
+    }));
+  }
+
+  #[test]
+  fn service_decl() {
+    let decls = parse(r#"Aggregate Order {
+  Repository OrderRepository {
+    findById(id: String): Order;
+    save(order: Order);
+  }
+}
+
+DomainService PricingService {
+  aggregate: Order;
+  price(order: Order, coupon: String): BigDecimal;
+}"#).unwrap();
+
+    match &decls[0] {
+      FklDeclaration::Aggregate(aggregate) => {
+        let repository = &aggregate.services[0];
+        assert_eq!(repository.name, "OrderRepository");
+        assert_eq!(repository.service_type, "Repository");
+        assert_eq!(repository.aggregate, "Order");
+        assert_eq!(repository.methods[0].name, "findById");
+        assert_eq!(repository.methods[0].return_type, Some("Order".to_string()));
+        assert_eq!(repository.methods[1].parameters[0].type_type, "Order");
+        assert_eq!(repository.methods[1].return_type, None);
+      }
+      _ => panic!("should be aggregate"),
+    }
+
+    match &decls[1] {
+      FklDeclaration::Service(service) => {
+        assert_eq!(service.service_type, "DomainService");
+        assert_eq!(service.aggregate, "Order");
+        assert_eq!(service.methods[0].parameters.len(), 2);
+      }
+      _ => panic!("should be service"),
+    }
+  }
+
+  #[test]
+  fn module_decl() {
+    let decls = parse(r#"Context Ordering {
+  Module order {
+    """ place and track orders """
+    Aggregate Order {}
+  }
+}
+
+Module payment {
+  context: Ordering;
+  dependencies: [order];
+  Aggregate Payment;
+}"#).unwrap();
+
+    match &decls[0] {
+      FklDeclaration::BoundedContext(context) => {
+        let module = &context.modules[0];
+        assert_eq!(module.name, "order");
+        assert_eq!(module.context, "Ordering");
+        assert_eq!(module.inline_doc, " place and track orders ");
+        assert_eq!(module.aggregates[0].name, "Order");
+      }
+      _ => panic!("should be context"),
+    }
+
+    match &decls[1] {
+      FklDeclaration::Module(module) => {
+        assert_eq!(module.context, "Ordering");
+        assert_eq!(module.used_domain_objects[0].name, "Payment");
+        assert_eq!(module.attributes[1].value, vec!["order".to_string()]);
+      }
+      _ => panic!("should be module"),
+    }
+  }
+
   #[test]
   fn env_database() {
     let decls = parse(r#"
@@ -1926,6 +2778,7 @@ env Local {
         name: "architecture".to_string(),
         loc: Loc(11, 23),
       },
+      state: "".to_string(),
       contexts: vec![
         BoundedContextDecl {
           name: "analyze".to_string(),
@@ -1941,10 +2794,12 @@ env Local {
                   identify: Default::default(),
                   inline_doc: "".to_string(),
                   fields: vec![
-                    VariableDefinition { name: "id".to_string(), type_type: "String".to_string(), initializer: None, loc: Loc(116, 126) },
-                    VariableDefinition { name: "name".to_string(), type_type: "String".to_string(), initializer: None, loc: Loc(144, 156) },
+                    VariableDefinition { name: "id".to_string(), type_type: "String".to_string(), type_expr: TypeExpr::Named("String".to_string()), initializer: None, loc: Loc(116, 126), constraints: vec![], annotations: vec![] },
+                    VariableDefinition { name: "name".to_string(), type_type: "String".to_string(), type_expr: TypeExpr::Named("String".to_string()), initializer: None, loc: Loc(144, 156), constraints: vec![], annotations: vec![] },
                   ],
                   value_objects: vec![],
+                  transitions: vec![],
+                  annotations: vec![],
                   loc: Loc(91, 171),
                 },
                 EntityDecl {
@@ -1956,28 +2811,43 @@ env Local {
                     VariableDefinition {
                       name: "name".to_string(),
                       type_type: "String".to_string(),
+                      type_expr: TypeExpr::Named("String".to_string()),
                       initializer: None,
+                      annotations: vec![],
                       loc: Loc(253, 265),
+                      constraints: vec![],
                     },
                     VariableDefinition {
                       name: "type".to_string(),
                       type_type: "ArchComponentType".to_string(),
+                      type_expr: TypeExpr::Named("ArchComponentType".to_string()),
                       initializer: None,
+                      annotations: vec![],
                       loc: Loc(287, 327),
+                      constraints: vec![],
                     },
                   ],
                   value_objects: vec![],
+                  transitions: vec![],
+                  annotations: vec![],
                   loc: Loc(185, 342),
                 },
               ],
               value_objects: vec![],
               domain_events: vec![],
+              services: vec![],
+              enums: vec![],
+              commands: vec![],
+              annotations: vec![],
               loc: Loc(56, 352),
             },
           ],
           domain_events: vec![],
           used_domain_objects: vec![],
+          annotations: vec![],
           loc: Loc(30, 358),
+          modules: vec![],
+          from_relation: false,
         },
       ],
       relations: vec![],
@@ -1992,4 +2862,73 @@ env Local {
       Err(e)
     }).unwrap();
   }
+
+  #[test]
+  fn annotations() {
+    let decls = parse(r#"@owner("sales") @stability(beta)
+Context Sales {
+  @table(orders)
+  Aggregate Order {
+    @deprecated
+    Entity Order {
+      Struct {
+        @column("order_id") id: String;
+        total: Money @required;
+      }
+    }
+  }
+}"#).unwrap();
+
+    let context = match &decls[0] {
+      FklDeclaration::BoundedContext(context) => context,
+      _ => panic!("context expected"),
+    };
+    assert_eq!(context.annotations, vec![
+      AttributeDefinition { key: "owner".to_string(), value: vec!["sales".to_string()], loc: Loc(0, 15) },
+      AttributeDefinition { key: "stability".to_string(), value: vec!["beta".to_string()], loc: Loc(16, 32) },
+    ]);
+
+    let aggregate = &context.aggregates[0];
+    assert_eq!(aggregate.annotations[0].key, "table");
+    assert_eq!(aggregate.entities[0].annotations[0].key, "deprecated");
+    assert!(aggregate.entities[0].annotations[0].value.is_empty());
+
+    let fields = &aggregate.entities[0].fields;
+    assert_eq!(fields[0].annotations[0].value, vec!["order_id".to_string()]);
+    assert_eq!(fields[1].constraints[0].name, "required");
+    assert!(fields[1].annotations.is_empty());
+  }
+
+  #[test]
+  fn type_expr() {
+    let order = Box::new(TypeExpr::Named("Sales.Order".to_string()));
+    assert_eq!(parse_type_expr("Sales.Order?").unwrap(), TypeExpr::Optional(order.clone()));
+    assert_eq!(parse_type_expr("Optional<Sales.Order>").unwrap(), TypeExpr::Optional(order.clone()));
+    assert_eq!(parse_type_expr("Sales.Order [ ]?").unwrap(), TypeExpr::Optional(Box::new(TypeExpr::Array(order))));
+    assert_eq!(parse_type_expr("Map<String, List<Int>>").unwrap().to_string(), "Map<String, List<Int>>");
+    assert!(parse_type_expr("List<Order").is_err());
+  }
+
+  #[test]
+  fn annotation_after_constraint() {
+    let decls = parse("Struct User {\n  name: String @required\n  @column(\"user_email\") email: String\n}").unwrap();
+    let fields = match &decls[0] {
+      FklDeclaration::Struct(decl) => &decl.fields,
+      _ => panic!("struct expected"),
+    };
+    assert_eq!(fields[0].constraints.len(), 1);
+    assert_eq!(fields[0].type_type, "String");
+    assert_eq!(fields[1].annotations[0].value, vec!["user_email".to_string()]);
+    assert!(fields[1].constraints.is_empty());
+
+    let decls = parse("Struct User { tags: List<String> [ ]? @length (1, 8) @required, age: Int }").unwrap();
+    let fields = match &decls[0] {
+      FklDeclaration::Struct(decl) => &decl.fields,
+      _ => panic!("struct expected"),
+    };
+    assert_eq!(fields[0].type_expr.to_string(), "List<String>[]?");
+    assert_eq!(fields[0].constraints[0].args, vec!["1".to_string(), "8".to_string()]);
+    assert_eq!(fields[0].constraints.len(), 2);
+    assert_eq!(fields[1].name, "age");
+  }
 }
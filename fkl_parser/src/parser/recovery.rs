@@ -0,0 +1,465 @@
+use std::ops::Range;
+
+use crate::parser::ast::FklDeclaration;
+use crate::parser::parse_result::SyntaxError;
+use crate::parser::parser::{consume_declarations, inner_parse, to_syntax_error};
+
+/// declaration keywords which can start a top-level declaration
+const DECLARATION_KEYWORDS: [&str; 31] = [
+  "include", "ContextMap", "Context", "CoreDomain", "SupportingDomain", "GenericDomain", "SubDomain", "Module", "Aggregate", "Entity", "Concept", "VO", "ValueObject",
+  "struct", "Struct", "DomainEvent", "Command", "Query", "Service", "DomainService", "ApplicationService", "Repository", "Factory", "enum", "Enum", "Component", "impl", "Saga", "layered", "SourceSet", "env",
+];
+
+/// blocks which can skip a broken member and continue parsing
+const RECOVERABLE_BLOCKS: [&str; 3] = ["Context", "Aggregate", "impl"];
+
+/// The result of [`parse_with_recovery`]:
+/// - [`decls`]: all declarations which can be parsed, may be partial when there are errors
+/// - [`errors`]: all syntax errors, in source order
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RecoveredParse {
+  pub decls: Vec<FklDeclaration>,
+  pub errors: Vec<SyntaxError>,
+}
+
+/// parse the source code, and resync at top-level declarations and at members of
+/// `Context`, `Aggregate` and `impl` blocks, so that all syntax errors can be reported in one pass.
+///
+/// every chunk is parsed with the other code blanked to spaces, so the error location is the
+/// same as the origin source.
+pub fn parse_with_recovery(code: &str) -> RecoveredParse {
+  let mut recovered = RecoveredParse::default();
+  if let Ok(pairs) = inner_parse(code) {
+    recovered.decls = consume_declarations(pairs);
+    return recovered;
+  }
+
+  for chunk in split_declarations(code) {
+    let mut text = blank_outside(code, &chunk);
+    loop {
+      match inner_parse(&text) {
+        Ok(pairs) => {
+          recovered.decls.extend(consume_declarations(pairs));
+          break;
+        }
+        Err(e) => {
+          let mut error = to_syntax_error(e);
+          error.line = source_line(code, error.line_col.0);
+
+          let member = find_broken_member(&text, &chunk, error.location.0);
+          recovered.errors.push(error);
+
+          match member {
+            Some(range) => text = blank_range(&text, &range),
+            None => break,
+          }
+        }
+      }
+    }
+  }
+
+  recovered
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+  Open,
+  Close,
+  Semi,
+  Word,
+  /// the `@` of annotation
+  At,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Token {
+  kind: TokenKind,
+  start: usize,
+  end: usize,
+}
+
+/// a light scanner for braces, semicolons and words, which skip strings, inline docs and comments
+fn scan(text: &str, range: &Range<usize>) -> Vec<Token> {
+  let bytes = text.as_bytes();
+  let mut tokens = vec![];
+  let mut i = range.start;
+
+  while i < range.end {
+    let rest = &bytes[i..range.end];
+    if rest.starts_with(b"\"\"\"") {
+      i = skip_until(bytes, i + 3, range.end, b"\"\"\"");
+    } else if rest[0] == b'"' || rest[0] == b'\'' {
+      i = skip_until(bytes, i + 1, range.end, &rest[..1]);
+    } else if rest.starts_with(b"//") {
+      while i < range.end && bytes[i] != b'\n' {
+        i += 1;
+      }
+    } else if rest.starts_with(b"/*") {
+      i = skip_block_comment(bytes, i, range.end);
+    } else if rest[0] == b'_' || rest[0].is_ascii_alphanumeric() {
+      let start = i;
+      while i < range.end && (bytes[i] == b'_' || bytes[i].is_ascii_alphanumeric()) {
+        i += 1;
+      }
+      tokens.push(Token { kind: TokenKind::Word, start, end: i });
+    } else {
+      let kind = match rest[0] {
+        b'{' => Some(TokenKind::Open),
+        b'}' => Some(TokenKind::Close),
+        b';' => Some(TokenKind::Semi),
+        b'@' => Some(TokenKind::At),
+        _ => None,
+      };
+      if let Some(kind) = kind {
+        tokens.push(Token { kind, start: i, end: i + 1 });
+      }
+      i += 1;
+    }
+  }
+
+  tokens
+}
+
+fn skip_until(bytes: &[u8], from: usize, end: usize, pattern: &[u8]) -> usize {
+  let mut i = from;
+  while i < end {
+    if bytes[i..end].starts_with(pattern) {
+      return i + pattern.len();
+    }
+    i += 1;
+  }
+
+  end
+}
+
+fn skip_block_comment(bytes: &[u8], from: usize, end: usize) -> usize {
+  let mut depth = 0;
+  let mut i = from;
+  while i < end {
+    if bytes[i..end].starts_with(b"/*") {
+      depth += 1;
+      i += 2;
+    } else if bytes[i..end].starts_with(b"*/") {
+      depth -= 1;
+      i += 2;
+      if depth == 0 {
+        return i;
+      }
+    } else {
+      i += 1;
+    }
+  }
+
+  end
+}
+
+fn is_line_start(code: &str, pos: usize) -> bool {
+  pos == 0 || code.as_bytes()[pos - 1] == b'\n'
+}
+
+/// split the code into top-level declaration chunks, a chunk ends when the braces are balanced,
+/// or when a declaration keyword starts at top-level or at the beginning of a line.
+fn split_declarations(code: &str) -> Vec<Range<usize>> {
+  let mut chunks = vec![];
+  let mut depth = 0;
+  let mut start: Option<usize> = None;
+
+  for token in scan(code, &(0..code.len())) {
+    if token.kind == TokenKind::Word
+      && DECLARATION_KEYWORDS.contains(&&code[token.start..token.end])
+      && (depth == 0 || is_line_start(code, token.start)) {
+      match start {
+        // the annotations before keyword are in the same chunk
+        Some(begin) if depth == 0 && code.as_bytes()[begin] == b'@' => {}
+        Some(begin) => {
+          chunks.push(begin..token.start);
+          start = Some(token.start);
+        }
+        None => start = Some(token.start),
+      }
+      depth = 0;
+    }
+
+    if start.is_none() {
+      start = Some(token.start);
+    }
+
+    match token.kind {
+      TokenKind::Open => depth += 1,
+      TokenKind::Close => {
+        if depth > 0 {
+          depth -= 1;
+        }
+        if depth == 0 {
+          chunks.push(start.unwrap()..token.end);
+          start = None;
+        }
+      }
+      _ => {}
+    }
+  }
+
+  if let Some(begin) = start {
+    chunks.push(begin..code.len());
+  }
+
+  chunks
+}
+
+struct Frame {
+  recoverable: bool,
+  member_start: usize,
+  member_word: Option<Range<usize>>,
+}
+
+/// find the member which contains the error position in the innermost recoverable block,
+/// a member ends with `;` or with a balanced `}`.
+fn find_broken_member(text: &str, chunk: &Range<usize>, pos: usize) -> Option<Range<usize>> {
+  let tokens = scan(text, chunk);
+  let mut stack = vec![Frame { recoverable: false, member_start: chunk.start, member_word: None }];
+
+  let split = tokens.iter().position(|token| token.start >= pos).unwrap_or(tokens.len());
+  let mut annotation = false;
+  for token in &tokens[..split] {
+    let top = stack.last_mut().unwrap();
+    match token.kind {
+      // the name of annotation is not the member word, like `owner` of `@owner("a") Aggregate A {}`
+      TokenKind::At => annotation = true,
+      TokenKind::Word if annotation => annotation = false,
+      TokenKind::Word => {
+        if top.member_word.is_none() {
+          top.member_word = Some(token.start..token.end);
+        }
+      }
+      TokenKind::Semi => {
+        top.member_start = token.end;
+        top.member_word = None;
+      }
+      TokenKind::Open => {
+        let recoverable = match &top.member_word {
+          Some(word) => RECOVERABLE_BLOCKS.contains(&&text[word.clone()]),
+          None => false,
+        };
+        stack.push(Frame { recoverable, member_start: token.end, member_word: None });
+      }
+      TokenKind::Close => {
+        if stack.len() > 1 {
+          stack.pop();
+        }
+        let top = stack.last_mut().unwrap();
+        top.member_start = token.end;
+        top.member_word = None;
+      }
+    }
+  }
+
+  let index = stack.iter().rposition(|frame| frame.recoverable)?;
+  let start = stack[index].member_start;
+  let mut depth = stack.len() - 1 - index;
+  let mut end = chunk.end;
+
+  for token in &tokens[split..] {
+    match token.kind {
+      TokenKind::Open => depth += 1,
+      TokenKind::Close => {
+        if depth == 0 {
+          end = token.start;
+          break;
+        }
+        depth -= 1;
+        if depth == 0 {
+          end = token.end;
+          break;
+        }
+      }
+      TokenKind::Semi => {
+        if depth == 0 {
+          end = token.end;
+          break;
+        }
+      }
+      TokenKind::Word | TokenKind::At => {}
+    }
+  }
+
+  if start >= end || text[start..end].trim().is_empty() {
+    return None;
+  }
+
+  Some(start..end)
+}
+
+/// replace the code outside the range with spaces, but keep the newlines and byte offsets
+fn blank_outside(code: &str, range: &Range<usize>) -> String {
+  code.char_indices()
+    .map(|(index, ch)| {
+      if range.contains(&index) || ch == '\n' {
+        ch.to_string()
+      } else {
+        " ".repeat(ch.len_utf8())
+      }
+    })
+    .collect()
+}
+
+fn blank_range(text: &str, range: &Range<usize>) -> String {
+  text.char_indices()
+    .map(|(index, ch)| {
+      if range.contains(&index) && ch != '\n' {
+        " ".repeat(ch.len_utf8())
+      } else {
+        ch.to_string()
+      }
+    })
+    .collect()
+}
+
+fn source_line(code: &str, line: usize) -> String {
+  code.lines().nth(line.saturating_sub(1)).unwrap_or_default().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::parser::ast::FklDeclaration;
+  use crate::parser::recovery::parse_with_recovery;
+
+  #[test]
+  fn no_error_for_valid_code() {
+    let recovered = parse_with_recovery(r#"
+ContextMap TicketBooking {
+  Reservation -> Cinema;
+}
+
+Context Cinema {
+  Aggregate Cinema;
+}
+"#);
+
+    assert!(recovered.errors.is_empty());
+    assert_eq!(recovered.decls.len(), 2);
+  }
+
+  #[test]
+  fn resync_at_top_level_declaration() {
+    let recovered = parse_with_recovery(r#"
+imple CinemaCreatedEvent {
+
+}
+
+Context Cinema {
+  Aggregate Cinema;
+}
+
+Entity Seat {
+  struct {
+    id: String
+    seat: String;
+  }
+  bad
+}
+
+Aggregate Cinema {
+  Entity Cinema, ScreeningRoom, Seat;
+}
+"#);
+
+    assert_eq!(recovered.errors.len(), 2);
+    assert_eq!(recovered.errors[0].line_col, (2, 1));
+    assert_eq!(recovered.errors[0].line, "imple CinemaCreatedEvent {");
+    assert_eq!(recovered.errors[1].line_col, (15, 3));
+    assert_eq!(recovered.errors[1].line, "  bad");
+
+    assert_eq!(recovered.decls.len(), 2);
+    assert!(matches!(recovered.decls[0], FklDeclaration::BoundedContext(_)));
+    assert!(matches!(recovered.decls[1], FklDeclaration::Aggregate(_)));
+  }
+
+  #[test]
+  fn keep_annotations_of_declaration() {
+    let recovered = parse_with_recovery(r#"
+Entity Seat {
+  bad
+}
+
+@owner("cinema")
+Context Cinema {
+  @table(seats)
+  Aggregate Seat {}
+}
+"#);
+
+    assert_eq!(recovered.errors.len(), 1);
+    assert_eq!(recovered.decls.len(), 1);
+    match &recovered.decls[0] {
+      FklDeclaration::BoundedContext(context) => {
+        assert_eq!(context.annotations[0].key, "owner");
+        assert_eq!(context.aggregates[0].annotations[0].key, "table");
+      }
+      _ => panic!("context expected"),
+    }
+  }
+
+  #[test]
+  fn resync_at_members_of_block() {
+    let recovered = parse_with_recovery(r#"
+Context Cinema {
+  Aggregate Cinema {
+    Entity Cinema, ScreeningRoom;
+    Entity Seat {
+      struct {
+        seat String;
+      }
+    }
+    Entity Movie;
+  }
+
+  Aggregate Screen {}
+  Aggregat Room;
+}
+
+impl CinemaCreated {
+  endpoint {
+    POST "/book/{id}";
+  }
+
+  aggregate: ;
+  entity: Cinema;
+}
+"#);
+
+    assert_eq!(recovered.errors.len(), 3);
+    assert_eq!(recovered.errors[0].line_col.0, 7);
+    assert_eq!(recovered.errors[1].line_col.0, 14);
+    assert_eq!(recovered.errors[2].line_col.0, 22);
+
+    match &recovered.decls[0] {
+      FklDeclaration::BoundedContext(context) => {
+        assert_eq!(context.aggregates.len(), 2);
+        assert_eq!(context.aggregates[0].used_domain_objects.len(), 3);
+      }
+      _ => panic!("should be context"),
+    }
+    match &recovered.decls[1] {
+      FklDeclaration::Implementation(implementation) => {
+        assert_eq!(implementation.endpoint.uri, "/book/{id}");
+        assert_eq!(implementation.target.as_ref().unwrap().name, "Cinema");
+      }
+      _ => panic!("should be implementation"),
+    }
+  }
+
+  #[test]
+  fn missing_close_brace() {
+    let recovered = parse_with_recovery(r#"
+Context Cinema {
+  Aggregate Cinema;
+
+Context Movie {
+  Aggregate Movie;
+}
+"#);
+
+    assert_eq!(recovered.errors.len(), 1);
+    assert_eq!(recovered.decls.len(), 1);
+  }
+}
@@ -0,0 +1,137 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use indexmap::IndexMap;
+
+use crate::parser::ast::FklDeclaration;
+use crate::parser::parse;
+use crate::parser::parse_result::ParseError;
+use crate::resolve::include_resolver::include_resolver;
+
+/// A parsed `.fkl` file in the include graph.
+/// - [`includes`]: the resolved paths of the `include` declarations, in the declaration order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceFile {
+  pub path: PathBuf,
+  pub code: String,
+  pub decls: Vec<FklDeclaration>,
+  pub includes: Vec<PathBuf>,
+}
+
+/// # Include Graph
+/// The dependency DAG of the entry file and all the files it includes, every file is parsed once,
+/// even it's included by many files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IncludeGraph {
+  pub entry: PathBuf,
+  pub files: IndexMap<PathBuf, SourceFile>,
+}
+
+impl IncludeGraph {
+  pub fn build(entry: &Path) -> Result<IncludeGraph, ParseError> {
+    let entry = entry.canonicalize()
+      .map_err(|e| ParseError::msg(format!("cannot read file {}: {}", entry.display(), e)))?;
+
+    let mut graph = IncludeGraph { entry: entry.clone(), files: IndexMap::new() };
+    let mut stack: Vec<PathBuf> = vec![];
+    graph.visit(&entry, &mut stack)?;
+
+    Ok(graph)
+  }
+
+  /// the path for display, relative to the directory of entry file if possible
+  pub fn display_path(&self, path: &Path) -> String {
+    let base = self.entry.parent().unwrap_or(Path::new(""));
+    match path.strip_prefix(base) {
+      Ok(relative) => relative.display().to_string(),
+      Err(_) => path.display().to_string(),
+    }
+  }
+
+  fn visit(&mut self, path: &PathBuf, stack: &mut Vec<PathBuf>) -> Result<(), ParseError> {
+    if let Some(index) = stack.iter().position(|it| it == path) {
+      let chain = stack[index..].iter()
+        .chain(std::iter::once(path))
+        .map(|it| self.display_path(it))
+        .collect();
+      return Err(ParseError::include_cycle(chain));
+    }
+
+    if self.files.contains_key(path) {
+      return Ok(());
+    }
+
+    let code = fs::read_to_string(path)
+      .map_err(|e| ParseError::msg(format!("cannot read file {}: {}", self.display_path(path), e)))?;
+    let decls = parse(&code).map_err(|e| e.with_file(self.display_path(path)))?;
+
+    let mut includes = vec![];
+    for decl in &decls {
+      if let FklDeclaration::Include(include) = decl {
+        let resolved = include_resolver(&path.display().to_string(), &include.path)
+          .ok_or_else(|| ParseError::msg(format!("cannot resolve include \"{}\" in {}", include.path, self.display_path(path))))?;
+        includes.push(resolved);
+      }
+    }
+
+    stack.push(path.clone());
+    for include in &includes {
+      self.visit(include, stack)?;
+    }
+    stack.pop();
+
+    self.files.insert(path.clone(), SourceFile { path: path.clone(), code, decls, includes });
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::fs;
+
+  use crate::parser::parse_result::ErrorKind;
+  use crate::resolve::include_graph::IncludeGraph;
+
+  #[test]
+  fn diamond_include() {
+    let temp = tempfile::tempdir().unwrap();
+    let dir = temp.path();
+    fs::write(dir.join("main.fkl"), "include \"./a.fkl\"\ninclude \"./b.fkl\"").unwrap();
+    fs::write(dir.join("a.fkl"), "include \"./common.fkl\"").unwrap();
+    fs::write(dir.join("b.fkl"), "include \"./common.fkl\"").unwrap();
+    fs::write(dir.join("common.fkl"), "Context Cinema {}").unwrap();
+
+    let graph = IncludeGraph::build(&dir.join("main.fkl")).unwrap();
+    assert_eq!(graph.files.len(), 4);
+    assert_eq!(graph.files[&graph.entry].includes.len(), 2);
+  }
+
+  #[test]
+  fn include_cycle() {
+    let temp = tempfile::tempdir().unwrap();
+    let dir = temp.path();
+    fs::write(dir.join("main.fkl"), "include \"./a.fkl\"").unwrap();
+    fs::write(dir.join("a.fkl"), "include \"./b.fkl\"").unwrap();
+    fs::write(dir.join("b.fkl"), "include \"./a.fkl\"").unwrap();
+
+    let error = IncludeGraph::build(&dir.join("main.fkl")).unwrap_err();
+    match &error.kind {
+      ErrorKind::IncludeCycle(chain) => {
+        assert_eq!(chain, &vec!["a.fkl".to_string(), "b.fkl".to_string(), "a.fkl".to_string()]);
+      }
+      _ => panic!("should be include cycle"),
+    }
+    assert_eq!(error.to_string(), "include cycle detected: a.fkl -> b.fkl -> a.fkl");
+  }
+
+  #[test]
+  fn syntax_error_in_included_file() {
+    let temp = tempfile::tempdir().unwrap();
+    let dir = temp.path();
+    fs::write(dir.join("main.fkl"), "include \"./a.fkl\"").unwrap();
+    fs::write(dir.join("a.fkl"), "imple Cinema {}").unwrap();
+
+    let error = IncludeGraph::build(&dir.join("main.fkl")).unwrap_err();
+    assert_eq!(error.as_syntax_error().unwrap().file, Some("a.fkl".to_string()));
+  }
+}
@@ -1 +1,2 @@
 pub mod include_resolver;
+pub mod include_graph;
@@ -0,0 +1,73 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use fkl_mir::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+  Error,
+  Warning,
+}
+
+impl fmt::Display for Severity {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Severity::Error => write!(f, "error"),
+      Severity::Warning => write!(f, "warning"),
+    }
+  }
+}
+
+/// A semantic problem found after lowering, like an undefined reference or a duplicate name.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+  pub severity: Severity,
+  pub message: String,
+  pub span: Span,
+}
+
+impl Diagnostic {
+  pub fn error(message: String, span: &Span) -> Self {
+    Diagnostic { severity: Severity::Error, message, span: span.clone() }
+  }
+
+  pub fn warning(message: String, span: &Span) -> Self {
+    Diagnostic { severity: Severity::Warning, message, span: span.clone() }
+  }
+
+  pub fn is_error(&self) -> bool {
+    self.severity == Severity::Error
+  }
+}
+
+impl fmt::Display for Diagnostic {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{}: {}", self.severity, self.message)?;
+    if self.span.is_empty() {
+      return Ok(());
+    }
+
+    match &self.span.file {
+      Some(file) => write!(f, "\n --> {}:{}:{}", file, self.span.line, self.span.column),
+      None => write!(f, "\n --> {}:{}", self.span.line, self.span.column),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use fkl_mir::Span;
+
+  use crate::semantic::Diagnostic;
+
+  #[test]
+  fn render_diagnostic() {
+    let diagnostic = Diagnostic::warning("aggregate `Order` is not declared".to_string(), &Span::new(10, 15, 2, 3).with_file("main.fkl"));
+    assert_eq!(diagnostic.to_string(), "warning: aggregate `Order` is not declared\n --> main.fkl:2:3");
+
+    let diagnostic = Diagnostic::error("duplicate entity `Order`".to_string(), &Span::default());
+    assert_eq!(diagnostic.to_string(), "error: duplicate entity `Order`");
+  }
+}
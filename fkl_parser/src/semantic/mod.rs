@@ -0,0 +1,391 @@
+use std::fmt;
+
+use fkl_mir::{ContextMap, Resolution, Span, StateMachine, Symbol, SymbolTable, SymbolType, TypeExpr};
+use fkl_mir::implementation::Implementation;
+
+pub use diagnostic::*;
+
+pub mod diagnostic;
+
+/// the builtin types of field, in lowercase
+const BUILTIN_TYPES: [&str; 30] = [
+  "any", "object", "void", "string", "char", "int", "integer", "long", "short", "byte", "float", "double",
+  "decimal", "bigdecimal", "number", "bool", "boolean", "date", "datetime", "time", "timestamp", "instant",
+  "uuid", "list", "set", "map", "vec", "array", "optional", "json",
+];
+
+/// the declarations which can be the type of field
+const TYPE_KINDS: [SymbolKind; 5] = [SymbolKind::Aggregate, SymbolKind::Entity, SymbolKind::ValueObject, SymbolKind::Struct, SymbolKind::Enum];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SymbolKind {
+  Context,
+  Aggregate,
+  Entity,
+  ValueObject,
+  Struct,
+  Enum,
+  Command,
+  Query,
+  /// the type of field, can be a builtin type or any declared type
+  Type,
+}
+
+impl fmt::Display for SymbolKind {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      SymbolKind::Context => write!(f, "context"),
+      SymbolKind::Aggregate => write!(f, "aggregate"),
+      SymbolKind::Entity => write!(f, "entity"),
+      SymbolKind::ValueObject => write!(f, "value object"),
+      SymbolKind::Struct => write!(f, "struct"),
+      SymbolKind::Enum => write!(f, "enum"),
+      SymbolKind::Command => write!(f, "command"),
+      SymbolKind::Query => write!(f, "query"),
+      SymbolKind::Type => write!(f, "type"),
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolRef {
+  pub kind: SymbolKind,
+  pub name: String,
+  /// the names of declarations which the symbol is in, like `["Sales", "Order"]`
+  pub scope: Vec<String>,
+  pub span: Span,
+}
+
+/// the declarations and references collected in lowering, the lowered ContextMap has merged the
+/// duplicates and created placeholders for the undefined references.
+/// - [`targets`]: the aggregates, entities, commands and queries which implementations are bound to
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Symbols {
+  pub declarations: Vec<SymbolRef>,
+  pub references: Vec<SymbolRef>,
+  pub targets: Vec<SymbolRef>,
+  /// the scope of the symbols which are recording
+  pub scope: Vec<String>,
+}
+
+impl Symbols {
+  /// the symbols recorded before [`Symbols::exit`] are in the scope of declaration
+  pub fn enter(&mut self, name: &str) {
+    self.scope.push(name.to_string());
+  }
+
+  pub fn exit(&mut self) {
+    self.scope.pop();
+  }
+
+  pub fn declare(&mut self, kind: SymbolKind, name: &str, span: Span) {
+    let symbol = self.symbol(kind, name, span);
+    self.declarations.push(symbol);
+  }
+
+  pub fn refer(&mut self, kind: SymbolKind, name: &str, span: Span) {
+    let symbol = self.symbol(kind, name, span);
+    self.references.push(symbol);
+  }
+
+  pub fn target(&mut self, kind: SymbolKind, name: &str, span: Span) {
+    let symbol = self.symbol(kind, name, span);
+    self.targets.push(symbol);
+  }
+
+  fn symbol(&self, kind: SymbolKind, name: &str, span: Span) -> SymbolRef {
+    SymbolRef { kind, name: name.to_string(), scope: self.scope.clone(), span }
+  }
+
+  /// refer all the type names in the type of field
+  pub fn refer_type(&mut self, type_expr: &TypeExpr, span: Span) {
+    for name in type_expr.names() {
+      if !name.is_empty() {
+        self.refer(SymbolKind::Type, name, span.clone());
+      }
+    }
+  }
+
+  /// find the declaration by name, the owners of qualified name are checked by [`check_ambiguities`]
+  fn find(&self, kinds: &[SymbolKind], name: &str) -> Option<&SymbolRef> {
+    let name = name.rsplit('.').next().unwrap_or_default();
+    self.declarations.iter().find(|decl| decl.name == name && kinds.contains(&decl.kind))
+  }
+
+  fn is_declared(&self, kinds: &[SymbolKind], symbol: &Symbol) -> bool {
+    self.declarations.iter().any(|decl| kinds.contains(&decl.kind) && symbol.span() == Some(&decl.span))
+  }
+}
+
+/// check the undefined references and duplicate declarations.
+pub fn check(symbols: &Symbols) -> Vec<Diagnostic> {
+  let mut diagnostics = vec![];
+  check_duplicates(symbols, &mut diagnostics);
+  check_references(symbols, &mut diagnostics);
+  check_targets(symbols, &mut diagnostics);
+  diagnostics
+}
+
+/// the aggregates and entities in different scopes are different symbols, the others are global
+fn check_duplicates(symbols: &Symbols, diagnostics: &mut Vec<Diagnostic>) {
+  let kinds = [SymbolKind::Aggregate, SymbolKind::Entity, SymbolKind::Struct, SymbolKind::Enum, SymbolKind::Command, SymbolKind::Query];
+  let scoped = [SymbolKind::Aggregate, SymbolKind::Entity];
+  for (index, decl) in symbols.declarations.iter().enumerate() {
+    if !kinds.contains(&decl.kind) {
+      continue;
+    }
+
+    let first = symbols.declarations[..index].iter()
+      .find(|it| it.kind == decl.kind && it.name == decl.name && (!scoped.contains(&decl.kind) || it.scope == decl.scope));
+    if let Some(first) = first {
+      let message = match first.span.line {
+        0 => format!("duplicate {} `{}`", decl.kind, decl.name),
+        line => format!("duplicate {} `{}`, first declared at line {}", decl.kind, decl.name, line),
+      };
+      diagnostics.push(Diagnostic::error(message, &decl.span));
+    }
+  }
+}
+
+fn check_references(symbols: &Symbols, diagnostics: &mut Vec<Diagnostic>) {
+  for reference in &symbols.references {
+    let message = match reference.kind {
+      SymbolKind::Aggregate => match symbols.find(&[SymbolKind::Aggregate], &reference.name) {
+        Some(_) => continue,
+        None => format!("aggregate `{}` is not declared, an empty aggregate is created", reference.name),
+      },
+      // the used value objects are also declared as entities
+      SymbolKind::Entity => match symbols.find(&[SymbolKind::Entity, SymbolKind::ValueObject], &reference.name) {
+        Some(_) => continue,
+        None => format!("entity `{}` is not declared", reference.name),
+      },
+      SymbolKind::Type => {
+        let name = reference.name.rsplit('.').next().unwrap_or_default();
+        if BUILTIN_TYPES.contains(&name.to_lowercase().as_str()) {
+          continue;
+        }
+
+        match symbols.find(&TYPE_KINDS, name) {
+          Some(_) => continue,
+          None => format!("unknown type `{}`, it's not a builtin type or declared struct, entity, value object or enum", reference.name),
+        }
+      }
+      kind => match symbols.find(&[kind], &reference.name) {
+        Some(_) => continue,
+        None => format!("{} `{}` is not declared", kind, reference.name),
+      },
+    };
+
+    diagnostics.push(Diagnostic::warning(message, &reference.span));
+  }
+}
+
+fn check_targets(symbols: &Symbols, diagnostics: &mut Vec<Diagnostic>) {
+  for target in &symbols.targets {
+    if symbols.find(&[target.kind], &target.name).is_none() {
+      let message = format!("the target {} `{}` of implementation is not declared", target.kind, target.name);
+      diagnostics.push(Diagnostic::error(message, &target.span));
+    }
+  }
+}
+
+/// check the references in the lowered ContextMap, warn the unqualified names which are declared in
+/// many scopes and none of them is closer to the reference, and the qualified names which don't
+/// match the owners of declarations.
+pub fn check_ambiguities(symbols: &Symbols, table: &SymbolTable) -> Vec<Diagnostic> {
+  let mut diagnostics = vec![];
+  for reference in &symbols.references {
+    let kinds: &[SymbolKind] = match reference.kind {
+      SymbolKind::Aggregate => &[SymbolKind::Aggregate],
+      SymbolKind::Entity | SymbolKind::ValueObject => &[SymbolKind::Entity, SymbolKind::ValueObject],
+      SymbolKind::Type if !BUILTIN_TYPES.contains(&reference.name.to_lowercase().as_str()) => &TYPE_KINDS,
+      _ => continue,
+    };
+
+    let accept = |symbol: &Symbol| kind_of(symbol).is_some_and(|kind| kinds.contains(&kind)) && symbols.is_declared(kinds, symbol);
+    let message = match table.resolve(&reference.name, &full_scope(table, &reference.scope), accept) {
+      Resolution::Found(_) => continue,
+      Resolution::Ambiguous(candidates) => {
+        let names: Vec<String> = candidates.iter().map(|it| format!("`{}`", it.qualified_name())).collect();
+        format!("ambiguous {} `{}`, it's declared as {}, use the qualified name instead", reference.kind, reference.name, names.join(", "))
+      }
+      // the unqualified ones are checked by `check_references`, and the qualified types can be the
+      // types of libraries, like `java.time.Instant`, unless they start with a context or aggregate
+      Resolution::NotFound => {
+        if !reference.name.contains('.') || symbols.find(kinds, &reference.name).is_none() {
+          continue;
+        }
+        if reference.kind == SymbolKind::Type && !is_model_root(table, &reference.name) {
+          continue;
+        }
+        format!("{} `{}` is not declared", reference.kind, reference.name)
+      }
+    };
+
+    diagnostics.push(Diagnostic::warning(message, &reference.span));
+  }
+
+  diagnostics
+}
+
+/// the qualified name starts with a context or an aggregate, like `Sales.Order`
+fn is_model_root(table: &SymbolTable, name: &str) -> bool {
+  let root = name.split('.').next().unwrap_or_default();
+  let accept = |symbol: &Symbol| matches!(kind_of(symbol), Some(SymbolKind::Context | SymbolKind::Aggregate));
+  !matches!(table.resolve(root, &[], accept), Resolution::NotFound)
+}
+
+/// the scope in the lowered ContextMap, like `["Sales", "Order"]` for the top-level aggregate `Order`
+/// which is used by context `Sales`
+fn full_scope(table: &SymbolTable, scope: &[String]) -> Vec<String> {
+  if scope.is_empty() {
+    return vec![];
+  }
+
+  match table.resolve(&scope.join("."), &[], |owner| kind_of(owner).is_some()) {
+    Resolution::Found(owner) => owner.path(),
+    _ => scope.to_vec(),
+  }
+}
+
+fn kind_of(symbol: &Symbol) -> Option<SymbolKind> {
+  match symbol.symbol_type {
+    SymbolType::BoundedContext(_) => Some(SymbolKind::Context),
+    SymbolType::Aggregate(_) => Some(SymbolKind::Aggregate),
+    SymbolType::Entity(_) => Some(SymbolKind::Entity),
+    SymbolType::ValueObject(_) => Some(SymbolKind::ValueObject),
+    SymbolType::Struct(_) => Some(SymbolKind::Struct),
+    SymbolType::Enum(_) => Some(SymbolKind::Enum),
+    _ => None,
+  }
+}
+
+/// check the states of entity which can't be reached from the initial state.
+pub fn check_states(entity: &str, states: &StateMachine) -> Vec<Diagnostic> {
+  let mut diagnostics = vec![];
+  for state in states.unreachable_states() {
+    let transition = states.transitions.iter().find(|it| &it.from == state || &it.to == state);
+    let span = transition.map(|it| it.span.clone()).unwrap_or_default();
+    let message = format!("state `{}` of entity `{}` is unreachable from the initial state `{}`", state, entity, states.initial);
+    diagnostics.push(Diagnostic::warning(message, &span));
+  }
+
+  // a state can't go to different states by the same trigger, and the same transition can't be repeated
+  for (index, transition) in states.transitions.iter().enumerate() {
+    let first = states.transitions[..index].iter().find(|it| {
+      it.from == transition.from && it.trigger == transition.trigger && (!it.trigger.is_empty() || it.to == transition.to)
+    });
+    let message = match first {
+      Some(first) if first.to == transition.to => {
+        format!("duplicate transition from `{}` to `{}` of entity `{}`", transition.from, transition.to, entity)
+      }
+      Some(first) => format!("state `{}` of entity `{}` goes to both `{}` and `{}` on `{}`", transition.from, entity, first.to, transition.to, transition.trigger),
+      None => continue,
+    };
+    diagnostics.push(Diagnostic::error(message, &transition.span));
+  }
+
+  diagnostics
+}
+
+/// check the steps of sagas only talk to the orchestrating context, or the contexts which have a
+/// relation with it.
+pub fn check_sagas(context_map: &ContextMap) -> Vec<Diagnostic> {
+  let mut diagnostics = vec![];
+  for saga in &context_map.sagas {
+    let owner = saga.owner();
+    for step in &saga.steps {
+      if step.context.is_empty() {
+        let message = format!("the context of step `{}` in saga `{}` is not declared", step.name, saga.name);
+        diagnostics.push(Diagnostic::error(message, &step.span));
+        continue;
+      }
+
+      if step.context != owner && !context_map.has_relation(owner, &step.context) {
+        let message = format!("step `{}` of saga `{}` talks to context `{}`, which has no relation with context `{}`", step.name, saga.name, step.context, owner);
+        diagnostics.push(Diagnostic::error(message, &step.span));
+      }
+    }
+  }
+
+  diagnostics
+}
+
+/// check the flow steps call the methods declared by services, or only the services if no method
+pub fn check_calls(context_map: &ContextMap) -> Vec<Diagnostic> {
+  let mut diagnostics = vec![];
+  for (impl_name, call) in context_map.unresolved_calls() {
+    let message = match context_map.get_service(&call.object) {
+      Some(_) => format!("method `{}::{}` called in impl `{}` is not declared", call.object, call.method, impl_name),
+      None => format!("service `{}` called in impl `{}` is not declared", call.object, impl_name),
+    };
+    diagnostics.push(Diagnostic::warning(message, &impl_span(context_map, &impl_name)));
+  }
+
+  diagnostics
+}
+
+/// check the default values of enum fields are the declared variants
+pub fn check_enum_defaults(context_map: &ContextMap) -> Vec<Diagnostic> {
+  let mut diagnostics = vec![];
+  for (owner, field) in context_map.invalid_enum_defaults() {
+    let message = format!("default `{}` of field `{}.{}` is not a variant of enum `{}`", field.initializer.unwrap_or_default(), owner, field.name, field.type_expr.name());
+    diagnostics.push(Diagnostic::warning(message, &field.span));
+  }
+
+  diagnostics
+}
+
+/// check the endpoints match their commands or queries, see [`ContextMap::mismatched_commands`]
+pub fn check_commands(context_map: &ContextMap) -> Vec<Diagnostic> {
+  let mut diagnostics = vec![];
+  for (impl_name, command) in context_map.mismatched_commands() {
+    let kind = command.command_type.to_string().to_lowercase();
+    let message = format!("endpoint of impl `{}` doesn't match {} `{}` of aggregate `{}`", impl_name, kind, command.name, command.aggregate);
+    diagnostics.push(Diagnostic::warning(message, &impl_span(context_map, &impl_name)));
+  }
+
+  diagnostics
+}
+
+fn impl_span(context_map: &ContextMap, name: &str) -> Span {
+  context_map.implementations.iter().find_map(|implementation| match implementation {
+    Implementation::PublishHttpApi(api) if api.name == name => Some(api.span.clone()),
+    _ => None,
+  }).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+  use fkl_mir::Span;
+
+  use crate::parse_type_expr;
+  use crate::semantic::{check, Severity, SymbolKind, Symbols};
+
+  #[test]
+  fn duplicate_declarations() {
+    let mut symbols = Symbols::default();
+    symbols.declare(SymbolKind::Entity, "Order", Span::new(0, 5, 1, 1));
+    symbols.declare(SymbolKind::Entity, "Order", Span::new(10, 15, 3, 1));
+    symbols.declare(SymbolKind::Aggregate, "Order", Span::new(20, 25, 5, 1));
+
+    let diagnostics = check(&symbols);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+    assert_eq!(diagnostics[0].message, "duplicate entity `Order`, first declared at line 1");
+    assert_eq!(diagnostics[0].span.line, 3);
+  }
+
+  #[test]
+  fn field_types() {
+    let mut symbols = Symbols::default();
+    symbols.declare(SymbolKind::Struct, "Address", Span::default());
+    symbols.refer_type(&parse_type_expr("List<Map<string, Address>>").unwrap(), Span::default());
+    symbols.refer_type(&parse_type_expr("Money?").unwrap(), Span::default());
+
+    let diagnostics = check(&symbols);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+    assert!(diagnostics[0].message.starts_with("unknown type `Money`"));
+  }
+}
@@ -1,7 +1,7 @@
 #[cfg(test)]
 mod test {
   use fkl_mir;
-  use fkl_mir::{Aggregate, BoundedContext, ContextRelation, ContextState, Entity};
+  use fkl_mir::{Aggregate, BoundedContext, ContextRelation, ContextState, Entity, TypeExpr, ValueObject};
   use fkl_mir::ConnectionDirection::PositiveDirected;
   use fkl_mir::tactic::block::Field;
   use crate::parse;
@@ -98,7 +98,8 @@ ValueObject Price { }
 ValueObject Notifications { }
 "#;
 
-    let decls = parse(booking_ticket).unwrap();
+    let mut decls = parse(booking_ticket).unwrap();
+    decls.strip_spans();
     assert_eq!(decls, fkl_mir::ContextMap {
       name: "TicketBooking".to_string(),
       state: ContextState::ToBe,
@@ -113,14 +114,16 @@ ValueObject Notifications { }
                 Entity {
                   name: "Cinema".to_string(),
                   description: "".to_string(),
-                  identify: Field { name: "".to_string(), initializer: None, type_type: "".to_string() },
+                  identify: Field { name: "".to_string(), initializer: None, type_type: "".to_string(), ..Default::default() },
                   fields: vec![],
+                  ..Default::default()
                 },
                 Entity {
                   name: "ScreeningRoom".to_string(),
                   description: "".to_string(),
-                  identify: Field { name: "".to_string(), initializer: None, type_type: "".to_string() },
+                  identify: Field { name: "".to_string(), initializer: None, type_type: "".to_string(), ..Default::default() },
                   fields: vec![],
+                  ..Default::default()
                 },
                 Entity {
                   name: "Seat".to_string(),
@@ -129,12 +132,16 @@ ValueObject Notifications { }
                     name: "".to_string(),
                     initializer: None,
                     type_type: "".to_string(),
+                    ..Default::default()
                   },
                   fields: vec![],
+                  ..Default::default()
                 },
               ],
+              ..Default::default()
             }
           ],
+          ..Default::default()
         },
         BoundedContext {
           name: "Movie".to_string(),
@@ -150,8 +157,10 @@ ValueObject Notifications { }
                     name: "".to_string(),
                     initializer: None,
                     type_type: "".to_string(),
+                    ..Default::default()
                   },
                   fields: vec![],
+                  ..Default::default()
                 },
                 Entity {
                   name: "Actor".to_string(),
@@ -160,8 +169,10 @@ ValueObject Notifications { }
                     name: "".to_string(),
                     initializer: None,
                     type_type: "".to_string(),
+                    ..Default::default()
                   },
                   fields: vec![],
+                  ..Default::default()
                 },
                 Entity {
                   name: "Publisher".to_string(),
@@ -170,12 +181,16 @@ ValueObject Notifications { }
                     name: "".to_string(),
                     initializer: None,
                     type_type: "".to_string(),
+                    ..Default::default()
                   },
                   fields: vec![],
+                  ..Default::default()
                 },
               ],
+              ..Default::default()
             }
           ],
+          ..Default::default()
         },
         BoundedContext {
           name: "Reservation".to_string(),
@@ -191,8 +206,10 @@ ValueObject Notifications { }
                     name: "".to_string(),
                     initializer: None,
                     type_type: "".to_string(),
+                    ..Default::default()
                   },
                   fields: vec![],
+                  ..Default::default()
                 },
                 Entity {
                   name: "Reservation".to_string(),
@@ -201,24 +218,28 @@ ValueObject Notifications { }
                     name: "".to_string(),
                     initializer: None,
                     type_type: "".to_string(),
+                    ..Default::default()
                   },
                   fields: vec![
-                    Field { name: "id".to_string(), initializer: None, type_type: "String".to_string() },
-                    Field { name: "token".to_string(), initializer: None, type_type: "UUID".to_string() },
-                    Field { name: "status".to_string(), initializer: Some("ReservationStatus.OPEN".to_string()), type_type: "ReservationStatus".to_string() },
-                    Field { name: "expiresAt".to_string(), initializer: None, type_type: "LocalDateTime".to_string() },
-                    Field { name: "createdAt".to_string(), initializer: None, type_type: "LocalDateTime".to_string() },
-                    Field { name: "screeningId".to_string(), initializer: None, type_type: "String".to_string() },
-                    Field { name: "screeningStartTime".to_string(), initializer: None, type_type: "LocalDateTime".to_string() },
-                    Field { name: "name".to_string(), initializer: None, type_type: "String".to_string() },
-                    Field { name: "surname".to_string(), initializer: None, type_type: "String".to_string() },
-                    Field { name: "tickets".to_string(), initializer: None, type_type: "Set<Ticket>".to_string() },
-                    Field { name: "totalPrice".to_string(), initializer: None, type_type: "BigDecimal".to_string() },
+                    Field { name: "id".to_string(), initializer: None, type_type: "String".to_string(), type_expr: TypeExpr::Named("String".to_string()), ..Default::default() },
+                    Field { name: "token".to_string(), initializer: None, type_type: "UUID".to_string(), type_expr: TypeExpr::Named("UUID".to_string()), ..Default::default() },
+                    Field { name: "status".to_string(), initializer: Some("ReservationStatus.OPEN".to_string()), type_type: "ReservationStatus".to_string(), type_expr: TypeExpr::Named("ReservationStatus".to_string()), ..Default::default() },
+                    Field { name: "expiresAt".to_string(), initializer: None, type_type: "LocalDateTime".to_string(), type_expr: TypeExpr::Named("LocalDateTime".to_string()), ..Default::default() },
+                    Field { name: "createdAt".to_string(), initializer: None, type_type: "LocalDateTime".to_string(), type_expr: TypeExpr::Named("LocalDateTime".to_string()), ..Default::default() },
+                    Field { name: "screeningId".to_string(), initializer: None, type_type: "String".to_string(), type_expr: TypeExpr::Named("String".to_string()), ..Default::default() },
+                    Field { name: "screeningStartTime".to_string(), initializer: None, type_type: "LocalDateTime".to_string(), type_expr: TypeExpr::Named("LocalDateTime".to_string()), ..Default::default() },
+                    Field { name: "name".to_string(), initializer: None, type_type: "String".to_string(), type_expr: TypeExpr::Named("String".to_string()), ..Default::default() },
+                    Field { name: "surname".to_string(), initializer: None, type_type: "String".to_string(), type_expr: TypeExpr::Named("String".to_string()), ..Default::default() },
+                    Field { name: "tickets".to_string(), initializer: None, type_type: "Set<Ticket>".to_string(), type_expr: TypeExpr::Generic("Set".to_string(), vec![TypeExpr::named("Ticket")]), ..Default::default() },
+                    Field { name: "totalPrice".to_string(), initializer: None, type_type: "BigDecimal".to_string(), type_expr: TypeExpr::Named("BigDecimal".to_string()), ..Default::default() },
                   ],
+                  ..Default::default()
                 },
               ],
+              ..Default::default()
             }
           ],
+          ..Default::default()
         },
         BoundedContext {
           name: "User".to_string(),
@@ -234,19 +255,23 @@ ValueObject Notifications { }
                     name: "".to_string(),
                     initializer: None,
                     type_type: "".to_string(),
+                    ..Default::default()
                   },
                   fields: vec![
-                    Field { name: "id".to_string(), initializer: None, type_type: "UUID".to_string() },
-                    Field { name: "mobile".to_string(), initializer: None, type_type: "String".to_string() },
-                    Field { name: "email".to_string(), initializer: None, type_type: "String".to_string() },
-                    Field { name: "username".to_string(), initializer: None, type_type: "String".to_string() },
-                    Field { name: "password".to_string(), initializer: None, type_type: "String".to_string() },
-                    Field { name: "address".to_string(), initializer: None, type_type: "String".to_string() },
+                    Field { name: "id".to_string(), initializer: None, type_type: "UUID".to_string(), type_expr: TypeExpr::Named("UUID".to_string()), ..Default::default() },
+                    Field { name: "mobile".to_string(), initializer: None, type_type: "String".to_string(), type_expr: TypeExpr::Named("String".to_string()), ..Default::default() },
+                    Field { name: "email".to_string(), initializer: None, type_type: "String".to_string(), type_expr: TypeExpr::Named("String".to_string()), ..Default::default() },
+                    Field { name: "username".to_string(), initializer: None, type_type: "String".to_string(), type_expr: TypeExpr::Named("String".to_string()), ..Default::default() },
+                    Field { name: "password".to_string(), initializer: None, type_type: "String".to_string(), type_expr: TypeExpr::Named("String".to_string()), ..Default::default() },
+                    Field { name: "address".to_string(), initializer: None, type_type: "String".to_string(), type_expr: TypeExpr::Named("String".to_string()), ..Default::default() },
                   ],
+                  ..Default::default()
                 }
               ],
+              ..Default::default()
             }
           ],
+          ..Default::default()
         }],
       relations: vec![
         ContextRelation {
@@ -255,6 +280,7 @@ ValueObject Notifications { }
           connection_type: PositiveDirected,
           source_type: vec![],
           target_type: vec![],
+          ..Default::default()
         },
         ContextRelation {
           source: "Reservation".to_string(),
@@ -262,6 +288,7 @@ ValueObject Notifications { }
           connection_type: PositiveDirected,
           source_type: vec![],
           target_type: vec![],
+          ..Default::default()
         },
         ContextRelation {
           source: "Reservation".to_string(),
@@ -269,12 +296,24 @@ ValueObject Notifications { }
           connection_type: PositiveDirected,
           source_type: vec![],
           target_type: vec![],
+          ..Default::default()
         }],
       implementations: vec![],
       layered: None,
       source_sets: None,
       envs: vec![],
-      structs: Default::default()
+      structs: Default::default(),
+      value_objects: vec![
+        ValueObject::new("Price"),
+        ValueObject::new("Notifications"),
+      ],
+      components: vec![],
+      domain_events: vec![],
+      domains: vec![],
+      services: vec![],
+      enums: vec![],
+      commands: vec![],
+      sagas: vec![],
     });
   }
 }
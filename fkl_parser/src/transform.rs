@@ -1,21 +1,26 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
 use indexmap::IndexMap;
 
-use fkl_mir::{BoundedContext, ConnectionDirection, ContextRelation, ContextRelationType, Datasource, Entity, Field, Flow, HttpMethod, Layer, LayeredArchitecture, LayerRelation, MethodCall, MySqlDatasource, PostgresDatasource, Step, ValueObject};
+use fkl_mir::{BoundedContext, ConnectionDirection, ContextRelation, ContextRelationType, Datasource, Entity, Field, Flow, HttpMethod, Layer, LayeredArchitecture, LayerRelation, MethodCall, MySqlDatasource, PostgresDatasource, Resolution, Step, Symbol, SymbolTable, SymbolType, ValueObject};
 use fkl_mir as mir;
 use fkl_mir::authorization::HttpAuthorization;
-use fkl_mir::implementation::{HttpEndpoint, Implementation, Request, Response};
+use fkl_mir::implementation::{CompareOperator, CompareValidation, HttpEndpoint, Implementation, LengthValidation, RangeValidation, RegexValidation, Request, Response, ResponseVariant, Validation};
 use fkl_mir::implementation::http_api_impl::HttpApiImpl;
 use fkl_mir::tactic::aggregate::Aggregate;
 
-use crate::{ContextMap, ParseError};
-use crate::parser::{ast, parse as ast_parse};
-use crate::parser::ast::{AggregateDecl, BoundedContextDecl, CustomDecl, DatasourceDecl, EndpointDecl, EntityDecl, EnvDecl, FklDeclaration, FlowDecl, ImplementationDecl, ImplementationTargetType, LayeredDecl, MethodCallDecl, RelationDirection, ServerDecl, SourceSetsDecl, StepDecl, VariableDefinition};
+use crate::{ContextMap, ParseError, SyntaxError};
+use crate::parser::{ast, parse as ast_parse, parse_with_recovery};
+use crate::resolve::include_graph::IncludeGraph;
+use crate::semantic::{self, Diagnostic, SymbolKind, Symbols};
+use crate::parser::ast::{AggregateDecl, AttributeDefinition, BoundedContextDecl, CommandDecl, ComponentDecl, ConstraintDecl, CustomDecl, DatasourceDecl, DomainDecl, DomainEventDecl, EndpointDecl, EntityDecl, EnumDecl, EnvDecl, FklDeclaration, FlowDecl, ImplementationDecl, ImplementationTarget, ImplementationTargetType, LayeredDecl, Loc, MethodCallDecl, ModuleDecl, RelationDirection, SagaDecl, ServerDecl, ServiceDecl, SourceSetsDecl, StepDecl, TransitionDecl, UsedDomainObject, UsedDomainObjectType, ValueObjectDecl, VariableDefinition};
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MirTransform {
   pub context_map_name: String,
+  /// the declared state of context map
+  pub context_map_state: Option<mir::ContextState>,
   pub contexts: IndexMap<String, BoundedContext>,
   pub relations: Vec<ContextRelation>,
   pub aggregates: HashMap<String, Aggregate>,
@@ -26,13 +31,90 @@ pub struct MirTransform {
   pub source_sets: Option<fkl_mir::SourceSets>,
   pub envs: Vec<fkl_mir::Environment>,
   pub structs: HashMap<String, fkl_mir::Struct>,
+  pub components: Vec<fkl_mir::Component>,
+  pub domain_events: IndexMap<String, mir::DomainEvent>,
+  pub domains: IndexMap<String, mir::Domain>,
+  pub services: IndexMap<String, mir::Service>,
+  pub enums: IndexMap<String, mir::Enum>,
+  pub commands: IndexMap<String, mir::Command>,
+  pub sagas: Vec<mir::Saga>,
+  /// the top-level modules with the name of context they are attached to
+  pub modules: IndexMap<String, (String, mir::Module)>,
+  /// the declarations and references for semantic checking
+  pub symbols: Symbols,
+  /// the problems found in lowering, like the unreachable states of entities
+  pub diagnostics: Vec<Diagnostic>,
+  /// byte offsets of each line start, for convert [`ast::Loc`] to [`mir::Span`]
+  pub line_starts: Vec<usize>,
+  /// the file of current lowering declarations
+  pub file: Option<String>,
 }
 
 impl MirTransform {
   // todo: refactor to symbol table
   pub fn mir(str: &str) -> Result<ContextMap, ParseError> {
-    let mut transform = MirTransform {
+    match ast_parse(str) {
+      Ok(decls) => Ok(MirTransform::lower(str, decls)),
+      Err(e) => Err(e),
+    }
+  }
+
+  /// lower the partial declarations from the recovering parser, and keep all syntax errors
+  pub fn mir_with_recovery(str: &str) -> (ContextMap, Vec<SyntaxError>) {
+    let recovered = parse_with_recovery(str);
+    (MirTransform::lower(str, recovered.decls), recovered.errors)
+  }
+
+  /// lower the code, and check the undefined references and duplicate declarations
+  pub fn mir_with_diagnostics(str: &str) -> Result<(ContextMap, Vec<Diagnostic>), ParseError> {
+    let decls = ast_parse(str)?;
+    let mut transform = MirTransform::new();
+    transform.set_source(None, str);
+    transform.lower_decls(decls);
+    Ok(transform.finish_with_diagnostics())
+  }
+
+  /// compile the file and all included files into one ContextMap
+  pub fn mir_from_file(path: &Path) -> Result<ContextMap, ParseError> {
+    Ok(MirTransform::lower_graphs(&[path.to_path_buf()])?.finish())
+  }
+
+  /// compile the file and all included files, and check them as a whole
+  pub fn mir_from_file_with_diagnostics(path: &Path) -> Result<(ContextMap, Vec<Diagnostic>), ParseError> {
+    Ok(MirTransform::lower_graphs(&[path.to_path_buf()])?.finish_with_diagnostics())
+  }
+
+  /// compile the files in order into one ContextMap, the file included by many of them is lowered once
+  pub fn mir_from_files(paths: &[PathBuf]) -> Result<ContextMap, ParseError> {
+    Ok(MirTransform::lower_graphs(paths)?.finish())
+  }
+
+  pub fn mir_from_files_with_diagnostics(paths: &[PathBuf]) -> Result<(ContextMap, Vec<Diagnostic>), ParseError> {
+    Ok(MirTransform::lower_graphs(paths)?.finish_with_diagnostics())
+  }
+
+  fn lower_graphs(paths: &[PathBuf]) -> Result<MirTransform, ParseError> {
+    let mut transform = MirTransform::new();
+    let mut lowered: HashSet<PathBuf> = HashSet::new();
+    for path in paths {
+      let graph = IncludeGraph::build(path)?;
+      transform.lower_file(&graph, &graph.entry, &mut lowered);
+    }
+
+    Ok(transform)
+  }
+
+  fn lower(str: &str, decls: Vec<FklDeclaration>) -> ContextMap {
+    let mut transform = MirTransform::new();
+    transform.set_source(None, str);
+    transform.lower_decls(decls);
+    transform.finish()
+  }
+
+  pub(crate) fn new() -> Self {
+    MirTransform {
       context_map_name: "".to_string(),
+      context_map_state: None,
       contexts: Default::default(),
       aggregates: Default::default(),
       relations: vec![],
@@ -42,38 +124,200 @@ impl MirTransform {
       layered: Default::default(),
       source_sets: None,
       envs: vec![],
-      structs: Default::default()
-    };
+      structs: Default::default(),
+      components: vec![],
+      domain_events: Default::default(),
+      domains: Default::default(),
+      services: Default::default(),
+      enums: Default::default(),
+      commands: Default::default(),
+      sagas: vec![],
+      modules: Default::default(),
+      symbols: Default::default(),
+      diagnostics: vec![],
+      line_starts: vec![0],
+      file: None,
+    }
+  }
 
-    match ast_parse(str) {
-      Ok(decls) => {
-        transform.lower_decls(decls);
+  fn set_source(&mut self, file: Option<String>, code: &str) {
+    self.file = file;
+    self.line_starts = line_starts(code);
+  }
+
+  /// lower the declaration without the others, the result can be merged by [`MirTransform::merge`]
+  pub(crate) fn lower_alone(file: Option<String>, line_starts: Vec<usize>, declaration: &FklDeclaration) -> MirTransform {
+    let mut transform = MirTransform::new();
+    transform.file = file;
+    transform.line_starts = line_starts;
+    transform.lower_decl(declaration);
+    transform
+  }
+
+  /// merge the declaration lowered by [`MirTransform::lower_alone`], in the same way as it's
+  /// lowered after the merged ones.
+  pub(crate) fn merge(&mut self, declaration: &FklDeclaration, lowered: &MirTransform) {
+    if !lowered.context_map_name.is_empty() {
+      self.context_map_name = lowered.context_map_name.clone();
+    }
+    if lowered.context_map_state.is_some() {
+      self.context_map_state = lowered.context_map_state.clone();
+    }
+
+    for (name, context) in &lowered.contexts {
+      match declaration {
+        FklDeclaration::ContextMap(context_map) => {
+          let from_relation = context_map.contexts.iter().any(|decl| &decl.name == name && decl.from_relation);
+          self.insert_mapped_context(context.clone(), from_relation);
+        }
+        _ => {
+          self.contexts.insert(name.clone(), context.clone());
+        }
       }
-      Err(e) => return Err(e),
-    };
+    }
+
+    for (name, domain) in &lowered.domains {
+      match self.domains.get_mut(name) {
+        Some(exist) => {
+          for context in &domain.contexts {
+            if !exist.contexts.contains(context) {
+              exist.contexts.push(context.clone());
+            }
+          }
+        }
+        None => {
+          self.domains.insert(name.clone(), domain.clone());
+        }
+      }
+    }
+
+    for (name, event) in &lowered.domain_events {
+      let is_declared = !event.fields.is_empty() || !event.description.is_empty();
+      if is_declared || !self.domain_events.contains_key(name) {
+        self.domain_events.insert(name.clone(), event.clone());
+      }
+    }
+
+    self.relations.extend(lowered.relations.iter().cloned());
+    self.aggregates.extend(lowered.aggregates.clone());
+    self.entities.extend(lowered.entities.clone());
+    self.value_objects.extend(lowered.value_objects.clone());
+    self.implementations.extend(lowered.implementations.iter().cloned());
+    self.envs.extend(lowered.envs.iter().cloned());
+    self.structs.extend(lowered.structs.clone());
+    self.components.extend(lowered.components.iter().cloned());
+    self.services.extend(lowered.services.clone());
+    self.enums.extend(lowered.enums.clone());
+    self.commands.extend(lowered.commands.clone());
+    self.sagas.extend(lowered.sagas.iter().cloned());
+    self.modules.extend(lowered.modules.clone());
+    if lowered.layered.is_some() {
+      self.layered = lowered.layered.clone();
+    }
+    if lowered.source_sets.is_some() {
+      self.source_sets = lowered.source_sets.clone();
+    }
+
+    self.symbols.declarations.extend(lowered.symbols.declarations.iter().cloned());
+    self.symbols.references.extend(lowered.symbols.references.iter().cloned());
+    self.symbols.targets.extend(lowered.symbols.targets.iter().cloned());
+    self.diagnostics.extend(lowered.diagnostics.iter().cloned());
+  }
+
+  fn finish_with_diagnostics(mut self) -> (ContextMap, Vec<Diagnostic>) {
+    let symbols = std::mem::take(&mut self.symbols);
+    let mut lowered = std::mem::take(&mut self.diagnostics);
+    lowered.extend(self.check_modules());
+    let context_map = self.finish();
+    let diagnostics = check_lowered(&symbols, lowered, &context_map);
+    (context_map, diagnostics)
+  }
 
-    let contexts = transform.update_aggregates();
+  pub(crate) fn finish(mut self) -> ContextMap {
+    let mut contexts = self.update_aggregates();
+    update_qualified_names(&mut contexts);
+    self.update_event_publishers(&contexts);
+    self.update_command_targets();
 
     // todo: add custom struct
 
-    Ok(ContextMap {
-      name: transform.context_map_name,
-      state: Default::default(),
+    ContextMap {
+      name: self.context_map_name,
+      state: self.context_map_state.unwrap_or_default(),
       contexts,
-      relations: transform.relations,
-      implementations: transform.implementations.into_iter()
+      relations: self.relations,
+      implementations: self.implementations.into_iter()
         .map(|impl_| Implementation::PublishHttpApi(impl_))
         .collect(),
-      layered: transform.layered,
-      source_sets: transform.source_sets,
-      envs: transform.envs,
-      structs: transform.structs,
-    })
+      layered: self.layered,
+      source_sets: self.source_sets,
+      envs: self.envs,
+      structs: self.structs,
+      value_objects: self.value_objects.into_values().collect(),
+      components: self.components,
+      domain_events: self.domain_events.into_values().collect(),
+      domains: self.domains.into_values().collect(),
+      services: self.services.into_values().collect(),
+      enums: self.enums.into_values().collect(),
+      commands: self.commands.into_values().collect(),
+      sagas: self.sagas,
+    }
+  }
+
+  /// lower the declarations of file, the included file will be lowered at the place of `include`,
+  /// and only once.
+  fn lower_file(&mut self, graph: &IncludeGraph, path: &PathBuf, lowered: &mut HashSet<PathBuf>) {
+    if !lowered.insert(path.clone()) {
+      return;
+    }
+
+    let file = &graph.files[path];
+    let file_name = graph.display_path(path);
+    self.set_source(Some(file_name.clone()), &file.code);
+
+    let mut includes = file.includes.iter();
+    for declaration in &file.decls {
+      match declaration {
+        FklDeclaration::Include(_) => {
+          if let Some(include) = includes.next() {
+            self.lower_file(graph, include, lowered);
+            self.set_source(Some(file_name.clone()), &file.code);
+          }
+        }
+        _ => self.lower_decl(declaration),
+      }
+    }
+  }
+
+  /// the top-level module should be attached to a declared context by `context: <name>;`
+  pub(crate) fn check_modules(&self) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    for (name, (context, module)) in &self.modules {
+      let message = match context.as_str() {
+        "" => format!("module `{}` is not in any context, declare it by `context: <name>;`", name),
+        context if !self.contexts.contains_key(context) => format!("context `{}` of module `{}` is not declared", context, name),
+        _ => continue,
+      };
+      diagnostics.push(Diagnostic::error(message, &module.span));
+    }
+    diagnostics
   }
 
   fn update_aggregates(&mut self) -> Vec<BoundedContext> {
     let mut contexts = vec![];
 
+    // the module without declared context keeps its aggregates in the context of the given name, or its own name
+    for (name, (context, module)) in self.modules.iter_mut() {
+      if context.is_empty() {
+        *context = name.clone();
+      }
+      if !self.contexts.contains_key(context.as_str()) {
+        let mut bounded_context = BoundedContext::new(context);
+        bounded_context.span = module.span.clone();
+        self.contexts.insert(context.clone(), bounded_context);
+      }
+    }
+
     self.aggregates.clone().iter().for_each(|(name, aggregate)| {
       aggregate.entities.iter().for_each(|entity| {
         if let Some(exist_entity) = self.entities.get(&entity.name) {
@@ -86,178 +330,778 @@ impl MirTransform {
 
     self.contexts.values().for_each(|origin| {
       let mut context: BoundedContext = BoundedContext::new(&origin.name);
+      context.annotations = origin.annotations.clone();
+      context.span = origin.span.clone();
+
+      context.modules = origin.modules.clone();
 
-      for aggregate in origin.aggregates.clone() {
-        if let Some(agg) = self.aggregates.get(&aggregate.name) {
-          context.aggregates.push(agg.clone());
-        } else {
-          context.aggregates.push(aggregate.clone())
+      let mut origin_aggregates = origin.aggregates.clone();
+      for (_, module) in self.modules.values().filter(|(context_name, _)| context_name == &origin.name) {
+        for name in &module.aggregates {
+          if !origin_aggregates.iter().any(|it| &it.name == name) {
+            origin_aggregates.push(Aggregate::new(name));
+          }
+        }
+        if !context.modules.iter().any(|it| it.name == module.name) {
+          context.modules.push(module.clone());
         }
       }
 
+      for aggregate in origin_aggregates {
+        let mut aggregate = match self.aggregates.get(&aggregate.name) {
+          Some(agg) => agg.clone(),
+          None => aggregate,
+        };
+        self.embed_value_objects(&mut aggregate);
+        context.aggregates.push(aggregate);
+      }
+
       contexts.push(context);
     });
 
     contexts
   }
 
-  fn lower_decls(&mut self, decls: Vec<FklDeclaration>) {
-    decls.iter().for_each(|declaration| {
-      match declaration {
-        FklDeclaration::None => {}
-        FklDeclaration::ContextMap(context_map) => {
-          self.context_map_name = context_map.name.name.clone();
+  /// fill the publishers of event catalog by the aggregates which emit them, the aggregates which are
+  /// not in any context are also publishers.
+  fn update_event_publishers(&mut self, contexts: &[BoundedContext]) {
+    let mut orphans: Vec<&Aggregate> = self.aggregates.values()
+      .filter(|aggregate| !contexts.iter().any(|context| context.aggregates.iter().any(|it| it.name == aggregate.name)))
+      .collect();
+    orphans.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let aggregates: Vec<Aggregate> = contexts.iter()
+      .flat_map(|context| context.aggregates.iter())
+      .chain(orphans)
+      .cloned()
+      .collect();
+
+    let commands: Vec<(String, String)> = self.commands.values()
+      .filter(|command| !command.aggregate.is_empty())
+      .flat_map(|command| command.events.iter().map(|event| (command.aggregate.clone(), event.clone())))
+      .collect();
+
+    let emitted = aggregates.iter()
+      .flat_map(|aggregate| aggregate.domain_events.iter().map(|event| (aggregate.name.clone(), event.clone())))
+      .chain(commands);
+    for (publisher, event_name) in emitted {
+      let event = self.domain_events.entry(event_name.clone())
+        .or_insert_with(|| mir::DomainEvent::new(&event_name));
+      if !event.publishers.contains(&publisher) {
+        event.publishers.push(publisher);
+      }
+    }
+  }
+
+  /// the implementations without target object are targeted to the aggregate of their command
+  fn update_command_targets(&mut self) {
+    for api in self.implementations.iter_mut() {
+      if !api.target().is_empty() {
+        continue;
+      }
 
-          context_map.contexts.iter().for_each(|context_decl| {
-            let bounded_context = self.transform_bounded_context(&context_decl);
+      if let Some(command) = self.commands.get(&api.command) {
+        api.target_aggregate = command.aggregate.clone();
+      }
+    }
+  }
+
+  /// add the domain events to the event catalog, the declared one will replace the used one.
+  fn register_events(&mut self, decls: &[DomainEventDecl]) {
+    for decl in decls {
+      let event = self.transform_domain_event(decl);
+      let is_declared = !decl.fields.is_empty() || !decl.inline_doc.is_empty();
+      if is_declared || !self.domain_events.contains_key(&decl.name) {
+        self.domain_events.insert(decl.name.clone(), event);
+      }
+    }
+  }
 
-            self.contexts.insert(bounded_context.name.clone(), bounded_context);
-          });
+  /// record the declarations and references before they are merged by name
+  fn record_symbols(&mut self, declaration: &FklDeclaration) {
+    match declaration {
+      FklDeclaration::ContextMap(context_map) => {
+        for context in &context_map.contexts {
+          if !context.from_relation {
+            let span = self.span(&context.loc);
+            self.symbols.declare(SymbolKind::Context, &context.name, span);
+          }
+          self.record_context(context);
+        }
 
-          self.relations = context_map.relations.iter().map(|relation| Self::transform_relation(&relation)).collect();
+        for relation in &context_map.relations {
+          let span = self.span(&relation.loc);
+          self.symbols.refer(SymbolKind::Context, &relation.source, span.clone());
+          self.symbols.refer(SymbolKind::Context, &relation.target, span);
+        }
+      }
+      FklDeclaration::Domain(decl) => {
+        for context in &decl.contexts {
+          let span = self.span(&context.loc);
+          self.symbols.refer(SymbolKind::Context, &context.name, span);
+        }
+      }
+      FklDeclaration::BoundedContext(decl) => {
+        let span = self.span(&decl.loc);
+        self.symbols.declare(SymbolKind::Context, &decl.name, span);
+        self.record_context(decl);
+      }
+      FklDeclaration::Module(decl) => self.record_module(decl),
+      FklDeclaration::Aggregate(decl) => self.record_aggregate(decl),
+      FklDeclaration::Entity(decl) => self.record_entity(decl),
+      FklDeclaration::ValueObject(decl) => self.record_value_object(decl),
+      FklDeclaration::DomainEvent(decl) => self.record_fields(&decl.fields),
+      FklDeclaration::Command(decl) => self.record_command(decl),
+      FklDeclaration::Saga(decl) => {
+        if !decl.context.is_empty() {
+          let span = self.span(&decl.loc);
+          self.symbols.refer(SymbolKind::Context, &decl.context, span);
         }
-        FklDeclaration::BoundedContext(decl) => {
-          let context = self.transform_bounded_context(&decl);
-          self.contexts.insert(decl.name.clone(), context);
+        for step in decl.steps.iter().filter(|step| !step.context.is_empty()) {
+          let span = self.span(&step.loc);
+          self.symbols.refer(SymbolKind::Context, &step.context, span);
+        }
+      }
+      FklDeclaration::Struct(decl) => {
+        let span = self.span(&decl.loc);
+        self.symbols.declare(SymbolKind::Struct, &decl.name, span);
+        self.record_fields(&decl.fields);
+      }
+      FklDeclaration::Enum(decl) => {
+        let span = self.span(&decl.loc);
+        self.symbols.declare(SymbolKind::Enum, &decl.name, span);
+      }
+      FklDeclaration::Implementation(decl) => {
+        if let Some(target) = &decl.target {
+          self.record_target(target);
         }
-        FklDeclaration::Aggregate(decl) => {
-          let aggregate = self.transform_aggregate(&decl);
-          self.aggregates.insert(decl.name.clone(), aggregate);
+        if let Some(command) = &decl.command {
+          self.record_target(command);
         }
-        FklDeclaration::Entity(decl) => {
-          let entity = self.transform_entity(&decl);
-          self.entities.insert(decl.name.clone(), entity);
+        self.record_fields(&decl.endpoint.path_params);
+        self.record_fields(&decl.endpoint.query_params);
+        self.record_fields(&decl.endpoint.headers);
+        for variant in &decl.endpoint.responses {
+          let span = self.span(&variant.loc);
+          self.symbols.refer_type(&variant.type_expr, span);
         }
-        FklDeclaration::ValueObject(_) => {}
-        FklDeclaration::Component(_) => {}
-        FklDeclaration::Implementation(implementation) => {
-          let api_impl = self.transform_implementation(implementation);
-          self.implementations.push(api_impl);
+      }
+      _ => {}
+    }
+  }
+
+  fn record_target(&mut self, target: &ImplementationTarget) {
+    let kind = match target.target_type {
+      ImplementationTargetType::Aggregate => SymbolKind::Aggregate,
+      ImplementationTargetType::Entity => SymbolKind::Entity,
+      ImplementationTargetType::ValueObject => SymbolKind::ValueObject,
+      ImplementationTargetType::Command => SymbolKind::Command,
+      ImplementationTargetType::Query => SymbolKind::Query,
+      ImplementationTargetType::None => return,
+    };
+    let span = self.span(&target.loc);
+    self.symbols.target(kind, &target.name, span);
+  }
+
+  fn record_command(&mut self, decl: &CommandDecl) {
+    let kind = match mir::CommandType::from(&decl.command_type) {
+      mir::CommandType::Command => SymbolKind::Command,
+      mir::CommandType::Query => SymbolKind::Query,
+    };
+    let span = self.span(&decl.loc);
+    self.symbols.declare(kind, &decl.name, span);
+    self.record_fields(&decl.fields);
+    if let Some(aggregate) = &decl.aggregate {
+      if aggregate.loc != Loc::default() {
+        let span = self.span(&aggregate.loc);
+        self.symbols.refer(SymbolKind::Aggregate, &aggregate.name, span);
+      }
+    }
+  }
+
+  fn record_context(&mut self, decl: &BoundedContextDecl) {
+    self.symbols.enter(&decl.name);
+    self.record_used(&decl.used_domain_objects);
+    decl.aggregates.iter().for_each(|aggregate| self.record_aggregate(aggregate));
+    decl.modules.iter().for_each(|module| self.record_module(module));
+    self.symbols.exit();
+  }
+
+  fn record_module(&mut self, decl: &ModuleDecl) {
+    self.record_used(&decl.used_domain_objects);
+    decl.aggregates.iter().for_each(|aggregate| self.record_aggregate(aggregate));
+  }
+
+  fn record_aggregate(&mut self, decl: &AggregateDecl) {
+    let span = self.span(&decl.loc);
+    self.symbols.declare(SymbolKind::Aggregate, &decl.name, span);
+    self.symbols.enter(&decl.name);
+    self.record_used(&decl.used_domain_objects);
+    decl.entities.iter().for_each(|entity| self.record_entity(entity));
+    decl.value_objects.iter().for_each(|vo| self.record_value_object(vo));
+    decl.domain_events.iter().for_each(|event| self.record_fields(&event.fields));
+    decl.commands.iter().for_each(|command| self.record_command(command));
+    for enum_ in &decl.enums {
+      let span = self.span(&enum_.loc);
+      self.symbols.declare(SymbolKind::Enum, &enum_.name, span);
+    }
+    self.symbols.exit();
+  }
+
+  fn record_entity(&mut self, decl: &EntityDecl) {
+    let span = self.span(&decl.loc);
+    self.symbols.declare(SymbolKind::Entity, &decl.name, span);
+    self.symbols.enter(&decl.name);
+    self.record_fields(std::slice::from_ref(&decl.identify));
+    self.record_fields(&decl.fields);
+    decl.value_objects.iter().for_each(|vo| self.record_value_object(vo));
+    self.symbols.exit();
+    if let Some(states) = self.transform_states(&decl.transitions) {
+      self.diagnostics.extend(semantic::check_states(&decl.name, &states));
+    }
+  }
+
+  fn record_value_object(&mut self, decl: &ValueObjectDecl) {
+    let span = self.span(&decl.loc);
+    self.symbols.declare(SymbolKind::ValueObject, &decl.name, span);
+    self.record_fields(&decl.fields);
+    self.record_fields(&decl.constructor);
+  }
+
+  fn record_fields(&mut self, fields: &[VariableDefinition]) {
+    for field in fields {
+      let span = self.span(&field.loc);
+      self.symbols.refer_type(&field.type_expr, span);
+      for constraint in &field.constraints {
+        if let Err(message) = transform_validation(constraint, &field.name) {
+          let span = self.span(&constraint.loc);
+          self.diagnostics.push(Diagnostic::error(message, &span));
         }
-        FklDeclaration::Struct(decl) => {
-          let fields: Vec<Field> = decl.fields.iter().map(|field| Self::transform_field(field)).collect();
-          self.structs.insert(decl.name.clone(), fkl_mir::Struct {
-            name: decl.name.clone(),
-            fields,
-          });
+      }
+    }
+  }
+
+  fn record_used(&mut self, used: &[UsedDomainObject]) {
+    for domain_object in used {
+      let kind = match domain_object.object_type {
+        UsedDomainObjectType::Aggregate => SymbolKind::Aggregate,
+        UsedDomainObjectType::Entity => SymbolKind::Entity,
+        UsedDomainObjectType::ValueObject => SymbolKind::ValueObject,
+      };
+      let span = self.span(&domain_object.loc);
+      self.symbols.refer(kind, &domain_object.name, span);
+    }
+  }
+
+  /// register the events, services and enums of aggregates in context, including the ones in modules.
+  fn register_context_items(&mut self, decl: &BoundedContextDecl) {
+    let in_modules = decl.modules.iter().flat_map(|module| module.aggregates.iter());
+    for aggregate in decl.aggregates.iter().chain(in_modules) {
+      self.register_aggregate_items(aggregate);
+    }
+  }
+
+  fn register_aggregate_items(&mut self, decl: &AggregateDecl) {
+    self.register_events(&decl.domain_events);
+    self.register_services(&decl.services);
+    self.register_enums(&decl.enums, &decl.name);
+    self.register_commands(&decl.commands);
+  }
+
+  fn register_commands(&mut self, decls: &[CommandDecl]) {
+    for decl in decls {
+      self.register_events(&decl.domain_events);
+      let command = self.transform_command(decl);
+      self.commands.insert(decl.name.clone(), command);
+    }
+  }
+
+  fn register_services(&mut self, decls: &[ServiceDecl]) {
+    for decl in decls {
+      let service = self.transform_service(decl);
+      self.services.insert(decl.name.clone(), service);
+    }
+  }
+
+  fn register_enums(&mut self, decls: &[EnumDecl], owner: &str) {
+    for decl in decls {
+      let mut enum_ = mir::Enum::new(&decl.name);
+      enum_.description = decl.inline_doc.clone();
+      enum_.variants = decl.variants.iter().map(|variant| variant.name.clone()).collect();
+      enum_.owner = owner.to_string();
+      enum_.annotations = transform_annotations(&decl.annotations);
+      enum_.span = self.span(&decl.loc);
+      self.enums.insert(decl.name.clone(), enum_);
+    }
+  }
+
+  /// the used domain objects are lowered as entities, move the ones which are declared as
+  /// top-level value objects into the aggregate.
+  fn embed_value_objects(&self, aggregate: &mut Aggregate) {
+    let (value_objects, entities): (Vec<Entity>, Vec<Entity>) = aggregate.entities.drain(..)
+      .partition(|entity| !self.entities.contains_key(&entity.name) && self.value_objects.contains_key(&entity.name));
+    aggregate.entities = entities;
+
+    for used in value_objects {
+      let mut vo = self.value_objects[&used.name].clone();
+      vo.owner = aggregate.name.clone();
+      aggregate.value_objects.push(vo);
+    }
+  }
+
+  fn lower_decls(&mut self, decls: Vec<FklDeclaration>) {
+    decls.iter().for_each(|declaration| self.lower_decl(declaration));
+  }
+
+  fn lower_decl(&mut self, declaration: &FklDeclaration) {
+    self.record_symbols(declaration);
+    match declaration {
+      FklDeclaration::None => {}
+      FklDeclaration::ContextMap(context_map) => {
+        self.context_map_name = context_map.name.name.clone();
+        if !context_map.state.is_empty() {
+          self.context_map_state = Some(mir::ContextState::from(&context_map.state));
         }
-        FklDeclaration::Layered(decl) => {
-          self.layered = Some(self.transform_layered(&decl));
+
+        context_map.contexts.iter().for_each(|context_decl| {
+          self.register_context_items(context_decl);
+          let bounded_context = self.transform_bounded_context(&context_decl);
+          self.insert_mapped_context(bounded_context, context_decl.from_relation);
+        });
+
+        let relations: Vec<ContextRelation> = context_map.relations.iter().map(|relation| self.transform_relation(&relation)).collect();
+        self.relations.extend(relations);
+      }
+      FklDeclaration::Domain(decl) => {
+        let domain = self.transform_domain(&decl);
+        match self.domains.get_mut(&domain.name) {
+          // the same subdomain can be declared in many files, merge the contexts
+          Some(exist) => {
+            for context in domain.contexts {
+              if !exist.contexts.contains(&context) {
+                exist.contexts.push(context);
+              }
+            }
+          }
+          None => {
+            self.domains.insert(domain.name.clone(), domain);
+          }
         }
-        FklDeclaration::SourceSets(decl) => {
-          self.source_sets = Some(self.transform_source_sets(&decl));
+      }
+      FklDeclaration::BoundedContext(decl) => {
+        self.register_context_items(decl);
+        let context = self.transform_bounded_context(&decl);
+        self.contexts.insert(decl.name.clone(), context);
+      }
+      FklDeclaration::Module(decl) => {
+        let module = self.transform_module(decl);
+        for aggregate in &decl.aggregates {
+          self.register_aggregate_items(aggregate);
+          let aggregate = self.transform_aggregate(aggregate);
+          self.aggregates.insert(aggregate.name.clone(), aggregate);
         }
-        FklDeclaration::Include(_include) => {
-          // todo: resolve include with DAG
+        self.modules.insert(decl.name.clone(), (decl.context.clone(), module));
+      }
+      FklDeclaration::Aggregate(decl) => {
+        self.register_aggregate_items(decl);
+        let aggregate = self.transform_aggregate(&decl);
+        self.aggregates.insert(decl.name.clone(), aggregate);
+      }
+      FklDeclaration::Entity(decl) => {
+        let entity = self.transform_entity(&decl);
+        self.entities.insert(decl.name.clone(), entity);
+      }
+      FklDeclaration::ValueObject(decl) => {
+        let vo = self.transform_value_object(&decl, "");
+        self.value_objects.insert(decl.name.clone(), vo);
+      }
+      FklDeclaration::DomainEvent(decl) => {
+        self.register_events(std::slice::from_ref(decl));
+      }
+      FklDeclaration::Service(decl) => {
+        self.register_services(std::slice::from_ref(decl));
+      }
+      FklDeclaration::Command(decl) => {
+        self.register_commands(std::slice::from_ref(decl));
+      }
+      FklDeclaration::Enum(decl) => {
+        self.register_enums(std::slice::from_ref(decl), "");
+      }
+      FklDeclaration::Component(decl) => {
+        let component = self.transform_component(&decl);
+        self.components.push(component);
+      }
+      FklDeclaration::Implementation(implementation) => {
+        let api_impl = self.transform_implementation(implementation);
+        self.implementations.push(api_impl);
+      }
+      FklDeclaration::Saga(decl) => {
+        let saga = self.transform_saga(decl);
+        self.sagas.push(saga);
+      }
+      FklDeclaration::Struct(decl) => {
+        let fields: Vec<Field> = decl.fields.iter().map(|field| self.transform_field(field)).collect();
+        self.structs.insert(decl.name.clone(), fkl_mir::Struct {
+          name: decl.name.clone(),
+          fields,
+          annotations: transform_annotations(&decl.annotations),
+          span: self.span(&decl.loc),
+        });
+      }
+      FklDeclaration::Layered(decl) => {
+        self.layered = Some(self.transform_layered(&decl));
+      }
+      FklDeclaration::SourceSets(decl) => {
+        self.source_sets = Some(self.transform_source_sets(&decl));
+      }
+      FklDeclaration::Include(_include) => {
+        // includes are resolved by the include graph in [`MirTransform::mir_from_file`]
+      }
+      FklDeclaration::Env(decl) => {
+        self.envs.push(self.transform_environment(&decl));
+      }
+    }
+  }
+
+  /// insert the context of ContextMap, a context only used in relations should not override the
+  /// declared one, like in included files, and a declared one is merged into it.
+  fn insert_mapped_context(&mut self, context: BoundedContext, from_relation: bool) {
+    match self.contexts.get_mut(&context.name) {
+      Some(_) if from_relation => {}
+      Some(exist) => {
+        for aggregate in context.aggregates {
+          if !exist.aggregates.iter().any(|it| it.name == aggregate.name) {
+            exist.aggregates.push(aggregate);
+          }
         }
-        FklDeclaration::Env(decl) => {
-          self.envs.push(self.transform_environment(&decl));
+        exist.modules.extend(context.modules);
+        for annotation in context.annotations {
+          if !exist.annotations.contains(&annotation) {
+            exist.annotations.push(annotation);
+          }
         }
       }
-    });
+      None => {
+        self.contexts.insert(context.name.clone(), context);
+      }
+    }
+  }
+
+  /// convert the byte range to span with 1-based line and column
+  fn span(&self, loc: &Loc) -> mir::Span {
+    if *loc == Loc::default() {
+      return mir::Span::default();
+    }
+
+    let line = match self.line_starts.binary_search(&loc.0) {
+      Ok(index) => index,
+      Err(index) => index - 1,
+    };
+
+    let span = mir::Span::new(loc.0, loc.1, line + 1, loc.0 - self.line_starts[line] + 1);
+    match &self.file {
+      Some(file) => span.with_file(file),
+      None => span,
+    }
   }
 
-  fn transform_relation(relation: &ast::ContextRelation) -> ContextRelation {
+  fn transform_relation(&self, relation: &ast::ContextRelation) -> ContextRelation {
     mir::ContextRelation {
       source: relation.source.clone(),
       target: relation.target.clone(),
       connection_type: transform_connection(&relation.direction),
       source_type: ContextRelationType::list(&relation.source_types),
       target_type: ContextRelationType::list(&relation.target_types),
+      span: self.span(&relation.loc),
     }
   }
 
+  fn transform_domain(&self, decl: &DomainDecl) -> mir::Domain {
+    let type_attr = decl.attributes.iter().find(|attr| attr.key == "type").and_then(|attr| attr.value.first());
+    let subdomain_type = match (decl.domain_type.as_str(), type_attr) {
+      ("SubDomain", Some(value)) => mir::SubDomainType::from(value),
+      (keyword, _) => mir::SubDomainType::from(keyword),
+    };
+
+    let mut domain = mir::Domain::new(&decl.name, subdomain_type);
+    domain.description = decl.inline_doc.clone();
+    domain.contexts = decl.contexts.iter().map(|context| context.name.clone()).collect();
+    domain.span = self.span(&decl.loc);
+    domain
+  }
+
   fn transform_bounded_context(&self, context_decl: &BoundedContextDecl) -> BoundedContext {
     let mut context = mir::BoundedContext::new(&context_decl.name);
+    context.annotations = transform_annotations(&context_decl.annotations);
+    context.span = self.span(&context_decl.loc);
     context.aggregates = context_decl.used_domain_objects.iter().map(|domain_object| {
-      Aggregate::new(&domain_object.name.clone())
+      let mut aggregate = Aggregate::new(&domain_object.name.clone());
+      aggregate.span = self.span(&domain_object.loc);
+      aggregate
     }).collect();
 
     let from_inside: Vec<Aggregate> = context_decl.aggregates.iter().map(|decl| {
-      let mut entities: Vec<Entity> = decl.entities.iter().map(|entity| {
-        self.transform_entity(entity)
-      }).collect();
-
-      let used_entities: Vec<Entity> = decl.used_domain_objects.iter().map(|domain_object| {
-        Entity::new(&domain_object.name)
-      }).collect();
+      self.transform_inner_aggregate(decl)
+    }).collect();
 
-      entities.extend(used_entities);
+    context.aggregates.extend(from_inside);
 
-      Aggregate {
-        name: decl.name.clone(),
-        description: "".to_string(),
-        entities: entities,
+    for module_decl in &context_decl.modules {
+      let module = self.transform_module(module_decl);
+      for aggregate in &module_decl.aggregates {
+        context.aggregates.push(self.transform_inner_aggregate(aggregate));
+      }
+      for used in &module_decl.used_domain_objects {
+        let mut aggregate = Aggregate::new(&used.name);
+        aggregate.span = self.span(&used.loc);
+        context.aggregates.push(aggregate);
       }
+      context.modules.push(module);
+    }
+
+    context
+  }
+
+  fn transform_inner_aggregate(&self, decl: &AggregateDecl) -> Aggregate {
+    let mut entities: Vec<Entity> = decl.entities.iter().map(|entity| {
+      self.transform_entity(entity)
     }).collect();
 
-    context.aggregates.extend(from_inside);
+    let used_entities: Vec<Entity> = decl.used_domain_objects.iter().map(|domain_object| {
+      self.transform_used_entity(domain_object)
+    }).collect();
 
-    context
+    entities.extend(used_entities);
+
+    Aggregate {
+      name: decl.name.clone(),
+      description: "".to_string(),
+      entities: entities,
+      value_objects: decl.value_objects.iter().map(|vo| self.transform_value_object(vo, &decl.name)).collect(),
+      domain_events: decl.domain_events.iter().map(|event| event.name.clone()).collect(),
+      annotations: transform_annotations(&decl.annotations),
+      span: self.span(&decl.loc),
+    }
+  }
+
+  fn transform_module(&self, decl: &ModuleDecl) -> mir::Module {
+    let mut module = mir::Module::new(&decl.name);
+    module.description = decl.inline_doc.clone();
+    module.aggregates = decl.aggregates.iter().map(|aggregate| aggregate.name.clone())
+      .chain(decl.used_domain_objects.iter().map(|used| used.name.clone()))
+      .collect();
+    module.dependencies = decl.attributes.iter()
+      .filter(|attribute| attribute.key == "dependencies")
+      .flat_map(|attribute| attribute.value.clone())
+      .collect();
+    module.span = self.span(&decl.loc);
+    module
   }
 
   fn transform_aggregate(&mut self, decl: &AggregateDecl) -> mir::Aggregate {
     let mut aggregate = mir::Aggregate::new(&decl.name);
+    aggregate.annotations = transform_annotations(&decl.annotations);
+    aggregate.span = self.span(&decl.loc);
     aggregate.entities = decl.used_domain_objects.iter().map(|domain_object| {
-      Entity::new(&domain_object.name)
+      self.transform_used_entity(domain_object)
     }).collect();
 
     decl.entities.iter().for_each(|entity| {
       aggregate.entities.push(self.transform_entity(entity));
     });
 
+    aggregate.value_objects = decl.value_objects.iter().map(|vo| self.transform_value_object(vo, &decl.name)).collect();
+    aggregate.domain_events = decl.domain_events.iter().map(|event| event.name.clone()).collect();
+
     aggregate
   }
 
+  fn transform_used_entity(&self, domain_object: &UsedDomainObject) -> mir::Entity {
+    let mut entity = Entity::new(&domain_object.name);
+    entity.span = self.span(&domain_object.loc);
+    entity
+  }
+
   fn transform_entity(&self, decl: &EntityDecl) -> mir::Entity {
     Entity {
       name: decl.name.clone(),
       description: decl.inline_doc.clone(),
-      fields: decl.fields.iter().map(|field| Self::transform_field(field)).collect(),
-      identify: Self::transform_field(&decl.identify),
+      fields: decl.fields.iter().map(|field| self.transform_field(field)).collect(),
+      identify: self.transform_field(&decl.identify),
+      value_objects: decl.value_objects.iter().map(|vo| self.transform_value_object(vo, &decl.name)).collect(),
+      states: self.transform_states(&decl.transitions),
+      annotations: transform_annotations(&decl.annotations),
+      span: self.span(&decl.loc),
     }
   }
 
-  fn transform_field(field: &VariableDefinition) -> Field {
-    Field {
-      initializer: field.initializer.clone(),
-      name: field.name.clone(),
-      type_type: field.type_type.clone(),
+  fn transform_states(&self, decls: &[TransitionDecl]) -> Option<mir::StateMachine> {
+    if decls.is_empty() {
+      return None;
     }
-  }
 
-  fn transform_implementation(&mut self, implementation: &ImplementationDecl) -> HttpApiImpl {
-    let mut http_api_impl = HttpApiImpl::new(implementation.name.clone());
-    http_api_impl.endpoint = Self::transform_endpoint(&implementation.endpoint);
+    let transitions = decls.iter().map(|decl| mir::Transition {
+      from: decl.from.clone(),
+      to: decl.to.clone(),
+      trigger: decl.trigger.clone(),
+      span: self.span(&decl.loc),
+    }).collect();
 
-    http_api_impl.flow = if let Some(flow) = &implementation.flow {
-      Some(self.transform_flow(&flow))
+    Some(mir::StateMachine::new(transitions))
+  }
+
+  fn transform_value_object(&self, decl: &ValueObjectDecl, owner: &str) -> mir::ValueObject {
+    let constructor: Vec<Field> = decl.constructor.iter().map(|field| self.transform_field(field)).collect();
+    let fields = if decl.fields.is_empty() {
+      constructor.clone()
     } else {
-      None
+      decl.fields.iter().map(|field| self.transform_field(field)).collect()
     };
 
-    if let Some(target) = &implementation.target {
-      match target.target_type {
-        ImplementationTargetType::None => {}
-        ImplementationTargetType::Aggregate => {
-          http_api_impl.target_aggregate = target.name.clone();
-        }
-        ImplementationTargetType::Entity => {
-          http_api_impl.target_entity = target.name.clone()
-        }
-        ImplementationTargetType::ValueObject => {}
-      }
+    ValueObject {
+      name: decl.name.clone(),
+      description: decl.inline_doc.clone(),
+      fields,
+      constructor,
+      owner: owner.to_string(),
+      annotations: transform_annotations(&decl.annotations),
+      span: self.span(&decl.loc),
     }
+  }
 
-    http_api_impl
+  fn transform_domain_event(&self, decl: &DomainEventDecl) -> mir::DomainEvent {
+    let mut event = mir::DomainEvent::new(&decl.name);
+    event.description = decl.inline_doc.clone();
+    event.fields = decl.fields.iter().map(|field| self.transform_field(field)).collect();
+    event.annotations = transform_annotations(&decl.annotations);
+    event.span = self.span(&decl.loc);
+    event
   }
 
-  fn transform_flow(&mut self, flow_decl: &FlowDecl) -> Flow {
-    let mut flow = Flow::default();
-    flow.steps = flow_decl.steps.iter().map(|step_decl| {
-      match step_decl {
-        StepDecl::MethodCall(call) => {
-          let mut method_call = MethodCall::new(call.name.clone());
+  fn transform_command(&self, decl: &CommandDecl) -> mir::Command {
+    let mut command = mir::Command::new(&decl.name, mir::CommandType::from(&decl.command_type));
+    command.description = decl.inline_doc.clone();
+    command.aggregate = decl.aggregate.as_ref().map(|it| last_segment(&it.name)).unwrap_or_default();
+    command.fields = decl.fields.iter().map(|field| self.transform_field(field)).collect();
+    command.events = decl.domain_events.iter().map(|event| event.name.clone()).collect();
+    command.annotations = transform_annotations(&decl.annotations);
+    command.span = self.span(&decl.loc);
+    command
+  }
+
+  fn transform_service(&self, decl: &ServiceDecl) -> mir::Service {
+    let mut service = mir::Service::new(&decl.name, mir::ServiceType::from(&decl.service_type));
+    service.description = decl.inline_doc.clone();
+    service.aggregate = decl.aggregate.clone();
+    service.annotations = transform_annotations(&decl.annotations);
+    service.span = self.span(&decl.loc);
+    service.methods = decl.methods.iter().map(|method| {
+      mir::Method {
+        name: method.name.clone(),
+        parameters: method.parameters.iter().map(|param| self.transform_field(param)).collect(),
+        return_type: method.return_type.clone(),
+        span: self.span(&method.loc),
+      }
+    }).collect();
+
+    service
+  }
+
+  fn transform_field(&self, field: &VariableDefinition) -> Field {
+    Field {
+      initializer: field.initializer.clone(),
+      name: field.name.clone(),
+      type_type: field.type_type.clone(),
+      type_expr: field.type_expr.clone(),
+      validations: field.constraints.iter().filter_map(|constraint| transform_validation(constraint, &field.name).ok()).collect(),
+      annotations: transform_annotations(&field.annotations),
+      span: self.span(&field.loc),
+    }
+  }
+
+  fn transform_component(&self, decl: &ComponentDecl) -> mir::Component {
+    let mut component = mir::Component::new(&decl.name);
+    component.description = decl.inline_doc.clone();
+    component.span = self.span(&decl.loc);
+    component.attributes = decl.attributes.iter().map(|attr| {
+      mir::ComponentAttribute { key: attr.key.clone(), value: attr.value.clone() }
+    }).collect();
+
+    if let Some(component_type) = component.attribute("type") {
+      component.component_type = mir::ComponentType::from(component_type);
+    }
+
+    decl.used_domain_objects.iter().for_each(|domain_object| {
+      let name = domain_object.name.clone();
+      match domain_object.object_type {
+        UsedDomainObjectType::Aggregate => component.aggregates.push(name),
+        UsedDomainObjectType::Entity => component.entities.push(name),
+        UsedDomainObjectType::ValueObject => component.value_objects.push(name),
+      }
+    });
+
+    component
+  }
+
+  fn transform_implementation(&mut self, implementation: &ImplementationDecl) -> HttpApiImpl {
+    let mut http_api_impl = HttpApiImpl::new(implementation.name.clone());
+    http_api_impl.annotations = transform_annotations(&implementation.annotations);
+    http_api_impl.span = self.span(&implementation.loc);
+    http_api_impl.endpoint = self.transform_endpoint(&implementation.endpoint);
+    self.check_path_params(&implementation.name, &http_api_impl.endpoint, &implementation.endpoint.loc);
+    self.check_responses(&implementation.name, &implementation.endpoint);
+
+    http_api_impl.flow = if let Some(flow) = &implementation.flow {
+      Some(self.transform_flow(&flow))
+    } else {
+      None
+    };
+
+    if let Some(target) = &implementation.target {
+      match target.target_type {
+        ImplementationTargetType::None => {}
+        ImplementationTargetType::Aggregate => {
+          http_api_impl.target_aggregate = last_segment(&target.name);
+        }
+        ImplementationTargetType::Entity => {
+          http_api_impl.target_entity = last_segment(&target.name)
+        }
+        ImplementationTargetType::ValueObject
+        | ImplementationTargetType::Command
+        | ImplementationTargetType::Query => {}
+      }
+    }
+
+    if let Some(command) = &implementation.command {
+      http_api_impl.command = command.name.clone();
+    }
+
+    http_api_impl
+  }
+
+  fn transform_saga(&self, decl: &SagaDecl) -> mir::Saga {
+    let mut saga = mir::Saga::new(&decl.name);
+    saga.description = decl.inline_doc.clone();
+    saga.context = decl.context.clone();
+    saga.triggers = decl.triggers.clone();
+    saga.steps = decl.steps.iter().map(|step| mir::SagaStep {
+      name: step.name.clone(),
+      description: step.inline_doc.clone(),
+      context: step.context.clone(),
+      compensation: step.compensation.clone(),
+      span: self.span(&step.loc),
+    }).collect();
+    saga.annotations = transform_annotations(&decl.annotations);
+    saga.span = self.span(&decl.loc);
+    saga
+  }
+
+  fn transform_flow(&mut self, flow_decl: &FlowDecl) -> Flow {
+    let mut flow = Flow::default();
+    flow.steps = flow_decl.steps.iter().map(|step_decl| {
+      match step_decl {
+        StepDecl::MethodCall(call) => {
+          let mut method_call = MethodCall::new(call.name.clone());
           method_call.method = call.method.clone();
           method_call.object = call.object.clone();
           method_call.parameters = self.transform_variables(&call.arguments);
@@ -279,10 +1123,13 @@ impl MirTransform {
     flow
   }
 
-  fn transform_endpoint(endpoint_decl: &EndpointDecl) -> HttpEndpoint {
+  fn transform_endpoint(&self, endpoint_decl: &EndpointDecl) -> HttpEndpoint {
     let mut endpoint = HttpEndpoint::new(endpoint_decl.name.clone());
     endpoint.method = HttpMethod::from(&endpoint_decl.method);
     endpoint.path = endpoint_decl.uri.clone();
+    endpoint.path_params = endpoint_decl.path_params.iter().map(|param| self.transform_field(param)).collect();
+    endpoint.query_params = endpoint_decl.query_params.iter().map(|param| self.transform_field(param)).collect();
+    endpoint.headers = endpoint_decl.headers.iter().map(|param| self.transform_field(param)).collect();
     if let Some(decl) = &endpoint_decl.response {
       endpoint.response = Some(Response {
         name: decl.name.clone(),
@@ -302,10 +1149,54 @@ impl MirTransform {
       });
     }
 
+    endpoint.responses = endpoint_decl.responses.iter().map(|decl| ResponseVariant {
+      status: decl.status,
+      name: decl.type_type.clone(),
+      content_type: decl.content_type.clone(),
+    }).collect();
+    if endpoint.response.is_none() {
+      endpoint.response = endpoint.responses.iter().find(|variant| variant.is_success()).map(|variant| Response {
+        name: variant.name.clone(),
+        post_validate: None,
+      });
+    }
 
     endpoint
   }
 
+  /// the status codes of responses should be valid and unique
+  fn check_responses(&mut self, impl_name: &str, endpoint: &EndpointDecl) {
+    for (index, variant) in endpoint.responses.iter().enumerate() {
+      let span = self.span(&variant.loc);
+      if !(100..=599).contains(&variant.status) {
+        let message = format!("`{}` of impl `{}` is not a valid status code", variant.status, impl_name);
+        self.diagnostics.push(Diagnostic::error(message, &span));
+      } else if endpoint.responses[..index].iter().any(|it| it.status == variant.status) {
+        let message = format!("duplicate response of status `{}` in impl `{}`", variant.status, impl_name);
+        self.diagnostics.push(Diagnostic::error(message, &span));
+      }
+    }
+  }
+
+  /// every `{placeholder}` in path should have a declared type, and every path parameter should be in path
+  fn check_path_params(&mut self, impl_name: &str, endpoint: &HttpEndpoint, loc: &Loc) {
+    let span = self.span(loc);
+    let placeholders = endpoint.placeholders();
+    for placeholder in &placeholders {
+      if endpoint.path_param(placeholder).is_none() {
+        let message = format!("the type of path parameter `{}` in impl `{}` is not declared, like `path {{ {}: String; }}`", placeholder, impl_name, placeholder);
+        self.diagnostics.push(Diagnostic::warning(message, &span));
+      }
+    }
+
+    for param in &endpoint.path_params {
+      if !placeholders.contains(&param.name.as_str()) {
+        let message = format!("path parameter `{}` in impl `{}` is not used in `{}`", param.name, impl_name, endpoint.path);
+        self.diagnostics.push(Diagnostic::warning(message, &param.span));
+      }
+    }
+  }
+
   fn transform_return_type(&mut self, call: &&MethodCallDecl) -> Option<mir::VariableDefinition> {
     match &call.return_type {
       None => None,
@@ -451,6 +1342,106 @@ impl MirTransform {
   }
 }
 
+/// replace the used domain objects which are referred by qualified names, like `Aggregate Sales.Order;`
+/// and `Entity Sales.Order.Customer;`, with the declared ones, or the empty ones with the short name
+/// if they are not found.
+fn update_qualified_names(contexts: &mut [BoundedContext]) {
+  let mut table = SymbolTable::new();
+  contexts.iter().for_each(|context| table.add_context(context));
+
+  for aggregate in contexts.iter_mut().flat_map(|context| context.aggregates.iter_mut()) {
+    if aggregate.name.contains('.') {
+      match table.resolve(&aggregate.name, &[], |it| matches!(it.symbol_type, SymbolType::Aggregate(_))) {
+        Resolution::Found(Symbol { symbol_type: SymbolType::Aggregate(found), .. }) => *aggregate = found.clone(),
+        _ => aggregate.name = last_segment(&aggregate.name),
+      }
+    }
+
+    for entity in aggregate.entities.iter_mut().filter(|entity| entity.name.contains('.')) {
+      match table.resolve(&entity.name, &[], |it| matches!(it.symbol_type, SymbolType::Entity(_))) {
+        Resolution::Found(Symbol { symbol_type: SymbolType::Entity(found), .. }) => *entity = found.clone(),
+        _ => entity.name = last_segment(&entity.name),
+      }
+    }
+  }
+}
+
+fn transform_annotations(decls: &[AttributeDefinition]) -> Vec<mir::Annotation> {
+  decls.iter().map(|decl| mir::Annotation::new(&decl.key, decl.value.clone())).collect()
+}
+
+/// the short name of qualified name, like `Customer` for `Sales.Customer`
+fn last_segment(name: &str) -> String {
+  name.rsplit('.').next().unwrap_or_default().to_string()
+}
+
+/// the semantic diagnostics of lowered symbols, followed by the ones found in lowering and the
+/// ones need the whole ContextMap
+pub(crate) fn check_lowered(symbols: &Symbols, lowered: Vec<Diagnostic>, context_map: &ContextMap) -> Vec<Diagnostic> {
+  let mut diagnostics = semantic::check(symbols);
+  diagnostics.extend(lowered);
+  diagnostics.extend(semantic::check_ambiguities(symbols, &SymbolTable::from(context_map)));
+  diagnostics.extend(semantic::check_calls(context_map));
+  diagnostics.extend(semantic::check_enum_defaults(context_map));
+  diagnostics.extend(semantic::check_commands(context_map));
+  diagnostics.extend(semantic::check_sagas(context_map));
+  diagnostics
+}
+
+pub(crate) fn line_starts(str: &str) -> Vec<usize> {
+  let mut starts = vec![0];
+  starts.extend(str.match_indices('\n').map(|(index, _)| index + 1));
+  starts
+}
+
+/// lower the constraint to validation, or the error message of unknown constraint or invalid argument:
+/// - `@required`
+/// - `@min(n)`, `@max(n)`, `@range(min, max)`, the bounds can be negative or decimal
+/// - `@length(max)`, `@length(min, max)`
+/// - `@pattern(regex)`, `@regex(regex)`
+/// - `@compare(operator, other_field)`, like `@compare(">=", startDate)`
+fn transform_validation(constraint: &ConstraintDecl, field_name: &str) -> Result<Validation, String> {
+  let name = &constraint.name;
+  let required = |index: usize| constraint.args.get(index)
+    .ok_or_else(|| format!("`@{}` of field `{}` requires {} argument(s)", name, field_name, index + 1));
+  let number = |index: usize| match constraint.args.get(index) {
+    Some(arg) => arg.parse::<f64>().map(Some)
+      .map_err(|_| format!("argument `{}` of `@{}` should be a number", arg, name)),
+    None => Ok(None),
+  };
+  let length = |index: usize| match constraint.args.get(index) {
+    Some(arg) => arg.parse::<usize>().map(Some)
+      .map_err(|_| format!("argument `{}` of `@{}` should be a non-negative integer", arg, name)),
+    None => Ok(None),
+  };
+
+  let validation = match name.to_lowercase().as_str() {
+    "required" => Validation::Required(true),
+    "min" => Validation::Range(RangeValidation { min: number(0)?, max: None }),
+    "max" => Validation::Range(RangeValidation { min: None, max: number(0)? }),
+    "range" => Validation::Range(RangeValidation { min: number(0)?, max: number(1)? }),
+    "length" if constraint.args.len() == 1 => Validation::Length(LengthValidation { min: None, max: length(0)? }),
+    "length" => Validation::Length(LengthValidation { min: length(0)?, max: length(1)? }),
+    "pattern" | "regex" => Validation::Regex(RegexValidation { pattern: required(0)?.clone() }),
+    "compare" => {
+      let operator = match required(0)?.as_str() {
+        "==" | "eq" => CompareOperator::Equal,
+        "!=" | "ne" => CompareOperator::NotEqual,
+        ">" | "gt" => CompareOperator::GreaterThan,
+        ">=" | "ge" => CompareOperator::GreaterThanOrEqual,
+        "<" | "lt" => CompareOperator::LessThan,
+        "<=" | "le" => CompareOperator::LessThanOrEqual,
+        operator => return Err(format!("unknown compare operator `{}` of field `{}`", operator, field_name)),
+      };
+
+      Validation::Compare(CompareValidation { left: field_name.to_string(), right: required(1)?.clone(), operator })
+    }
+    _ => return Err(format!("unknown constraint `@{}` of field `{}`", name, field_name)),
+  };
+
+  Ok(validation)
+}
+
 fn transform_connection(rd: &RelationDirection) -> ConnectionDirection {
   match rd {
     RelationDirection::Undirected => ConnectionDirection::Undirected,
@@ -462,15 +1453,18 @@ fn transform_connection(rd: &RelationDirection) -> ConnectionDirection {
 
 #[cfg(test)]
 mod tests {
-  use fkl_mir::{Aggregate, BoundedContext, ContextRelation, ContextRelationType, CustomEnv, Entity, Environment, Flow, HttpMethod, Layer, LayeredArchitecture, LayerRelation, MethodCall, PostgresDatasource, ServerConfig, SourceSet, SourceSets, Step, VariableDefinition};
+  use std::path::PathBuf;
+
+  use fkl_mir::{Aggregate, Annotation, BoundedContext, Component, ComponentAttribute, ComponentType, ContextRelation, ContextRelationType, CustomEnv, Domain, Entity, Environment, Flow, HttpMethod, Layer, LayeredArchitecture, LayerRelation, MethodCall, PostgresDatasource, ServerConfig, SourceSet, SourceSets, Span, Step, SubDomainType, TypeExpr, ValueObject, VariableDefinition};
   use fkl_mir::authorization::HttpAuthorization;
   use fkl_mir::ConnectionDirection::PositiveDirected;
   use fkl_mir::Datasource::Postgres;
-  use fkl_mir::implementation::{HttpEndpoint, Implementation, Response};
+  use fkl_mir::implementation::{HttpEndpoint, Implementation, LengthValidation, RangeValidation, RegexValidation, Response, Validation};
   use fkl_mir::implementation::http_api_impl::HttpApiImpl;
   use fkl_mir::tactic::block::Field;
 
-  use crate::transform::MirTransform;
+  use crate::parser::parse;
+  use crate::transform::{line_starts, MirTransform};
 
   #[test]
   fn basic_mir() {
@@ -515,7 +1509,8 @@ ContextMap {
   ShoppingCartContext [acl] -> MallContext [acl];
 }
 "#;
-    let context_map = MirTransform::mir(str).unwrap();
+    let mut context_map = MirTransform::mir(str).unwrap();
+    context_map.strip_spans();
 
     assert_eq!(context_map.contexts.len(), 2);
     assert_eq!(context_map.relations, vec![
@@ -525,6 +1520,7 @@ ContextMap {
         connection_type: PositiveDirected,
         source_type: vec![ContextRelationType::AntiCorruptionLayer],
         target_type: vec![ContextRelationType::AntiCorruptionLayer],
+        ..Default::default()
       }]);
   }
 
@@ -549,12 +1545,14 @@ Entity Shopping {
   }
 }
 "#;
-    let context_map = MirTransform::mir(str).unwrap();
+    let mut context_map = MirTransform::mir(str).unwrap();
+    context_map.strip_spans();
 
     assert_eq!(context_map.contexts, vec![
       BoundedContext {
         name: "MallContext".to_string(),
         aggregates: vec![],
+        ..Default::default()
       },
       BoundedContext {
         name: "ShoppingCartContext".to_string(),
@@ -570,13 +1568,17 @@ Entity Shopping {
                   name: "".to_string(),
                   initializer: None,
                   type_type: "".to_string(),
+                  ..Default::default()
                 },
                 fields: vec![
-                  Field { name: "id".to_string(), initializer: None, type_type: "String".to_string() }
+                  Field { name: "id".to_string(), initializer: None, type_type: "String".to_string(), type_expr: TypeExpr::Named("String".to_string()), ..Default::default() }
                 ],
+                ..Default::default()
               }
             ],
+            ..Default::default()
           }],
+        ..Default::default()
       },
     ]);
   }
@@ -594,7 +1596,8 @@ impl CinemaCreatedEvent {
 }
 "#;
 
-    let context_map = MirTransform::mir(str).unwrap();
+    let mut context_map = MirTransform::mir(str).unwrap();
+    context_map.strip_spans();
     assert_eq!(context_map.implementations[0], Implementation::PublishHttpApi(HttpApiImpl {
       name: "CinemaCreatedEvent".to_string(),
       target_aggregate: "Cinema".to_string(),
@@ -611,8 +1614,10 @@ impl CinemaCreatedEvent {
           name: "Cinema".to_string(),
           post_validate: None,
         }),
+        ..Default::default()
       },
       flow: None,
+      ..Default::default()
     }
     ));
   }
@@ -633,7 +1638,8 @@ impl CinemaCreatedEvent {
    }
 }"#;
 
-    let context_map = MirTransform::mir(str).unwrap();
+    let mut context_map = MirTransform::mir(str).unwrap();
+    context_map.strip_spans();
     assert_eq!(context_map.implementations[0], Implementation::PublishHttpApi(HttpApiImpl {
       name: "CinemaCreatedEvent".to_string(),
       target_aggregate: "".to_string(),
@@ -650,6 +1656,7 @@ impl CinemaCreatedEvent {
           name: "Cinema".to_string(),
           post_validate: None,
         }),
+        ..Default::default()
       },
       flow: Some(Flow {
         inline_doc: "".to_string(),
@@ -681,6 +1688,7 @@ impl CinemaCreatedEvent {
             }),
           })],
       }),
+      ..Default::default()
     }
     ));
   }
@@ -874,7 +1882,8 @@ impl CinemaCreatedEvent {
   }
 }"#;
 
-    let context_map = MirTransform::mir(str).unwrap();
+    let mut context_map = MirTransform::mir(str).unwrap();
+    context_map.strip_spans();
     assert_eq!(context_map.contexts, vec![
       BoundedContext {
         name: "Movie".to_string(),
@@ -887,23 +1896,28 @@ impl CinemaCreatedEvent {
                 fields: vec![],
                 description: "".to_string(),
                 identify: Default::default(),
+                ..Default::default()
               },
               Entity {
                 name: "Actor".to_string(),
                 fields: vec![],
                 description: "".to_string(),
                 identify: Default::default(),
+                ..Default::default()
               },
               Entity {
                 name: "Publisher".to_string(),
                 fields: vec![],
                 description: "".to_string(),
                 identify: Default::default(),
+                ..Default::default()
               },
             ],
             description: "".to_string(),
+            ..Default::default()
           },
         ],
+        ..Default::default()
       },
     ]);
   }
@@ -928,7 +1942,8 @@ impl CinemaCreatedEvent {
     }
 }"#;
 
-    let context_map = MirTransform::mir(str).unwrap();
+    let mut context_map = MirTransform::mir(str).unwrap();
+    context_map.strip_spans();
     assert_eq!(context_map.contexts, vec![
       BoundedContext {
         name: "analyze".to_string(),
@@ -939,21 +1954,973 @@ impl CinemaCreatedEvent {
             Entity {
               name: "ArchSystem".to_string(),
               description: "".to_string(),
-              identify: Field { name: "".to_string(), initializer: None, type_type: "".to_string() },
+              identify: Field { name: "".to_string(), initializer: None, type_type: "".to_string(), ..Default::default() },
               fields: vec![
-                Field { name: "id".to_string(), initializer: None, type_type: "String".to_string() },
-                Field { name: "name".to_string(), initializer: None, type_type: "String".to_string() }],
+                Field { name: "id".to_string(), initializer: None, type_type: "String".to_string(), type_expr: TypeExpr::Named("String".to_string()), ..Default::default() },
+                Field { name: "name".to_string(), initializer: None, type_type: "String".to_string(), type_expr: TypeExpr::Named("String".to_string()), ..Default::default() }],
+              ..Default::default()
             },
             Entity {
               name: "ArchComponent".to_string(),
               description: "".to_string(),
-              identify: Field { name: "".to_string(), initializer: None, type_type: "".to_string() },
+              identify: Field { name: "".to_string(), initializer: None, type_type: "".to_string(), ..Default::default() },
               fields: vec![
-                Field { name: "name".to_string(), initializer: None, type_type: "String".to_string() },
-                Field { name: "type".to_string(), initializer: None, type_type: "ArchComponentType".to_string() }],
+                Field { name: "name".to_string(), initializer: None, type_type: "String".to_string(), type_expr: TypeExpr::Named("String".to_string()), ..Default::default() },
+                Field { name: "type".to_string(), initializer: None, type_type: "ArchComponentType".to_string(), type_expr: TypeExpr::Named("ArchComponentType".to_string()), ..Default::default() }],
+              ..Default::default()
             }],
+          ..Default::default()
         }],
+        ..Default::default()
+      }
+    ]);
+  }
+
+  #[test]
+  fn sub_domains() {
+    let str = r#"
+CoreDomain Ticketing {
+  """ selling the movie tickets """
+  Context Reservation, Cinema;
+}
+
+SubDomain Identity {
+  type: Generic;
+  Context User;
+}
+
+SupportingDomain Notification {
+  Context Notify;
+}
+
+Context Reservation {}
+Context Cinema {}
+Context User {}
+"#;
+
+    let mut context_map = MirTransform::mir(str).unwrap();
+    context_map.strip_spans();
+
+    assert_eq!(context_map.domains[0], Domain {
+      name: "Ticketing".to_string(),
+      description: " selling the movie tickets ".to_string(),
+      subdomain_type: SubDomainType::Core,
+      contexts: vec!["Reservation".to_string(), "Cinema".to_string()],
+      ..Default::default()
+    });
+    assert_eq!(context_map.domains[1].subdomain_type, SubDomainType::Generic);
+    assert_eq!(context_map.domains[2].subdomain_type, SubDomainType::Supporting);
+
+    assert_eq!(context_map.domain_of("User").unwrap().name, "Identity");
+    assert_eq!(context_map.contexts_of("Ticketing").len(), 2);
+    assert!(context_map.contexts_of("Notification").is_empty());
+  }
+
+  #[test]
+  fn domain_events() {
+    let str = r#"
+Context Ordering {
+  Aggregate Order {
+    DomainEvent OrderCreated {
+      struct {
+        orderId: String;
+        amount: BigDecimal;
+      }
+    }
+    DomainEvent OrderCancelled;
+  }
+  Aggregate Payment;
+}
+
+Aggregate Payment {
+  DomainEvent OrderCancelled, PaymentReceived;
+}
+
+DomainEvent PaymentReceived {
+  """ the payment is received from the bank """
+  struct {
+    paymentId: String;
+  }
+}
+"#;
+
+    let mut context_map = MirTransform::mir(str).unwrap();
+    context_map.strip_spans();
+
+    let aggregates = &context_map.contexts[0].aggregates;
+    let order = aggregates.iter().find(|aggregate| aggregate.name == "Order").unwrap();
+    assert_eq!(order.domain_events, vec!["OrderCreated".to_string(), "OrderCancelled".to_string()]);
+    let payment = aggregates.iter().find(|aggregate| aggregate.name == "Payment").unwrap();
+    assert_eq!(payment.domain_events, vec!["OrderCancelled".to_string(), "PaymentReceived".to_string()]);
+
+    let names: Vec<String> = context_map.domain_events.iter().map(|event| event.name.clone()).collect();
+    assert_eq!(names, vec!["OrderCreated", "OrderCancelled", "PaymentReceived"]);
+
+    let created = context_map.get_domain_event("OrderCreated").unwrap();
+    assert_eq!(created.fields.len(), 2);
+    assert_eq!(created.publishers, vec!["Order".to_string()]);
+
+    let cancelled = context_map.get_domain_event("OrderCancelled").unwrap();
+    assert_eq!(cancelled.publishers, vec!["Payment".to_string(), "Order".to_string()]);
+
+    let received = context_map.get_domain_event("PaymentReceived").unwrap();
+    assert_eq!(received.description, " the payment is received from the bank ");
+    assert_eq!(received.fields[0].name, "paymentId");
+
+    assert_eq!(context_map.events_of("Payment").len(), 2);
+  }
+
+  #[test]
+  fn services() {
+    let str = r#"
+Aggregate Order {
+  Repository OrderRepository {
+    findById(id: String): Order;
+  }
+}
+
+ApplicationService OrderService {
+  """ place and cancel orders """
+  placeOrder(request: PlaceOrderRequest): Order;
+}
+
+impl PlaceOrder {
+  endpoint {
+    POST "/orders";
+  }
+
+  flow {
+    via OrderService::placeOrder(request: PlaceOrderRequest) receive order: Order;
+    via OrderRepository::save(order: Order) receive order: Order;
+    via PaymentClient::pay(order: Order) receive payment: Payment;
+  }
+}
+"#;
+
+    let context_map = MirTransform::mir(str).unwrap();
+
+    let repository = context_map.get_service("OrderRepository").unwrap();
+    assert_eq!(repository.service_type, fkl_mir::ServiceType::Repository);
+    assert_eq!(repository.methods[0].parameters[0].name, "id");
+    assert_eq!(context_map.services_of("Order").len(), 1);
+
+    let service = context_map.get_service("OrderService").unwrap();
+    assert_eq!(service.service_type, fkl_mir::ServiceType::ApplicationService);
+    assert_eq!(service.description, " place and cancel orders ");
+
+    let unresolved: Vec<String> = context_map.unresolved_calls().iter()
+      .map(|(_, call)| format!("{}::{}", call.object, call.method))
+      .collect();
+    assert_eq!(unresolved, vec!["OrderRepository::save", "PaymentClient::pay"]);
+
+    let (_, diagnostics) = MirTransform::mir_with_diagnostics(str).unwrap();
+    let messages: Vec<String> = diagnostics.iter().map(|d| d.message.clone()).filter(|it| it.contains("called in impl")).collect();
+    assert_eq!(messages, vec![
+      "method `OrderRepository::save` called in impl `PlaceOrder` is not declared",
+      "service `PaymentClient` called in impl `PlaceOrder` is not declared",
+    ]);
+  }
+
+  #[test]
+  fn modules() {
+    let str = r#"
+Context Ordering {
+  Module order {
+    Aggregate Order {
+      DomainEvent OrderPlaced;
+    }
+  }
+  Aggregate Customer;
+}
+
+Module payment {
+  context: Ordering;
+  dependencies: [order];
+  Aggregate Payment {
+    Entity Payment {}
+  }
+}
+"#;
+
+    let context_map = MirTransform::mir(str).unwrap();
+    let context = &context_map.contexts[0];
+
+    let names: Vec<&str> = context.aggregates.iter().map(|aggregate| aggregate.name.as_str()).collect();
+    assert_eq!(names, vec!["Customer", "Order", "Payment"]);
+    assert_eq!(context.aggregates[2].entities[0].name, "Payment");
+
+    assert_eq!(context.modules.len(), 2);
+    assert_eq!(context.module_of("Order").unwrap().name, "order");
+    assert_eq!(context.module_of("Payment").unwrap().dependencies, vec!["order".to_string()]);
+    assert!(context.module_of("Customer").is_none());
+
+    assert_eq!(context_map.domain_events[0].publishers, vec!["Order".to_string()]);
+  }
+
+  #[test]
+  fn module_without_context() {
+    let str = r#"
+Context Billing {}
+
+Module payment {
+  Aggregate Payment {}
+}
+
+Module billing {
+  context: Billingg;
+  Aggregate Invoice {}
+}
+"#;
+
+    let (mut context_map, diagnostics) = MirTransform::mir_with_diagnostics(str).unwrap();
+    let messages: Vec<String> = diagnostics.iter().map(|d| format!("{}: {}: {}", d.span.line, d.severity, d.message)).collect();
+    assert_eq!(messages, vec![
+      "4: error: module `payment` is not in any context, declare it by `context: <name>;`",
+      "8: error: context `Billingg` of module `billing` is not declared",
+    ]);
+
+    let payment = context_map.contexts.iter().find(|context| context.name == "payment").unwrap();
+    assert_eq!(payment.aggregates[0].name, "Payment");
+    let billing = context_map.contexts.iter().find(|context| context.name == "Billingg").unwrap();
+    assert_eq!(billing.module_of("Invoice").unwrap().name, "billing");
+
+    context_map.strip_spans();
+    assert!(context_map.contexts.iter().flat_map(|context| context.modules.iter()).all(|module| module.span.is_empty()));
+  }
+
+  #[test]
+  fn generic_field_types() {
+    let str = r#"
+ValueObject Cart {
+  struct {
+    items: List<Map<String, Order>>;
+    coupon: Coupon?;
+    tags: String[];
+  }
+}
+"#;
+
+    let context_map = MirTransform::mir(str).unwrap();
+    let cart = context_map.get_value_object("Cart").unwrap();
+
+    assert_eq!(cart.fields[0].type_type, "List<Map<String, Order>>");
+    assert_eq!(cart.fields[0].type_expr, TypeExpr::Generic("List".to_string(), vec![
+      TypeExpr::Generic("Map".to_string(), vec![
+        TypeExpr::Named("String".to_string()),
+        TypeExpr::Named("Order".to_string()),
+      ])
+    ]));
+    assert_eq!(cart.fields[1].type_expr, TypeExpr::Optional(Box::new(TypeExpr::Named("Coupon".to_string()))));
+    assert_eq!(cart.fields[2].type_expr, TypeExpr::Array(Box::new(TypeExpr::Named("String".to_string()))));
+  }
+
+  #[test]
+  fn enums() {
+    let str = r#"
+Context Ticket {
+  Aggregate Ticket {
+    Entity Ticket {
+      struct {
+        status: Status = Status.Open;
+        priority: Priority = Urgent;
+      }
+    }
+
+    enum Priority { Low, High }
+  }
+}
+
+enum Status {
+  """ the lifecycle of ticket """
+  Open,
+  Closed
+}
+"#;
+
+    let context_map = MirTransform::mir(str).unwrap();
+
+    let priority = context_map.get_enum("Priority").unwrap();
+    assert_eq!(priority.owner, "Ticket");
+    let status = context_map.get_enum("Status").unwrap();
+    assert_eq!(status.variants, vec!["Open".to_string(), "Closed".to_string()]);
+    assert_eq!(status.description, " the lifecycle of ticket ");
+
+    let invalid: Vec<String> = context_map.invalid_enum_defaults().iter()
+      .map(|(owner, field)| format!("{}.{}", owner, field.name))
+      .collect();
+    assert_eq!(invalid, vec!["Ticket.priority"]);
+
+    let (_, diagnostics) = MirTransform::mir_with_diagnostics(str).unwrap();
+    let diagnostic = diagnostics.iter().find(|d| d.message.starts_with("default")).unwrap();
+    assert_eq!(diagnostic.message, "default `Urgent` of field `Ticket.priority` is not a variant of enum `Priority`");
+    assert_ne!(diagnostic.span.line, 0);
+  }
+
+  #[test]
+  fn field_validations() {
+    let str = r#"
+struct CreateUser {
+  name: String @required @length(1, 64);
+  age: Int @min(18) = 18;
+  email: String @pattern("^.+@.+$") @unknown;
+  score: Float @range(-1, 1.5) @min("abc");
+  endDate: Date @compare("~=", startDate);
+}
+"#;
+
+    let (context_map, diagnostics) = MirTransform::mir_with_diagnostics(str).unwrap();
+    let fields = &context_map.get_struct("CreateUser").unwrap().fields;
+
+    assert_eq!(fields[0].validations, vec![
+      Validation::Required(true),
+      Validation::Length(LengthValidation { min: Some(1), max: Some(64) }),
+    ]);
+    assert_eq!(fields[1].validations, vec![Validation::Range(RangeValidation { min: Some(18.0), max: None })]);
+    assert_eq!(fields[1].initializer, Some("18".to_string()));
+    assert_eq!(fields[2].validations, vec![Validation::Regex(RegexValidation { pattern: "^.+@.+$".to_string() })]);
+    assert_eq!(fields[3].validations, vec![Validation::Range(RangeValidation { min: Some(-1.0), max: Some(1.5) })]);
+
+    let messages: Vec<String> = diagnostics.iter().map(|d| format!("{}: {}", d.severity, d.message)).collect();
+    assert_eq!(messages, vec![
+      "error: unknown constraint `@unknown` of field `email`",
+      "error: argument `abc` of `@min` should be a number",
+      "error: unknown compare operator `~=` of field `endDate`",
+    ]);
+  }
+
+  #[test]
+  fn component() {
+    let str = r#"
+Component SalesComponent {
+  """ the sales service """
+  name = 'Sample Phodal';
+  type: "Service";
+  Aggregate SalesOrder, Customer;
+  Entity Invoice;
+}
+"#;
+
+    let mut context_map = MirTransform::mir(str).unwrap();
+    context_map.strip_spans();
+
+    assert_eq!(context_map.components, vec![Component {
+      name: "SalesComponent".to_string(),
+      description: " the sales service ".to_string(),
+      component_type: ComponentType::Service,
+      attributes: vec![
+        ComponentAttribute { key: "name".to_string(), value: vec!["Sample Phodal".to_string()] },
+        ComponentAttribute { key: "type".to_string(), value: vec!["Service".to_string()] },
+      ],
+      aggregates: vec!["SalesOrder".to_string(), "Customer".to_string()],
+      entities: vec!["Invoice".to_string()],
+      ..Default::default()
+    }]);
+  }
+
+  #[test]
+  fn value_objects() {
+    let str = r#"
+Context Cart {
+  Aggregate Cart {
+    Entity Cart {
+      ValueObject CartId {
+        constructor(id: String)
+      }
+    }
+    ValueObject CartTotal {
+      struct {
+        amount: Money;
+        currency: String;
       }
+    }
+    ValueObject Money;
+  }
+}
+
+ValueObject Money {
+  constructor(amount: BigDecimal, currency: String)
+}
+"#;
+
+    let mut context_map = MirTransform::mir(str).unwrap();
+    context_map.strip_spans();
+
+    let aggregate = &context_map.contexts[0].aggregates[0];
+    assert_eq!(aggregate.entities.len(), 1);
+    assert_eq!(aggregate.entities[0].value_objects, vec![ValueObject {
+      name: "CartId".to_string(),
+      fields: vec![Field { name: "id".to_string(), type_type: "String".to_string(), type_expr: TypeExpr::Named("String".to_string()), ..Default::default() }],
+      constructor: vec![Field { name: "id".to_string(), type_type: "String".to_string(), type_expr: TypeExpr::Named("String".to_string()), ..Default::default() }],
+      owner: "Cart".to_string(),
+      ..Default::default()
+    }]);
+
+    let names: Vec<(String, String)> = aggregate.value_objects.iter().map(|vo| (vo.name.clone(), vo.owner.clone())).collect();
+    assert_eq!(names, vec![("CartTotal".to_string(), "Cart".to_string()), ("Money".to_string(), "Cart".to_string())]);
+    assert_eq!(aggregate.value_objects[0].fields.len(), 2);
+    assert!(aggregate.value_objects[0].constructor.is_empty());
+    assert_eq!(aggregate.value_objects[1].constructor.len(), 2);
+
+    assert_eq!(context_map.value_objects.len(), 1);
+    assert_eq!(context_map.value_objects[0].owner, "");
+    assert_eq!(context_map.get_value_object("CartId").unwrap().owner, "Cart");
+  }
+
+  #[test]
+  fn source_spans() {
+    let str = r#"ContextMap TicketBooking {
+  Reservation -> Cinema;
+}
+
+Context Cinema {
+  Aggregate Cinema {
+    Entity Cinema {
+      Struct {
+        id: String;
+      }
+    }
+  }
+}
+
+impl CinemaCreated {
+  endpoint {
+    POST "/book/{id}";
+  }
+}
+"#;
+
+    let mut context_map = MirTransform::mir(str).unwrap();
+    assert_eq!(context_map.relations[0].span, Span::new(29, 51, 2, 3));
+
+    let context = context_map.contexts.iter().find(|context| context.name == "Cinema").unwrap();
+    assert_eq!(context.span, Span::new(55, 167, 5, 1));
+    assert_eq!(context.aggregates[0].span, Span::new(74, 165, 6, 3));
+
+    let entity = &context.aggregates[0].entities[0];
+    assert_eq!(entity.span, Span::new(97, 161, 7, 5));
+    assert_eq!(entity.fields[0].span, Span::new(136, 146, 9, 9));
+
+    match &context_map.implementations[0] {
+      Implementation::PublishHttpApi(api) => {
+        assert_eq!(api.span.line, 15);
+        assert_eq!(api.span.column, 1);
+      }
+      _ => panic!("should be http api"),
+    }
+
+    context_map.strip_spans();
+    let context = context_map.contexts.iter().find(|context| context.name == "Cinema").unwrap();
+    assert!(context.span.is_empty());
+    assert!(context.aggregates[0].entities[0].fields[0].span.is_empty());
+  }
+
+  #[test]
+  fn merge_included_files() {
+    let mut path: PathBuf = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("test_data/include/main.fkl");
+
+    let context_map = MirTransform::mir_from_file(&path).unwrap();
+    assert_eq!(context_map.name, "TicketBooking");
+    assert_eq!(context_map.relations.len(), 2);
+
+    let names: Vec<String> = context_map.contexts.iter().map(|context| context.name.clone()).collect();
+    assert_eq!(names, vec!["Movie".to_string(), "Cinema".to_string(), "Reservation".to_string()]);
+
+    let cinema = &context_map.contexts[1];
+    assert_eq!(cinema.aggregates[0].entities.len(), 3);
+    assert_eq!(cinema.span.file, Some("cinema/cinema.fkl".to_string()));
+    assert_eq!(cinema.span.line, 3);
+    assert_eq!(context_map.relations[0].span.file, Some("main.fkl".to_string()));
+  }
+
+  #[test]
+  fn commands_and_queries() {
+    let str = r#"
+Context Ordering {
+  Aggregate Order;
+}
+
+Aggregate Order {
+  Entity Order;
+
+  Query FindOrders {
+    struct {
+      status: String;
+    }
+  }
+}
+
+Command PlaceOrder {
+  aggregate: Order;
+  struct {
+    items: List<String>;
+  }
+  DomainEvent OrderPlaced;
+}
+
+impl PlaceOrderApi {
+  command: PlaceOrder;
+  endpoint {
+    POST "/orders";
+  }
+}
+
+impl FindOrdersApi {
+  aggregate: Order;
+  query: FindOrders;
+  endpoint {
+    POST "/orders/search";
+  }
+}
+"#;
+
+    let context_map = MirTransform::mir(str).unwrap();
+    let names: Vec<String> = context_map.commands.iter().map(|it| format!("{}({})", it.command_type, it.name)).collect();
+    assert_eq!(names, vec!["Query(FindOrders)", "Command(PlaceOrder)"]);
+    assert_eq!(context_map.commands_of("Order").len(), 2);
+
+    let place_order = context_map.get_command("PlaceOrder").unwrap();
+    assert_eq!(place_order.fields[0].type_type, "List<String>");
+    assert_eq!(place_order.events, vec!["OrderPlaced".to_string()]);
+    assert_eq!(context_map.get_domain_event("OrderPlaced").unwrap().publishers, vec!["Order".to_string()]);
+
+    match &context_map.implementations[0] {
+      Implementation::PublishHttpApi(api) => {
+        assert_eq!(api.command, "PlaceOrder");
+        assert_eq!(api.target_aggregate, "Order");
+      }
+      _ => panic!("http api expected"),
+    }
+
+    let mismatched: Vec<(String, String)> = context_map.mismatched_commands().into_iter()
+      .map(|(name, command)| (name, command.name))
+      .collect();
+    assert_eq!(mismatched, vec![("FindOrdersApi".to_string(), "FindOrders".to_string())]);
+
+    let (_, diagnostics) = MirTransform::mir_with_diagnostics(str).unwrap();
+    let diagnostic = diagnostics.iter().find(|d| d.message.starts_with("endpoint of impl")).unwrap();
+    assert_eq!(diagnostic.message, "endpoint of impl `FindOrdersApi` doesn't match query `FindOrders` of aggregate `Order`");
+    assert_ne!(diagnostic.span.line, 0);
+  }
+
+  #[test]
+  fn context_declared_in_context_map() {
+    let str = r#"
+ContextMap Mall {
+  Context A {
+    Aggregate Cart;
+  }
+  A -> B;
+}
+"#;
+
+    let (context_map, diagnostics) = MirTransform::mir_with_diagnostics(str).unwrap();
+    let messages: Vec<String> = diagnostics.iter().map(|d| format!("{}: {}", d.severity, d.message)).collect();
+    assert_eq!(messages, vec![
+      "warning: aggregate `Cart` is not declared, an empty aggregate is created",
+      "warning: context `B` is not declared",
+    ]);
+    assert_eq!(context_map.contexts[0].aggregates[0].name, "Cart");
+  }
+
+  #[test]
+  fn context_redeclared_in_context_map() {
+    let str = r#"
+Context Sales {
+  Aggregate Order;
+}
+
+ContextMap Shop {
+  @owner("billing-team")
+  Context Sales { }
+  Sales -> Billing;
+}
+"#;
+
+    let context_map = MirTransform::mir(str).unwrap();
+    let sales = &context_map.contexts[0];
+    assert_eq!(sales.aggregates[0].name, "Order");
+    assert_eq!(sales.annotations, vec![Annotation::new("owner", vec!["billing-team".to_string()])]);
+
+    let decls = parse(str).unwrap();
+    let mut merged = MirTransform::new();
+    for declaration in &decls {
+      let lowered = MirTransform::lower_alone(None, line_starts(str), declaration);
+      merged.merge(declaration, &lowered);
+    }
+    let mut lowered = MirTransform::new();
+    lowered.set_source(None, str);
+    lowered.lower_decls(decls);
+    assert_eq!(merged.contexts, lowered.contexts);
+  }
+
+  #[test]
+  fn entity_states() {
+    let str = r#"
+Context Ordering {
+  Aggregate Order;
+}
+
+Aggregate Order {
+  Entity Order;
+}
+
+Entity Order {
+  states {
+    Draft -> Submitted on Submit;
+    Submitted -> Approved on Approve;
+    Archived -> Draft on Restore;
+  }
+}
+"#;
+
+    let (context_map, diagnostics) = MirTransform::mir_with_diagnostics(str).unwrap();
+    let states = context_map.get_entity("Order").unwrap().states.unwrap();
+    assert_eq!(states.initial, "Draft");
+    assert_eq!(states.states, vec!["Draft", "Submitted", "Approved", "Archived"]);
+    assert_eq!(states.transitions[0].trigger, "Submit");
+    assert_eq!(states.transitions[0].span.line, 12);
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, "state `Archived` of entity `Order` is unreachable from the initial state `Draft`");
+    assert_eq!(diagnostics[0].span.line, 14);
+
+    let mut context_map = context_map;
+    context_map.strip_spans();
+    assert!(context_map.get_entity("Order").unwrap().states.unwrap().transitions.iter().all(|it| it.span.is_empty()));
+  }
+
+  #[test]
+  fn nondeterministic_states() {
+    let str = r#"
+Entity Order {
+  states {
+    Draft -> Submitted on Submit;
+    Draft -> Rejected on Submit;
+    Draft -> Cancelled;
+    Draft -> Cancelled;
+    Draft -> Archived;
+  }
+}
+"#;
+
+    let (_, diagnostics) = MirTransform::mir_with_diagnostics(str).unwrap();
+    let messages: Vec<String> = diagnostics.iter().map(|d| format!("{}: {}: {}", d.span.line, d.severity, d.message)).collect();
+    assert_eq!(messages, vec![
+      "5: error: state `Draft` of entity `Order` goes to both `Submitted` and `Rejected` on `Submit`",
+      "7: error: duplicate transition from `Draft` to `Cancelled` of entity `Order`",
+    ]);
+  }
+
+  #[test]
+  fn sagas() {
+    let str = r#"
+ContextMap Shopping {
+  Ordering -> Inventory;
+}
+
+Context Ordering {}
+Context Inventory {}
+
+Saga PlaceOrder {
+  """ reserve the stock and charge the payment """
+  context: Ordering;
+  trigger: [OrderPlaced, OrderRetried];
+
+  step ReserveStock {
+    context: Inventory;
+    compensate: ReleaseStock;
+  }
+
+  step ChargePayment {
+    context: Payment;
+  }
+}
+"#;
+
+    let (context_map, diagnostics) = MirTransform::mir_with_diagnostics(str).unwrap();
+    let saga = context_map.get_saga("PlaceOrder").unwrap();
+    assert_eq!(saga.description, " reserve the stock and charge the payment ");
+    assert_eq!(saga.triggers, vec!["OrderPlaced".to_string(), "OrderRetried".to_string()]);
+    assert_eq!(saga.steps[0].compensation, "ReleaseStock");
+    assert_eq!(saga.contexts(), vec!["Ordering", "Inventory", "Payment"]);
+
+    let messages: Vec<String> = diagnostics.iter().map(|d| format!("{}: {}", d.severity, d.message)).collect();
+    assert_eq!(messages, vec![
+      "warning: context `Payment` is not declared".to_string(),
+      "error: step `ChargePayment` of saga `PlaceOrder` talks to context `Payment`, which has no relation with context `Ordering`".to_string(),
+    ]);
+  }
+
+  #[test]
+  fn semantic_diagnostics() {
+    let str = r#"
+ContextMap TicketBooking {
+  Reservation -> Cinema;
+}
+
+Context Cinema {
+  Aggregate Cinema, Screening;
+}
+
+Aggregate Cinema {
+  Entity Cinema, Seat;
+}
+
+Entity Seat {
+  struct {
+    id: UUID;
+    room: Room;
+  }
+}
+
+Entity Seat {}
+
+impl CinemaCreated {
+  aggregate: Show;
+}
+"#;
+
+    let (_, diagnostics) = MirTransform::mir_with_diagnostics(str).unwrap();
+    let messages: Vec<String> = diagnostics.iter().map(|d| format!("{}: {}", d.severity, d.message)).collect();
+
+    assert_eq!(messages, vec![
+      "error: duplicate entity `Seat`, first declared at line 14".to_string(),
+      "warning: context `Reservation` is not declared".to_string(),
+      "warning: aggregate `Screening` is not declared, an empty aggregate is created".to_string(),
+      "warning: entity `Cinema` is not declared".to_string(),
+      "warning: unknown type `Room`, it's not a builtin type or declared struct, entity, value object or enum".to_string(),
+      "error: the target aggregate `Show` of implementation is not declared".to_string(),
+    ]);
+  }
+
+  #[test]
+  fn qualified_names() {
+    let str = r#"
+Context Sales {
+  Aggregate Order {
+    Entity Customer {
+      Struct {
+        name: String;
+      }
+    }
+  }
+}
+
+Context Billing {
+  Aggregate Invoice {
+    Entity Customer {
+      Struct {
+        account: String;
+      }
+    }
+    Entity Payer {
+      Struct {
+        customer: Customer;
+      }
+    }
+  }
+}
+
+Context Shipping {
+  Aggregate Parcel {
+    Entity Sales.Order.Customer, Billing.Order.Customer;
+  }
+}
+
+Entity Receiver {
+  Struct {
+    customer: Customer;
+    buyer: Sales.Customer;
+  }
+}
+
+impl ShipParcel {
+  aggregate: Shipping.Parcel;
+}
+"#;
+
+    let (context_map, diagnostics) = MirTransform::mir_with_diagnostics(str).unwrap();
+    let messages: Vec<String> = diagnostics.iter().map(|d| format!("{}: {}: {}", d.span.line, d.severity, d.message)).collect();
+    assert_eq!(messages, vec![
+      "29: warning: entity `Billing.Order.Customer` is not declared".to_string(),
+      "35: warning: ambiguous type `Customer`, it's declared as `Billing.Invoice.Customer`, `Sales.Order.Customer`, use the qualified name instead".to_string(),
+    ]);
+
+    let parcel = &context_map.contexts[2].aggregates[0];
+    assert_eq!(parcel.entities[0].name, "Customer");
+    assert_eq!(parcel.entities[0].fields[0].name, "name");
+    assert_eq!(parcel.entities[1].name, "Customer");
+    assert!(parcel.entities[1].fields.is_empty());
+    assert_eq!(context_map.get_entity("Billing.Customer").unwrap().fields[0].name, "account");
+    assert_eq!(context_map.get_entity("Sales.Order.Customer").unwrap().fields[0].name, "name");
+
+    match &context_map.implementations[0] {
+      Implementation::PublishHttpApi(api) => assert_eq!(api.target_aggregate, "Parcel"),
+      _ => panic!("should be http api"),
+    }
+  }
+
+  #[test]
+  fn qualified_type_of_other_owner() {
+    let str = r#"
+ContextMap Shop {
+  Sales -> Billing;
+}
+
+Context Sales {
+  Aggregate Order {
+    Entity Order {
+      Struct {
+        id: String;
+      }
+    }
+  }
+}
+
+Entity Invoice {
+  Struct {
+    wrong: Billing.Order;
+    right: Sales.Order.Order;
+    created: java.time.Instant;
+  }
+}
+"#;
+
+    let (_, diagnostics) = MirTransform::mir_with_diagnostics(str).unwrap();
+    let messages: Vec<String> = diagnostics.iter().map(|d| format!("{}: {}: {}", d.span.line, d.severity, d.message)).collect();
+    assert_eq!(messages, vec![
+      "3: warning: context `Billing` is not declared".to_string(),
+      "18: warning: type `Billing.Order` is not declared".to_string(),
+    ]);
+  }
+
+  #[test]
+  fn annotations() {
+    let str = r#"
+@owner("sales")
+Context Sales {
+  Aggregate Order;
+}
+
+@table(orders)
+Aggregate Order {
+  Entity Order;
+}
+
+@deprecated
+Entity Order {
+  Struct {
+    @column("order_id") id: String;
+  }
+}
+
+@stability(beta)
+impl PlaceOrder {
+  aggregate: Order;
+}
+"#;
+
+    let context_map = MirTransform::mir(str).unwrap();
+    let context = &context_map.contexts[0];
+    assert_eq!(context.annotations, vec![fkl_mir::Annotation::new("owner", vec!["sales".to_string()])]);
+
+    let aggregate = &context.aggregates[0];
+    assert_eq!(fkl_mir::Annotation::find(&aggregate.annotations, "table").unwrap().first_value(), Some("orders"));
+
+    let entity = &aggregate.entities[0];
+    assert!(fkl_mir::Annotation::find(&entity.annotations, "deprecated").is_some());
+    assert_eq!(entity.fields[0].annotations[0].key, "column");
+
+    match &context_map.implementations[0] {
+      Implementation::PublishHttpApi(api) => assert_eq!(api.annotations[0].key, "stability"),
+      _ => panic!("should be http api"),
+    }
+  }
+
+  #[test]
+  fn context_map_state() {
+    let context_map = MirTransform::mir(r#"
+ContextMap Shop {
+  state: AsIs;
+  Sales -> Billing;
+}
+"#).unwrap();
+    assert_eq!(context_map.state, fkl_mir::ContextState::AsIs);
+
+    let context_map = MirTransform::mir("ContextMap Shop { Sales -> Billing; }").unwrap();
+    assert_eq!(context_map.state, fkl_mir::ContextState::ToBe);
+  }
+
+  #[test]
+  fn endpoint_params() {
+    let str = r#"
+impl GetOrder {
+  endpoint {
+    GET "/orders/{id}/items/{itemId}";
+    path { id: Long; sku: String; }
+    query { page: Int = 1; keyword: String?; }
+    header { @name("X-Tenant") tenant: Tenant; }
+    response: Order;
+  }
+}
+"#;
+
+    let (context_map, diagnostics) = MirTransform::mir_with_diagnostics(str).unwrap();
+    let endpoint = match &context_map.implementations[0] {
+      Implementation::PublishHttpApi(api) => &api.endpoint,
+      _ => panic!("should be http api"),
+    };
+    assert_eq!(endpoint.placeholders(), vec!["id", "itemId"]);
+    assert_eq!(endpoint.path_param("id").unwrap().type_expr, TypeExpr::Named("Long".to_string()));
+    assert_eq!(endpoint.query_params[0].initializer, Some("1".to_string()));
+    assert!(!HttpEndpoint::is_required(&endpoint.query_params[1]));
+    assert_eq!(HttpEndpoint::header_name(&endpoint.headers[0]), "X-Tenant");
+
+    let messages: Vec<String> = diagnostics.iter().map(|d| format!("{}: {}", d.severity, d.message)).collect();
+    assert_eq!(messages, vec![
+      "warning: unknown type `Tenant`, it's not a builtin type or declared struct, entity, value object or enum".to_string(),
+      "warning: the type of path parameter `itemId` in impl `GetOrder` is not declared, like `path { itemId: String; }`".to_string(),
+      "warning: path parameter `sku` in impl `GetOrder` is not used in `/orders/{id}/items/{itemId}`".to_string(),
+    ]);
+  }
+
+  #[test]
+  fn endpoint_responses() {
+    let str = r#"
+impl GetOrder {
+  endpoint {
+    GET "/orders";
+    responses {
+      200: List<Order>;
+      404: ErrorBody as "application/problem+json";
+      404: ErrorBody;
+      999: ValidationErrors;
+    }
+  }
+}
+
+struct ErrorBody {
+  message: String;
+}
+"#;
+
+    let (context_map, diagnostics) = MirTransform::mir_with_diagnostics(str).unwrap();
+    let endpoint = match &context_map.implementations[0] {
+      Implementation::PublishHttpApi(api) => &api.endpoint,
+      _ => panic!("should be http api"),
+    };
+    assert_eq!(endpoint.response.as_ref().unwrap().name, "List<Order>");
+    assert_eq!(endpoint.responses.len(), 4);
+    assert_eq!(endpoint.response_of(404).unwrap().media_type(), "application/problem+json");
+    assert_eq!(endpoint.response_of(200).unwrap().media_type(), "application/json");
+
+    let messages: Vec<String> = diagnostics.iter().map(|d| format!("{}: {}", d.severity, d.message)).collect();
+    assert_eq!(messages, vec![
+      "warning: unknown type `Order`, it's not a builtin type or declared struct, entity, value object or enum".to_string(),
+      "warning: unknown type `ValidationErrors`, it's not a builtin type or declared struct, entity, value object or enum".to_string(),
+      "error: duplicate response of status `404` in impl `GetOrder`".to_string(),
+      "error: `999` of impl `GetOrder` is not a valid status code".to_string(),
     ]);
   }
 }
@@ -2,7 +2,7 @@ use fkl_codegen_dot::graph::Graph;
 use fkl_codegen_dot::helper::naming::cluster_name;
 use fkl_codegen_dot::node::Node;
 use fkl_codegen_dot::subgraph::Subgraph;
-use fkl_mir::{ConnectionDirection, ContextMap, ContextRelation};
+use fkl_mir::{Aggregate, BoundedContext, ConnectionDirection, ContextMap, ContextRelation, Entity, Saga, SagaStep};
 
 use crate::bc_edge_style;
 use crate::bc_edge_style::BcEdgeStyle;
@@ -11,22 +11,21 @@ pub(crate) fn to_dot(context_map: &ContextMap) -> String {
   let mut graph = Graph::new(&context_map.name);
   graph.use_default_style();
 
+  // contexts in the same subdomain are drawn inside the domain cluster
+  let mut domain_graphs: Vec<(String, Subgraph)> = vec![];
   for bc in &context_map.contexts {
-    let name = &bc.name;
-    let mut subgraph = Subgraph::new(&bc.name, &format!("{}(Context)", name));
-    subgraph.set_depth(1);
-    for aggregate in &bc.aggregates {
-      let mut aggregate_graph = Subgraph::new(&format!("aggregate_{}", aggregate.name), &format!("{}(Aggregate)", aggregate.name));
-      aggregate_graph.set_depth(2);
-
-      aggregate.entities.iter().for_each(|entity| {
-        aggregate_graph.add_node(Node::label(&format!("entity_{}", entity.name), &entity.name));
-      });
-
-      subgraph.add_subgraph(aggregate_graph);
+    match context_map.domain_of(&bc.name) {
+      Some(domain) => {
+        let label = format!("{}({})", domain.name, domain.subdomain_type);
+        let index = cluster_index(&mut domain_graphs, &domain.name, &format!("domain_{}", domain.name), &label, 1);
+        domain_graphs[index].1.add_subgraph(context_subgraph(bc, context_map, 2));
+      }
+      None => graph.add_subgraph(context_subgraph(bc, context_map, 1)),
     }
+  }
 
-    graph.add_subgraph(subgraph);
+  for (_, domain_graph) in domain_graphs {
+    graph.add_subgraph(domain_graph);
   }
 
   for rel in &context_map.relations {
@@ -36,6 +35,137 @@ pub(crate) fn to_dot(context_map: &ContextMap) -> String {
   format!("{}", graph)
 }
 
+/// the state diagram of entity, the initial state is pointed by a start point, and the final states
+/// are drawn in double circles.
+pub(crate) fn to_state_dot(entity: &Entity) -> String {
+  let mut graph = Graph::new(&entity.name);
+  graph.add_graph_style("rankdir=LR");
+  graph.add_node_style("shape=ellipse");
+
+  if let Some(states) = &entity.states {
+    graph.add_node(Node::styled("_start", "", vec!["shape=point".to_string()]));
+    let final_states = states.final_states();
+    for state in &states.states {
+      match final_states.contains(&state) {
+        true => graph.add_node(Node::styled(state, state, vec!["shape=doublecircle".to_string()])),
+        false => graph.add_node(Node::new(state)),
+      }
+    }
+
+    graph.add_edge("_start", &states.initial);
+    for transition in &states.transitions {
+      match transition.trigger.is_empty() {
+        true => graph.add_edge(&transition.from, &transition.to),
+        false => graph.add_edge_with_style(&transition.from, &transition.to, vec![format!("label=\"{}\"", transition.trigger)]),
+      }
+    }
+  }
+
+  format!("{}", graph)
+}
+
+/// the swimlane graph of saga, each involved context is a lane with its steps and compensations,
+/// the steps are chained in running order from the trigger events.
+pub(crate) fn to_saga_dot(saga: &Saga) -> String {
+  let mut graph = Graph::new(&saga.name);
+  graph.add_graph_style("rankdir=LR");
+  graph.add_node_style("shape=box");
+
+  for trigger in &saga.triggers {
+    graph.add_node(Node::styled(&format!("event_{}", trigger), trigger, vec!["shape=ellipse".to_string()]));
+  }
+
+  // the orchestrating context is the first lane, the lanes without steps are skipped
+  for context in saga.contexts() {
+    let steps: Vec<&SagaStep> = saga.steps.iter().filter(|step| step.context == context).collect();
+    if steps.is_empty() {
+      continue;
+    }
+
+    let mut lane = Subgraph::new(&format!("lane_{}", context), &format!("{}(Context)", context));
+    for step in steps {
+      lane.add_node(Node::label(&format!("step_{}", step.name), &step.name));
+      if !step.compensation.is_empty() {
+        lane.add_node(Node::styled(&format!("compensate_{}", step.name), &step.compensation, vec!["style=dashed".to_string()]));
+      }
+    }
+    graph.add_subgraph(lane);
+  }
+
+  if let Some(first) = saga.steps.first() {
+    for trigger in &saga.triggers {
+      graph.add_edge(&format!("event_{}", trigger), &format!("step_{}", first.name));
+    }
+  }
+
+  for (step, next) in saga.steps.iter().zip(saga.steps.iter().skip(1)) {
+    graph.add_edge(&format!("step_{}", step.name), &format!("step_{}", next.name));
+  }
+
+  for step in saga.steps.iter().filter(|step| !step.compensation.is_empty()) {
+    graph.add_edge_with_style(&format!("step_{}", step.name), &format!("compensate_{}", step.name), vec!["style=dashed".to_string()]);
+  }
+
+  format!("{}", graph)
+}
+
+fn context_subgraph(bc: &BoundedContext, context_map: &ContextMap, depth: usize) -> Subgraph {
+  let mut subgraph = Subgraph::new(&bc.name, &format!("{}(Context)", bc.name));
+  subgraph.set_depth(depth);
+
+  // aggregates in the same component are drawn inside the component cluster
+  let mut component_graphs: Vec<(String, Subgraph)> = vec![];
+  for aggregate in &bc.aggregates {
+    let component = context_map.components.iter()
+      .find(|component| component.aggregates.contains(&aggregate.name));
+
+    match component {
+      Some(component) => {
+        let label = format!("{}(Component)", component.name);
+        let index = cluster_index(&mut component_graphs, &component.name, &format!("component_{}", component.name), &label, depth + 1);
+        component_graphs[index].1.add_subgraph(aggregate_subgraph(aggregate, depth + 2));
+      }
+      None => subgraph.add_subgraph(aggregate_subgraph(aggregate, depth + 1)),
+    }
+  }
+
+  for (_, component_graph) in component_graphs {
+    subgraph.add_subgraph(component_graph);
+  }
+
+  subgraph
+}
+
+/// find the cluster by key, or create a new one
+fn cluster_index(graphs: &mut Vec<(String, Subgraph)>, key: &str, name: &str, label: &str, depth: usize) -> usize {
+  if let Some(index) = graphs.iter().position(|(it, _)| it == key) {
+    return index;
+  }
+
+  let mut subgraph = Subgraph::new(name, label);
+  subgraph.set_depth(depth);
+  graphs.push((key.to_string(), subgraph));
+  graphs.len() - 1
+}
+
+fn aggregate_subgraph(aggregate: &Aggregate, depth: usize) -> Subgraph {
+  let mut aggregate_graph = Subgraph::new(&format!("aggregate_{}", aggregate.name), &format!("{}(Aggregate)", aggregate.name));
+  aggregate_graph.set_depth(depth);
+
+  aggregate.entities.iter().for_each(|entity| {
+    aggregate_graph.add_node(Node::label(&format!("entity_{}", entity.name), &entity.name));
+    entity.value_objects.iter().for_each(|vo| {
+      aggregate_graph.add_node(Node::label(&format!("value_object_{}", vo.name), &vo.name));
+    });
+  });
+
+  aggregate.value_objects.iter().for_each(|vo| {
+    aggregate_graph.add_node(Node::label(&format!("value_object_{}", vo.name), &vo.name));
+  });
+
+  aggregate_graph
+}
+
 fn process_context_edge(graph: &mut Graph, relation: &ContextRelation) {
   let bc_edge_style = bc_edge_style::generate_edge_style(&relation.source_type, &relation.target_type);
   let style = create_graph_edge_style(bc_edge_style);
@@ -79,7 +209,7 @@ fn create_graph_edge_style(bc_style: BcEdgeStyle) -> Vec<String> {
 mod test {
   use fkl_parser::parse;
 
-  use crate::dot_gen::to_dot;
+  use crate::dot_gen::{to_dot, to_saga_dot, to_state_dot};
 
   #[test]
   fn nested_entity() {
@@ -129,6 +259,220 @@ Aggregate Reservation {
   subgraph cluster_user {
     label="User(Context)";
   }
+}"#);
+  }
+
+  #[test]
+  fn value_object_node() {
+    let input = r#"
+Context Cart {
+  Aggregate Cart {
+    Entity Cart {
+      ValueObject CartId
+    }
+    ValueObject CartTotal;
+  }
+}
+
+ValueObject CartTotal {
+  constructor(amount: Float)
+}
+"#;
+
+    let context_map = parse(input).unwrap();
+    let dot = to_dot(&context_map);
+    assert_eq!(dot, r#"digraph  {
+  component=true;layout=fdp;
+  node [shape=box style=filled];
+
+  subgraph cluster_cart {
+    label="Cart(Context)";
+
+    subgraph cluster_aggregate_cart {
+      label="Cart(Aggregate)";
+      entity_Cart [label="Cart"];
+      value_object_CartId [label="CartId"];
+      value_object_CartTotal [label="CartTotal"];
+    }
+  }
+}"#);
+  }
+
+  #[test]
+  fn component_cluster() {
+    let input = r#"
+Context Sales {
+  Aggregate SalesOrder;
+  Aggregate Customer;
+  Aggregate Invoice;
+}
+
+Component SalesComponent {
+  type: "Service";
+  Aggregate SalesOrder, Invoice;
+}
+"#;
+
+    let context_map = parse(input).unwrap();
+    let dot = to_dot(&context_map);
+    assert_eq!(dot, r#"digraph  {
+  component=true;layout=fdp;
+  node [shape=box style=filled];
+
+  subgraph cluster_sales {
+    label="Sales(Context)";
+
+    subgraph cluster_aggregate_customer {
+      label="Customer(Aggregate)";
+    }
+
+    subgraph cluster_component_salescomponent {
+      label="SalesComponent(Component)";
+
+      subgraph cluster_aggregate_salesorder {
+        label="SalesOrder(Aggregate)";
+      }
+
+      subgraph cluster_aggregate_invoice {
+        label="Invoice(Aggregate)";
+      }
+    }
+  }
+}"#);
+  }
+
+  #[test]
+  fn group_contexts_by_domain() {
+    let input = r#"
+ContextMap TicketBooking {
+  Reservation -> Cinema;
+  Reservation -> User;
+}
+
+CoreDomain Ticketing {
+  Context Reservation, Cinema;
+}
+
+GenericDomain Identity {
+  Context User;
+}
+"#;
+
+    let context_map = parse(input).unwrap();
+    let dot = to_dot(&context_map);
+    assert_eq!(dot, r#"digraph TicketBooking {
+  component=true;layout=fdp;
+  node [shape=box style=filled];
+  cluster_reservation -> cluster_cinema;
+  cluster_reservation -> cluster_user;
+
+  subgraph cluster_domain_ticketing {
+    label="Ticketing(CoreDomain)";
+
+    subgraph cluster_cinema {
+      label="Cinema(Context)";
+    }
+
+    subgraph cluster_reservation {
+      label="Reservation(Context)";
+    }
+  }
+
+  subgraph cluster_domain_identity {
+    label="Identity(GenericDomain)";
+
+    subgraph cluster_user {
+      label="User(Context)";
+    }
+  }
+}"#);
+  }
+
+  #[test]
+  fn entity_states() {
+    let input = r#"
+Context Ordering {
+  Aggregate Order {
+    Entity Order {
+      states {
+        Draft -> Submitted on Submit;
+        Submitted -> Approved on Approve;
+        Submitted -> Draft on Reject;
+      }
+    }
+  }
+}
+"#;
+
+    let context_map = parse(input).unwrap();
+    let dot = to_state_dot(&context_map.get_entity("Order").unwrap());
+    assert_eq!(dot, r#"digraph Order {
+  rankdir=LR;
+  node [shape=ellipse];
+  _start [label="" shape=point];
+  Draft [label="Draft"];
+  Submitted [label="Submitted"];
+  Approved [label="Approved" shape=doublecircle];
+  _start -> Draft;
+  Draft -> Submitted [label="Submit"];
+  Submitted -> Approved [label="Approve"];
+  Submitted -> Draft [label="Reject"];
+}"#);
+  }
+
+  #[test]
+  fn saga_swimlanes() {
+    let input = r#"
+ContextMap Shopping {
+  Ordering -> Inventory;
+  Ordering -> Payment;
+}
+
+Saga PlaceOrder {
+  context: Ordering;
+  trigger: OrderPlaced;
+
+  step ReserveStock {
+    context: Inventory;
+    compensate: ReleaseStock;
+  }
+
+  step ChargePayment {
+    context: Payment;
+  }
+
+  step ConfirmOrder {
+    context: Ordering;
+  }
+}
+"#;
+
+    let context_map = parse(input).unwrap();
+    let dot = to_saga_dot(context_map.get_saga("PlaceOrder").unwrap());
+    assert_eq!(dot, r#"digraph PlaceOrder {
+  rankdir=LR;
+  node [shape=box];
+  event_OrderPlaced [label="OrderPlaced" shape=ellipse];
+  event_OrderPlaced -> step_ReserveStock;
+  step_ReserveStock -> step_ChargePayment;
+  step_ChargePayment -> step_ConfirmOrder;
+  step_ReserveStock -> compensate_ReserveStock [style=dashed];
+
+  subgraph cluster_lane_ordering {
+    label="Ordering(Context)";
+    step_ConfirmOrder [label="ConfirmOrder"];
+  }
+
+  subgraph cluster_lane_inventory {
+    label="Inventory(Context)";
+    step_ReserveStock [label="ReserveStock"];
+    compensate_ReserveStock [label="ReleaseStock" style=dashed];
+  }
+
+  subgraph cluster_lane_payment {
+    label="Payment(Context)";
+    step_ChargePayment [label="ChargePayment"];
+  }
 }"#);
   }
 }
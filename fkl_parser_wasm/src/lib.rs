@@ -44,6 +44,30 @@ impl FklParser {
     dot
   }
 
+  /// the state diagram of entity, empty if the entity is not found
+  #[wasm_bindgen]
+  pub fn to_state_dot(&self, entity_name: String) -> String {
+    set_panic_hook();
+
+    let context_map = fkl_parse(&self.str).unwrap();
+    match context_map.get_entity(&entity_name) {
+      Some(entity) => dot_gen::to_state_dot(&entity),
+      None => "".to_string(),
+    }
+  }
+
+  /// the swimlane graph of saga, empty if the saga is not found
+  #[wasm_bindgen]
+  pub fn to_saga_dot(&self, saga_name: String) -> String {
+    set_panic_hook();
+
+    let context_map = fkl_parse(&self.str).unwrap();
+    match context_map.get_saga(&saga_name) {
+      Some(saga) => dot_gen::to_saga_dot(saga),
+      None => "".to_string(),
+    }
+  }
+
   #[wasm_bindgen]
   pub fn parse(&self) -> Result<JsValue, JsValue> {
     set_panic_hook();
@@ -54,8 +78,10 @@ impl FklParser {
         Ok(js_value)
       }
       Err(error) => {
-        let error_msg = error.to_string();
-        Err(serde_wasm_bindgen::to_value(&error_msg)?)
+        match error.as_syntax_error() {
+          Some(syntax_error) => Err(serde_wasm_bindgen::to_value(syntax_error)?),
+          None => Err(serde_wasm_bindgen::to_value(&error.to_string())?),
+        }
       }
     }
   }